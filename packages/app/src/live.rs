@@ -0,0 +1,86 @@
+use core::challenge::Challenge;
+use core::game::Color;
+use core::tournament::SeatAssignment;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use uuid::Uuid;
+
+/// An event pushed to a user over the live channel.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// A game has been created with the user seated as `color`. The seat token is required to act
+    /// on behalf of the seat.
+    GameStarted {
+        game_id: String,
+        color: Color,
+        token: String,
+    },
+
+    /// Another player has challenged the user.
+    ChallengeReceived { challenge: Box<Challenge> },
+
+    /// The recipient of a challenge the user created has declined it.
+    ChallengeDeclined { challenge_id: Uuid },
+}
+
+/// Delivers [LiveEvent]s to connected users. Events for users that are not connected are kept
+/// until they next connect.
+pub struct LiveChannel {
+    subscribers: BTreeMap<Uuid, Vec<UnboundedSender<String>>>,
+    mailboxes: BTreeMap<Uuid, Vec<String>>,
+}
+
+impl LiveChannel {
+    pub fn new() -> LiveChannel {
+        LiveChannel {
+            subscribers: BTreeMap::new(),
+            mailboxes: BTreeMap::new(),
+        }
+    }
+
+    /// Subscribe to the events for the specified user. Any events that were sent while the user
+    /// was not connected are delivered immediately.
+    pub fn subscribe(&mut self, user_id: Uuid) -> UnboundedReceiver<String> {
+        let (sender, receiver) = unbounded_channel();
+        for event in self.mailboxes.remove(&user_id).unwrap_or_default() {
+            let _ = sender.send(event);
+        }
+
+        self.subscribers.entry(user_id).or_default().push(sender);
+        receiver
+    }
+
+    /// Send an event to every connection the specified user has open.
+    pub fn send(&mut self, user_id: Uuid, event: &LiveEvent) {
+        let event = serde_json::to_string(event).unwrap();
+
+        // Drop the connections that have since been closed.
+        let senders = self.subscribers.entry(user_id).or_default();
+        senders.retain(|sender| sender.send(event.clone()).is_ok());
+
+        if senders.is_empty() {
+            self.subscribers.remove(&user_id);
+            self.mailboxes.entry(user_id).or_default().push(event);
+        }
+    }
+
+    /// Send each player their seat in a newly created game.
+    pub fn send_seats(&mut self, seats: Vec<SeatAssignment>) {
+        for seat in seats {
+            let event = LiveEvent::GameStarted {
+                game_id: seat.game_id.to_string(),
+                color: seat.color,
+                token: seat.token,
+            };
+            self.send(seat.user_id, &event);
+        }
+    }
+}
+
+impl Default for LiveChannel {
+    fn default() -> Self {
+        LiveChannel::new()
+    }
+}
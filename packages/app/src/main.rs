@@ -1,27 +1,76 @@
+mod live;
 mod routes;
+mod ticker;
 
-use crate::routes::{delete_game, get_details, get_game, get_games, post_move, put_game};
+use crate::live::LiveChannel;
+use crate::routes::{
+    delete_analysis, delete_analysis_variation, delete_challenge, delete_game, delete_seek,
+    delete_setup_piece, get_analysis, get_analysis_pgn, get_arena, get_arena_standings, get_arenas,
+    get_book, get_challenges, get_details, get_game, get_game_pgn, get_games, get_live, get_me,
+    get_rating_history, get_seeks, get_setup, get_tournament, get_tournament_standings,
+    get_tournaments, get_user, post_abort, post_analysis_cursor, post_analysis_demote,
+    post_analysis_move, post_analysis_pgn, post_analysis_promote, post_arena_join,
+    post_arena_start, post_arena_withdraw, post_berserk, post_challenge, post_challenge_accept,
+    post_challenge_decline, post_draw_accept, post_draw_decline, post_draw_offer, post_drop,
+    post_fork, post_game_pgn, post_login, post_logout, post_move, post_register, post_resign,
+    post_seat, post_seek, post_tournament_join, post_tournament_round, put_analysis,
+    put_analysis_annotation, put_annotation, put_arena, put_bughouse, put_game, put_setup_piece,
+    put_setup_rights, put_tournament,
+};
 use actix_web::http::header::{
     ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
     CONTENT_TYPE,
 };
 use actix_web::http::Method;
 use actix_web::{middleware, web, App, HttpResponse, HttpServer};
+use core::arena::ArenaManager;
+use core::book::Book;
+use core::challenge::ChallengeManager;
 use core::game_manager::GameManager;
+use core::lobby::Lobby;
+use core::tournament::TournamentManager;
+use core::user_manager::UserManager;
 use std::sync::Mutex;
 
 struct AppState {
     game_manager: Mutex<GameManager>,
+    user_manager: Mutex<UserManager>,
+    lobby: Mutex<Lobby>,
+    challenge_manager: Mutex<ChallengeManager>,
+    tournament_manager: Mutex<TournamentManager>,
+    arena_manager: Mutex<ArenaManager>,
+    live_channel: Mutex<LiveChannel>,
+
+    /// The opening book served by `GET /game/{id}/book`, if one was given.
+    book: Option<Book>,
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     const ADDRESS: (&str, u16) = ("127.0.0.1", 8080);
 
+    // A Polyglot opening book may be given as the path in the BOOK environment variable.
+    let book = match std::env::var("BOOK") {
+        Ok(path) => Some(
+            Book::open(&path)
+                .map_err(|e| std::io::Error::other(format!("cannot load {}: {}", path, e)))?,
+        ),
+        Err(_) => None,
+    };
+
     let state = web::Data::new(AppState {
         game_manager: Mutex::new(GameManager::new()),
+        user_manager: Mutex::new(UserManager::new()),
+        lobby: Mutex::new(Lobby::new()),
+        challenge_manager: Mutex::new(ChallengeManager::new()),
+        tournament_manager: Mutex::new(TournamentManager::new()),
+        arena_manager: Mutex::new(ArenaManager::new()),
+        live_channel: Mutex::new(LiveChannel::new()),
+        book,
     });
 
+    actix_web::rt::spawn(ticker::run(state.clone()));
+
     let server = HttpServer::new(move || {
         App::new()
             .app_data(state.clone())
@@ -35,9 +84,66 @@ async fn main() -> std::io::Result<()> {
             .service(get_details)
             .service(get_games)
             .service(put_game)
+            .service(put_bughouse)
             .service(get_game)
+            .service(get_setup)
+            .service(put_setup_piece)
+            .service(delete_setup_piece)
+            .service(put_setup_rights)
             .service(delete_game)
             .service(post_move)
+            .service(post_drop)
+            .service(post_seat)
+            .service(post_resign)
+            .service(post_draw_offer)
+            .service(post_draw_accept)
+            .service(post_draw_decline)
+            .service(post_abort)
+            .service(post_berserk)
+            .service(post_fork)
+            .service(put_annotation)
+            .service(get_game_pgn)
+            .service(get_book)
+            .service(post_game_pgn)
+            .service(post_register)
+            .service(post_login)
+            .service(post_logout)
+            .service(get_me)
+            .service(get_user)
+            .service(get_rating_history)
+            .service(get_live)
+            .service(get_seeks)
+            .service(post_seek)
+            .service(delete_seek)
+            .service(get_challenges)
+            .service(post_challenge)
+            .service(post_challenge_accept)
+            .service(post_challenge_decline)
+            .service(delete_challenge)
+            .service(get_tournaments)
+            .service(put_tournament)
+            .service(get_tournament)
+            .service(get_tournament_standings)
+            .service(post_tournament_join)
+            .service(post_tournament_round)
+            .service(get_arenas)
+            .service(put_arena)
+            .service(get_arena)
+            .service(get_arena_standings)
+            .service(post_arena_join)
+            .service(post_arena_withdraw)
+            .service(post_arena_start)
+            .service(put_analysis)
+            .service(get_analysis)
+            .service(delete_analysis)
+            .service(get_analysis_pgn)
+            .service(post_analysis_pgn)
+            .service(put_analysis_annotation)
+            .service(post_analysis_move)
+            .service(post_analysis_cursor)
+            .service(post_analysis_promote)
+            .service(post_analysis_demote)
+            .service(delete_analysis_variation)
             .default_service(web::route().method(Method::OPTIONS).to(HttpResponse::Ok))
     })
     .bind(ADDRESS)?
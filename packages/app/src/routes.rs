@@ -1,14 +1,41 @@
+use crate::live::LiveEvent;
 use crate::AppState;
-use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
-use core::game::Game;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::web::Bytes;
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use core::analysis::Analysis;
+use core::annotation::Annotation;
+use core::arena::Arena;
+use core::challenge::Challenge;
+use core::chess960;
+use core::error::{
+    AccountError, ActionError, AnalysisError, AnnotationError, ArenaError, ChallengeError,
+    ForkError, SetupError,
+};
+use core::game::Color::{Black, White};
+use core::game::{Color, Game, GameOptions, Piece, PieceKind, Variant, Viewer};
+use core::lobby::{ColorPreference, Pairing, RatingRange, Seek};
 use core::moves::Position;
-use serde::Serialize;
+use core::pgn;
+use core::time_control::TimeControlCategory;
+use core::tournament::{Tournament, TournamentFormat};
+use core::user_manager::{User, UserManager};
+use futures_util::stream;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// The header used to supply a session token (as returned when logging in).
+const SESSION_TOKEN_HEADER: &str = "X-Session-Token";
+
+/// The response to a request for the moves of a game that hides them (see [Game::hides_moves]).
+const HIDDEN_MOVES_MESSAGE: &str = "The moves of a game of Dark Chess are hidden until it is over";
+
 #[derive(Serialize)]
 struct VersionResponse<'a> {
     version: &'a str,
@@ -19,24 +46,114 @@ async fn get_details() -> impl Responder {
     HttpResponse::Ok().body(serde_json::to_string(&VersionResponse { version: VERSION }).unwrap())
 }
 
+#[derive(Deserialize)]
+struct GamesQuery {
+    /// Only list games in which the logged-in user holds a seat.
+    #[serde(default)]
+    mine: bool,
+}
+
 #[get("/games")]
-async fn get_games(data: web::Data<AppState>) -> impl Responder {
+async fn get_games(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    query: web::Query<GamesQuery>,
+) -> impl Responder {
+    let user_id = if query.mine {
+        match authenticate_user(&request, &data) {
+            Some(user) => Some(user.id),
+            None => return HttpResponse::Unauthorized().body("You must be logged in"),
+        }
+    } else {
+        None
+    };
+
     let game_manager = data.game_manager.lock().unwrap();
-    let games = game_manager.get_all_games();
-    HttpResponse::Ok().body(serde_json::to_string(&games).unwrap())
+    let games = match user_id {
+        Some(user_id) => game_manager.get_games_for_user(user_id),
+        None => game_manager.get_all_games(),
+    };
+
+    // The user sees their own games as the player they are seated as.
+    let games: Vec<_> = games.iter().map(|game| game.lock().unwrap()).collect();
+    let views: Vec<_> = games
+        .iter()
+        .map(|game| {
+            let color = [White, Black]
+                .into_iter()
+                .find(|&color| user_id.is_some() && game.get_player(color) == user_id);
+            game.view(color.map_or(Viewer::Spectator, Viewer::Player))
+        })
+        .collect();
+    HttpResponse::Ok().body(serde_json::to_string(&views).unwrap())
 }
 
+/// Create a new game. The body may optionally contain the [GameOptions] to create the game with
+/// (e.g., `{"rated": true, "time_control": {"initial": 300, "increment": 3}}`).
 #[put("/game")]
-async fn put_game(data: web::Data<AppState>) -> impl Responder {
+async fn put_game(data: web::Data<AppState>, request: HttpRequest, body: String) -> impl Responder {
+    let options = if body.trim().is_empty() {
+        GameOptions::default()
+    } else {
+        match serde_json::from_str::<GameOptions>(&body) {
+            Ok(options) => options,
+            Err(e) => return HttpResponse::BadRequest().body(e.to_string()),
+        }
+    };
+    if let Err(e) = validate_game_options(&options) {
+        return e;
+    }
+
+    // If the player is logged in, record them as the creator of the game.
+    let user_id = authenticate_user(&request, &data).map(|user| user.id);
+
+    let mut game_manager = data.game_manager.lock().unwrap();
+    let game = game_manager.new_game_with_options(options);
+    let mut game = game.lock().unwrap();
+    game.set_creator(user_id);
+    HttpResponse::Ok().body(serde_json::to_string(&game.view(Viewer::Spectator)).unwrap())
+}
+
+/// Create the two linked games of a Bughouse match, responding with both games. The body may
+/// optionally contain the [GameOptions] to create the games with (e.g., a time control).
+#[put("/bughouse")]
+async fn put_bughouse(data: web::Data<AppState>, body: String) -> impl Responder {
+    let options = if body.trim().is_empty() {
+        GameOptions::default()
+    } else {
+        match serde_json::from_str::<GameOptions>(&body) {
+            Ok(options) => options,
+            Err(e) => return HttpResponse::BadRequest().body(e.to_string()),
+        }
+    };
+    let options = GameOptions {
+        variant: Variant::Bughouse,
+        ..options
+    };
+    if let Err(e) = validate_variant_options(&options) {
+        return e;
+    }
+
     let mut game_manager = data.game_manager.lock().unwrap();
-    let game = game_manager.new_game();
-    HttpResponse::Ok().body(serde_json::to_string(&game).unwrap())
+    let [first, second] = game_manager.new_bughouse_match(options);
+    let (first, second) = (first.lock().unwrap(), second.lock().unwrap());
+    HttpResponse::Ok().body(serde_json::to_string(&[&*first, &*second]).unwrap())
 }
 
+/// Get a game. In Dark Chess, a request with a seat token sees the board as that player does, and
+/// any other request sees it as a spectator.
 #[get("/game/{id}")]
-async fn get_game(data: web::Data<AppState>, game_id: web::Path<String>) -> impl Responder {
+async fn get_game(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    game_id: web::Path<String>,
+) -> impl Responder {
     match locate_game_by_id(data, game_id.into_inner()) {
-        Ok((_, game)) => HttpResponse::Ok().body(serde_json::to_string(&game).unwrap()),
+        Ok((_, game)) => {
+            let game = game.lock().unwrap();
+            let view = game.view(get_viewer(&request, &game));
+            HttpResponse::Ok().body(serde_json::to_string(&view).unwrap())
+        }
         Err(e) => e,
     }
 }
@@ -56,6 +173,7 @@ async fn delete_game(data: web::Data<AppState>, game_id: web::Path<String>) -> i
 #[post("/game/{game_id}/{position}/move")]
 async fn post_move(
     data: web::Data<AppState>,
+    request: HttpRequest,
     path: web::Path<(String, String)>,
     new_position: web::Json<Position>,
 ) -> impl Responder {
@@ -70,14 +188,29 @@ async fn post_move(
     let position = position.unwrap();
     let new_position = new_position.into_inner();
 
-    match locate_game_by_id(data, game_id) {
+    match locate_game_by_id(data.clone(), game_id) {
         Ok((_, game)) => {
-            match game
-                .lock()
-                .unwrap()
-                .move_piece_at_position(&position, &new_position)
-            {
-                Ok(_) => HttpResponse::Ok().finish(),
+            let game = game.lock().unwrap();
+
+            // A player whose time has run out loses rather than moving.
+            if game.check_flag() {
+                data.user_manager.lock().unwrap().record_game_result(&game);
+            }
+
+            // Only the player seated as the side to move may move.
+            match authenticate_seat(&request, &game) {
+                Ok(color) if color == game.get_current_move() => {}
+                Ok(_) => return HttpResponse::Forbidden().body("Cannot move out of turn"),
+                Err(e) => return e,
+            }
+
+            match game.move_piece_at_position(&position, &new_position) {
+                Ok(_) => {
+                    if game.get_result().is_some() {
+                        data.user_manager.lock().unwrap().record_game_result(&game);
+                    }
+                    HttpResponse::Ok().finish()
+                }
                 Err(e) => HttpResponse::NotFound().body(format!("{:?}", e)),
             }
         }
@@ -85,22 +218,1660 @@ async fn post_move(
     }
 }
 
-fn locate_game_by_id(
+#[post("/game/{game_id}/{piece}/drop")]
+async fn post_drop(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    path: web::Path<(String, PieceKind)>,
+    position: web::Json<Position>,
+) -> impl Responder {
+    let (game_id, kind) = path.into_inner();
+    let position = position.into_inner();
+
+    match locate_game_by_id(data.clone(), game_id) {
+        Ok((_, game)) => {
+            let game = game.lock().unwrap();
+
+            // A player whose time has run out loses rather than dropping a piece.
+            if game.check_flag() {
+                data.user_manager.lock().unwrap().record_game_result(&game);
+            }
+
+            // Only the player seated as the side to move may drop a piece.
+            match authenticate_seat(&request, &game) {
+                Ok(color) if color == game.get_current_move() => {}
+                Ok(_) => return HttpResponse::Forbidden().body("Cannot move out of turn"),
+                Err(e) => return e,
+            }
+
+            match game.drop_piece(kind, &position) {
+                Ok(_) => {
+                    if game.get_result().is_some() {
+                        data.user_manager.lock().unwrap().record_game_result(&game);
+                    }
+                    HttpResponse::Ok().finish()
+                }
+                Err(e) => HttpResponse::NotFound().body(format!("{:?}", e)),
+            }
+        }
+        Err(e) => e,
+    }
+}
+
+#[derive(Serialize)]
+struct SeatResponse {
+    color: Color,
+    token: String,
+}
+
+#[post("/game/{id}/seat/{color}")]
+async fn post_seat(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    path: web::Path<(String, Color)>,
+) -> impl Responder {
+    let (game_id, color) = path.into_inner();
+
+    // If the player is logged in, record them as the player for the seat.
+    let user_id = authenticate_user(&request, &data).map(|user| user.id);
+
+    match locate_game_by_id(data, game_id) {
+        Ok((_, game)) => match game.lock().unwrap().claim_seat(color, user_id) {
+            Ok(token) => HttpResponse::Ok()
+                .body(serde_json::to_string(&SeatResponse { color, token }).unwrap()),
+            Err(e) => HttpResponse::Conflict().body(e.to_string()),
+        },
+        Err(e) => e,
+    }
+}
+
+#[post("/game/{id}/resign")]
+async fn post_resign(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    game_id: web::Path<String>,
+) -> impl Responder {
+    perform_action(data, &request, game_id.into_inner(), |game, color| {
+        game.resign(color)
+    })
+}
+
+#[post("/game/{id}/draw/offer")]
+async fn post_draw_offer(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    game_id: web::Path<String>,
+) -> impl Responder {
+    perform_action(data, &request, game_id.into_inner(), |game, color| {
+        game.offer_draw(color)
+    })
+}
+
+#[post("/game/{id}/draw/accept")]
+async fn post_draw_accept(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    game_id: web::Path<String>,
+) -> impl Responder {
+    perform_action(data, &request, game_id.into_inner(), |game, color| {
+        game.accept_draw(color)
+    })
+}
+
+#[post("/game/{id}/draw/decline")]
+async fn post_draw_decline(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    game_id: web::Path<String>,
+) -> impl Responder {
+    perform_action(data, &request, game_id.into_inner(), |game, color| {
+        game.decline_draw(color)
+    })
+}
+
+#[post("/game/{id}/abort")]
+async fn post_abort(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    game_id: web::Path<String>,
+) -> impl Responder {
+    perform_action(data, &request, game_id.into_inner(), |game, _| game.abort())
+}
+
+#[post("/game/{id}/berserk")]
+async fn post_berserk(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    game_id: web::Path<String>,
+) -> impl Responder {
+    perform_action(data, &request, game_id.into_inner(), |game, color| {
+        game.berserk(color)
+    })
+}
+
+/// Perform a player action on a game on behalf of the seat authenticated by the request,
+/// responding with the updated game.
+fn perform_action<F>(
     data: web::Data<AppState>,
+    request: &HttpRequest,
     id: String,
-) -> Result<(Uuid, Arc<Mutex<Game>>), HttpResponse> {
-    let uuid = Uuid::from_str(id.as_str());
+    action: F,
+) -> HttpResponse
+where
+    F: FnOnce(&Game, Color) -> Result<(), ActionError>,
+{
+    match locate_game_by_id(data.clone(), id) {
+        Ok((_, game)) => {
+            let game = game.lock().unwrap();
+            let color = match authenticate_seat(request, &game) {
+                Ok(color) => color,
+                Err(e) => return e,
+            };
 
-    match uuid {
-        Ok(uuid) => {
-            let game_manager = data.game_manager.lock().unwrap();
-            match game_manager.get_game(uuid) {
-                Some(game) => Ok((uuid, game)),
-                None => {
-                    Err(HttpResponse::NotFound().body("No game found for the supplied game ID"))
+            let was_over = game.get_result().is_some();
+            match action(&game, color) {
+                Ok(_) => {
+                    // Update the players' ratings if the action ended the game.
+                    if !was_over && game.get_result().is_some() {
+                        data.user_manager.lock().unwrap().record_game_result(&game);
+                    }
+
+                    let view = game.view(Viewer::Player(color));
+                    HttpResponse::Ok().body(serde_json::to_string(&view).unwrap())
                 }
+                Err(e) => HttpResponse::Conflict().body(e.to_string()),
             }
         }
-        Err(_) => Err(HttpResponse::BadRequest().body("Invalid game ID")),
+        Err(e) => e,
+    }
+}
+
+/// Annotate a move of a game, responding with the updated game. Only the players may annotate a
+/// game, so the request must carry the seat token of either player.
+#[put("/game/{id}/ply/{ply}/annotation")]
+async fn put_annotation(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    path: web::Path<(String, usize)>,
+    annotation: web::Json<Annotation>,
+) -> impl Responder {
+    let (game_id, ply) = path.into_inner();
+
+    match locate_game_by_id(data, game_id) {
+        Ok((_, game)) => {
+            let mut game = game.lock().unwrap();
+            let color = match authenticate_seat(&request, &game) {
+                Ok(color) => color,
+                Err(e) => return e,
+            };
+            match game.set_annotation(ply, annotation.into_inner()) {
+                Ok(_) => {
+                    let view = game.view(Viewer::Player(color));
+                    HttpResponse::Ok().body(serde_json::to_string(&view).unwrap())
+                }
+                Err(e @ AnnotationError::InvalidPlyError) => {
+                    HttpResponse::NotFound().body(e.to_string())
+                }
+                Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+            }
+        }
+        Err(e) => e,
+    }
+}
+
+/// The starting position of a game being set up in the board editor, and why it is illegal (if it
+/// is). The game cannot start until the position is legal.
+#[derive(Serialize)]
+struct SetupResponse {
+    fen: String,
+    error: Option<String>,
+}
+
+/// A piece placed in the board editor.
+#[derive(Deserialize)]
+struct SetupPiece {
+    kind: PieceKind,
+    color: Color,
+}
+
+/// The rest of a position set up in the board editor, as in the last fields of a FEN: the player
+/// to move first, the castling rights (e.g., `KQkq`) and any en passant square.
+#[derive(Deserialize)]
+struct SetupRights {
+    turn: Color,
+    castling: String,
+    #[serde(default)]
+    en_passant: Option<Position>,
+}
+
+/// Get the starting position of a game, and whether it is legal.
+#[get("/game/{id}/setup")]
+async fn get_setup(data: web::Data<AppState>, game_id: web::Path<String>) -> impl Responder {
+    match locate_game_by_id(data, game_id.into_inner()) {
+        Ok((_, game)) => setup_response(&game.lock().unwrap()),
+        Err(e) => e,
+    }
+}
+
+/// Put a piece on a square of the starting position of a game that has not started.
+#[put("/game/{id}/setup/{position}")]
+async fn put_setup_piece(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    path: web::Path<(String, String)>,
+    piece: web::Json<SetupPiece>,
+) -> impl Responder {
+    let (game_id, raw_position) = path.into_inner();
+    let position = match serde_json::from_str::<Position>(&raw_position) {
+        Ok(position) => position,
+        Err(e) => return HttpResponse::BadRequest().body(format!("{:?}", e)),
+    };
+
+    let piece = Piece::new(piece.kind, piece.color);
+    edit_setup(data, &request, game_id, |game| {
+        game.set_piece(&position, Some(piece))
+    })
+}
+
+/// Clear a square of the starting position of a game that has not started.
+#[delete("/game/{id}/setup/{position}")]
+async fn delete_setup_piece(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (game_id, raw_position) = path.into_inner();
+    let position = match serde_json::from_str::<Position>(&raw_position) {
+        Ok(position) => position,
+        Err(e) => return HttpResponse::BadRequest().body(format!("{:?}", e)),
+    };
+
+    edit_setup(data, &request, game_id, |game| {
+        game.set_piece(&position, None)
+    })
+}
+
+/// Set the player to move first, the castling rights and the en passant square of the starting
+/// position of a game that has not started.
+#[put("/game/{id}/setup")]
+async fn put_setup_rights(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    game_id: web::Path<String>,
+    rights: web::Json<SetupRights>,
+) -> impl Responder {
+    let rights = rights.into_inner();
+    edit_setup(data, &request, game_id.into_inner(), |game| {
+        game.set_turn(rights.turn)?;
+        game.set_castling(&rights.castling)?;
+        game.set_en_passant(rights.en_passant)
+    })
+}
+
+/// Edit the starting position of a game in the board editor, responding with the position and
+/// whether it is legal. Once a seat has been claimed, only the (logged in) creator of an unrated
+/// game may edit the position, and once both seats have been claimed, no one may.
+fn edit_setup<F>(
+    data: web::Data<AppState>,
+    request: &HttpRequest,
+    id: String,
+    edit: F,
+) -> HttpResponse
+where
+    F: FnOnce(&mut Game) -> Result<(), SetupError>,
+{
+    let user_id = authenticate_user(request, &data).map(|user| user.id);
+
+    match locate_game_by_id(data, id) {
+        Ok((_, game)) => {
+            let mut game = game.lock().unwrap();
+            if game.is_seat_claimed(White) && game.is_seat_claimed(Black) {
+                return HttpResponse::Conflict()
+                    .body("The starting position cannot be edited once both seats are taken");
+            }
+            if game.is_seat_claimed(White) || game.is_seat_claimed(Black) {
+                let is_creator = user_id.is_some() && user_id == game.get_creator();
+                if game.is_rated() || !is_creator {
+                    return HttpResponse::Forbidden().body(
+                        "Only the creator of an unrated game may edit it once a seat is taken",
+                    );
+                }
+            }
+
+            match edit(&mut game) {
+                Ok(_) => setup_response(&game),
+                Err(e @ SetupError::GameStartedError) => {
+                    HttpResponse::Conflict().body(e.to_string())
+                }
+                Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+            }
+        }
+        Err(e) => e,
+    }
+}
+
+fn setup_response(game: &Game) -> HttpResponse {
+    let response = SetupResponse {
+        fen: game.get_starting_position().to_string(),
+        error: game
+            .validate_starting_position()
+            .err()
+            .map(|e| e.to_string()),
+    };
+    HttpResponse::Ok().body(serde_json::to_string(&response).unwrap())
+}
+
+/// Get the moves the server's opening book knows for the current position of a game, with their
+/// weights and learn values.
+#[get("/game/{id}/book")]
+async fn get_book(data: web::Data<AppState>, game_id: web::Path<String>) -> impl Responder {
+    let Some(book) = &data.book else {
+        return HttpResponse::NotFound().body("No opening book is loaded");
+    };
+
+    match locate_game_by_id(data.clone(), game_id.into_inner()) {
+        Ok((_, game)) => {
+            let game = game.lock().unwrap();
+            HttpResponse::Ok().body(serde_json::to_string(&book.get_moves(&game)).unwrap())
+        }
+        Err(e) => e,
+    }
+}
+
+#[get("/game/{id}/pgn")]
+async fn get_game_pgn(data: web::Data<AppState>, game_id: web::Path<String>) -> impl Responder {
+    match locate_game_by_id(data, game_id.into_inner()) {
+        Ok((_, game)) => {
+            let game = game.lock().unwrap();
+            if game.hides_moves() {
+                return HttpResponse::Forbidden().body(HIDDEN_MOVES_MESSAGE);
+            }
+
+            HttpResponse::Ok()
+                .content_type("application/x-chess-pgn")
+                .body(pgn::write_game(&game))
+        }
+        Err(e) => e,
+    }
+}
+
+#[post("/game/pgn")]
+async fn post_game_pgn(data: web::Data<AppState>, body: String) -> impl Responder {
+    let mut game_manager = data.game_manager.lock().unwrap();
+    match game_manager.import_game(&body) {
+        Ok(game) => HttpResponse::Ok().body(serde_json::to_string(&game).unwrap()),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct ForkRequest {
+    ply: usize,
+}
+
+#[post("/game/{id}/fork")]
+async fn post_fork(
+    data: web::Data<AppState>,
+    game_id: web::Path<String>,
+    request: web::Json<ForkRequest>,
+) -> impl Responder {
+    let id = match locate_game_by_id(data.clone(), game_id.into_inner()) {
+        Ok((_, game)) if game.lock().unwrap().hides_moves() => {
+            return HttpResponse::Forbidden().body(HIDDEN_MOVES_MESSAGE);
+        }
+        Ok((id, _)) => id,
+        Err(e) => return e,
+    };
+
+    let mut game_manager = data.game_manager.lock().unwrap();
+    match game_manager.fork_game(id, request.ply) {
+        Ok(game) => HttpResponse::Ok().body(serde_json::to_string(&game).unwrap()),
+        Err(ForkError::GameNotFoundError) => {
+            HttpResponse::NotFound().body("No game found for the supplied game ID")
+        }
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[put("/analysis")]
+async fn put_analysis(data: web::Data<AppState>) -> impl Responder {
+    let mut game_manager = data.game_manager.lock().unwrap();
+    let analysis = game_manager.new_analysis();
+    HttpResponse::Ok().body(serde_json::to_string(&analysis).unwrap())
+}
+
+#[get("/analysis/{id}")]
+async fn get_analysis(data: web::Data<AppState>, analysis_id: web::Path<String>) -> impl Responder {
+    match locate_analysis_by_id(data, analysis_id.into_inner()) {
+        Ok((_, analysis)) => HttpResponse::Ok().body(serde_json::to_string(&analysis).unwrap()),
+        Err(e) => e,
+    }
+}
+
+#[delete("/analysis/{id}")]
+async fn delete_analysis(
+    data: web::Data<AppState>,
+    analysis_id: web::Path<String>,
+) -> impl Responder {
+    match locate_analysis_by_id(data.clone(), analysis_id.into_inner()) {
+        Ok((id, _)) => {
+            data.game_manager.lock().unwrap().delete_analysis(id);
+            HttpResponse::Ok().finish()
+        }
+        Err(e) => e,
+    }
+}
+
+#[post("/analysis/pgn")]
+async fn post_analysis_pgn(data: web::Data<AppState>, body: String) -> impl Responder {
+    let mut game_manager = data.game_manager.lock().unwrap();
+    match game_manager.import_analysis(&body) {
+        Ok(analysis) => HttpResponse::Ok().body(serde_json::to_string(&analysis).unwrap()),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[get("/analysis/{id}/pgn")]
+async fn get_analysis_pgn(
+    data: web::Data<AppState>,
+    analysis_id: web::Path<String>,
+) -> impl Responder {
+    match locate_analysis_by_id(data, analysis_id.into_inner()) {
+        Ok((_, analysis)) => HttpResponse::Ok()
+            .content_type("application/x-chess-pgn")
+            .body(pgn::write_analysis(&analysis.lock().unwrap())),
+        Err(e) => e,
+    }
+}
+
+#[post("/analysis/{analysis_id}/{position}/move")]
+async fn post_analysis_move(
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    new_position: web::Json<Position>,
+) -> impl Responder {
+    let (analysis_id, raw_position) = path.into_inner();
+    let position = serde_json::from_str::<Position>(&raw_position);
+
+    // Ensure the position in the URL is valid.
+    if let Err(e) = position {
+        return HttpResponse::BadRequest().body(format!("{:?}", e));
+    }
+
+    let position = position.unwrap();
+    let new_position = new_position.into_inner();
+
+    match locate_analysis_by_id(data, analysis_id) {
+        Ok((_, analysis)) => {
+            let mut analysis = analysis.lock().unwrap();
+            match analysis.play_move(&position, &new_position) {
+                Ok(_) => HttpResponse::Ok().body(serde_json::to_string(&*analysis).unwrap()),
+                Err(e) => analysis_error_response(e),
+            }
+        }
+        Err(e) => e,
+    }
+}
+
+#[derive(Deserialize)]
+struct CursorRequest {
+    node: usize,
+}
+
+#[post("/analysis/{id}/cursor")]
+async fn post_analysis_cursor(
+    data: web::Data<AppState>,
+    analysis_id: web::Path<String>,
+    request: web::Json<CursorRequest>,
+) -> impl Responder {
+    update_analysis(data, analysis_id.into_inner(), |analysis| {
+        analysis.set_cursor(request.node)
+    })
+}
+
+#[put("/analysis/{id}/node/{node}/annotation")]
+async fn put_analysis_annotation(
+    data: web::Data<AppState>,
+    path: web::Path<(String, usize)>,
+    annotation: web::Json<Annotation>,
+) -> impl Responder {
+    let (analysis_id, node) = path.into_inner();
+    update_analysis(data, analysis_id, |analysis| {
+        analysis.set_annotation(node, annotation.into_inner())
+    })
+}
+
+#[post("/analysis/{id}/node/{node}/promote")]
+async fn post_analysis_promote(
+    data: web::Data<AppState>,
+    path: web::Path<(String, usize)>,
+) -> impl Responder {
+    let (analysis_id, node) = path.into_inner();
+    update_analysis(data, analysis_id, |analysis| {
+        analysis.promote_variation(node)
+    })
+}
+
+#[post("/analysis/{id}/node/{node}/demote")]
+async fn post_analysis_demote(
+    data: web::Data<AppState>,
+    path: web::Path<(String, usize)>,
+) -> impl Responder {
+    let (analysis_id, node) = path.into_inner();
+    update_analysis(data, analysis_id, |analysis| {
+        analysis.demote_variation(node)
+    })
+}
+
+#[delete("/analysis/{id}/node/{node}")]
+async fn delete_analysis_variation(
+    data: web::Data<AppState>,
+    path: web::Path<(String, usize)>,
+) -> impl Responder {
+    let (analysis_id, node) = path.into_inner();
+    update_analysis(data, analysis_id, |analysis| {
+        analysis.delete_variation(node)
+    })
+}
+
+/// Apply the specified operation to an analysis, responding with the updated analysis.
+fn update_analysis<F>(data: web::Data<AppState>, id: String, operation: F) -> HttpResponse
+where
+    F: FnOnce(&mut Analysis) -> Result<(), AnalysisError>,
+{
+    match locate_analysis_by_id(data, id) {
+        Ok((_, analysis)) => {
+            let mut analysis = analysis.lock().unwrap();
+            match operation(&mut analysis) {
+                Ok(_) => HttpResponse::Ok().body(serde_json::to_string(&*analysis).unwrap()),
+                Err(e) => analysis_error_response(e),
+            }
+        }
+        Err(e) => e,
+    }
+}
+
+fn analysis_error_response(error: AnalysisError) -> HttpResponse {
+    match error {
+        AnalysisError::NodeNotFoundError => HttpResponse::NotFound().body(error.to_string()),
+        _ => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
+
+fn locate_analysis_by_id(
+    data: web::Data<AppState>,
+    id: String,
+) -> Result<(Uuid, Arc<Mutex<Analysis>>), HttpResponse> {
+    let uuid = Uuid::from_str(id.as_str());
+
+    match uuid {
+        Ok(uuid) => {
+            let game_manager = data.game_manager.lock().unwrap();
+            match game_manager.get_analysis(uuid) {
+                Some(analysis) => Ok((uuid, analysis)),
+                None => {
+                    Err(HttpResponse::NotFound()
+                        .body("No analysis found for the supplied analysis ID"))
+                }
+            }
+        }
+        Err(_) => Err(HttpResponse::BadRequest().body("Invalid analysis ID")),
+    }
+}
+
+#[derive(Deserialize)]
+struct CredentialsRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Serialize)]
+struct LoginResponse<'a> {
+    token: String,
+    user: &'a User,
+}
+
+#[post("/register")]
+async fn post_register(
+    data: web::Data<AppState>,
+    request: web::Json<CredentialsRequest>,
+) -> impl Responder {
+    // The password is hashed before locking the users, as hashing is slow.
+    let password_hash = match UserManager::hash_password(&request.password) {
+        Ok(password_hash) => password_hash,
+        Err(e) => return HttpResponse::BadRequest().body(e.to_string()),
+    };
+
+    let mut user_manager = data.user_manager.lock().unwrap();
+    match user_manager.add_user(&request.username, password_hash) {
+        Ok(user) => HttpResponse::Ok().body(serde_json::to_string(&user).unwrap()),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[post("/login")]
+async fn post_login(
+    data: web::Data<AppState>,
+    request: web::Json<CredentialsRequest>,
+) -> impl Responder {
+    // The password is verified without holding the lock on the users, as verifying is slow.
+    let user = data
+        .user_manager
+        .lock()
+        .unwrap()
+        .find_user_by_username(request.username.trim())
+        .cloned()
+        .ok_or(AccountError::InvalidCredentialsError)
+        .and_then(|user| user.verify_password(&request.password).map(|_| user));
+
+    match user {
+        Ok(user) => {
+            let token = data.user_manager.lock().unwrap().start_session(user.id);
+            HttpResponse::Ok()
+                .body(serde_json::to_string(&LoginResponse { token, user: &user }).unwrap())
+        }
+        Err(e) => HttpResponse::Unauthorized().body(e.to_string()),
+    }
+}
+
+#[post("/logout")]
+async fn post_logout(data: web::Data<AppState>, request: HttpRequest) -> impl Responder {
+    if let Some(token) = get_session_token(&request) {
+        data.user_manager.lock().unwrap().logout(token);
+    }
+    HttpResponse::Ok().finish()
+}
+
+#[get("/me")]
+async fn get_me(data: web::Data<AppState>, request: HttpRequest) -> impl Responder {
+    match authenticate_user(&request, &data) {
+        Some(user) => HttpResponse::Ok().body(serde_json::to_string(&user).unwrap()),
+        None => HttpResponse::Unauthorized().body("You must be logged in"),
+    }
+}
+
+#[get("/user/{username}")]
+async fn get_user(data: web::Data<AppState>, username: web::Path<String>) -> impl Responder {
+    let user_manager = data.user_manager.lock().unwrap();
+    match user_manager.find_user_by_username(&username) {
+        Some(user) => HttpResponse::Ok().body(serde_json::to_string(user).unwrap()),
+        None => HttpResponse::NotFound().body("No user found with the supplied username"),
+    }
+}
+
+#[get("/user/{username}/ratings/{category}")]
+async fn get_rating_history(
+    data: web::Data<AppState>,
+    path: web::Path<(String, TimeControlCategory)>,
+) -> impl Responder {
+    let (username, category) = path.into_inner();
+
+    let user_manager = data.user_manager.lock().unwrap();
+    match user_manager.find_user_by_username(&username) {
+        Some(user) => {
+            let rating = user.get_rating(category);
+            HttpResponse::Ok().body(serde_json::to_string(&rating.history).unwrap())
+        }
+        None => HttpResponse::NotFound().body("No user found with the supplied username"),
+    }
+}
+
+/// Stream the [LiveEvent]s for the logged-in user as server-sent events.
+#[get("/live")]
+async fn get_live(data: web::Data<AppState>, request: HttpRequest) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let receiver = data.live_channel.lock().unwrap().subscribe(user.id);
+    let events = stream::unfold(receiver, |mut receiver| async move {
+        let event = receiver.recv().await?;
+        let event = Bytes::from(format!("data: {}\n\n", event));
+        Some((Ok::<_, Infallible>(event), receiver))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .streaming(events)
+}
+
+#[get("/seeks")]
+async fn get_seeks(data: web::Data<AppState>) -> impl Responder {
+    let lobby = data.lobby.lock().unwrap();
+    HttpResponse::Ok().body(serde_json::to_string(&lobby.get_seeks()).unwrap())
+}
+
+#[derive(Deserialize)]
+struct SeekRequest {
+    #[serde(flatten)]
+    options: GameOptions,
+    #[serde(default)]
+    color: ColorPreference,
+    rating_range: Option<RatingRange>,
+}
+
+#[derive(Serialize)]
+struct SeekResponse {
+    seek: Seek,
+
+    /// The ID of the game that was created, if the seek was paired immediately.
+    game_id: Option<String>,
+}
+
+/// Post a seek for the logged-in user. If the seek is paired, the game is created and both
+/// players are sent their seat tokens over the live channel.
+#[post("/seek")]
+async fn post_seek(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    seek: web::Json<SeekRequest>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let SeekRequest {
+        options,
+        color,
+        rating_range,
+    } = seek.into_inner();
+    if let Err(e) = validate_game_options(&options) {
+        return e;
+    }
+    let rating = user.get_rating(options.category()).current.rating;
+
+    let seek = Seek::new(user.id, rating, options, color, rating_range);
+    let pairing = data.lobby.lock().unwrap().post_seek(seek.clone());
+    let game_id = pairing.map(|Pairing { white, black }| {
+        start_game(&data, white.options, white.user_id, black.user_id)
+    });
+
+    HttpResponse::Ok().body(serde_json::to_string(&SeekResponse { seek, game_id }).unwrap())
+}
+
+#[delete("/seek/{id}")]
+async fn delete_seek(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    seek_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let mut lobby = data.lobby.lock().unwrap();
+    match lobby.cancel_seek(seek_id.into_inner(), user.id) {
+        Ok(_) => HttpResponse::Ok().finish(),
+        Err(e) => HttpResponse::NotFound().body(e.to_string()),
+    }
+}
+
+#[get("/challenges")]
+async fn get_challenges(data: web::Data<AppState>, request: HttpRequest) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let challenge_manager = data.challenge_manager.lock().unwrap();
+    let challenges = challenge_manager.get_challenges_for_user(user.id);
+    HttpResponse::Ok().body(serde_json::to_string(&challenges).unwrap())
+}
+
+#[derive(Deserialize)]
+struct ChallengeRequest {
+    /// The username of the player to challenge, or [None] to create an open invite.
+    recipient: Option<String>,
+    #[serde(flatten)]
+    options: GameOptions,
+    #[serde(default)]
+    color: ColorPreference,
+}
+
+#[derive(Serialize)]
+struct ChallengeResponse<'a> {
+    challenge: &'a Challenge,
+
+    /// The one-time code to share to invite a player to an open invite.
+    code: Option<&'a str>,
+}
+
+/// Challenge another player, or create an open invite (with a one-time code) if no recipient is
+/// specified. The recipient is notified over the live channel.
+#[post("/challenge")]
+async fn post_challenge(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    challenge: web::Json<ChallengeRequest>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let ChallengeRequest {
+        recipient,
+        options,
+        color,
+    } = challenge.into_inner();
+    if let Err(e) = validate_game_options(&options) {
+        return e;
+    }
+    let recipient = match recipient {
+        Some(username) => {
+            let user_manager = data.user_manager.lock().unwrap();
+            match user_manager.find_user_by_username(&username) {
+                Some(recipient) => Some(recipient.id),
+                None => {
+                    return HttpResponse::NotFound()
+                        .body("No user found with the supplied username")
+                }
+            }
+        }
+        None => None,
+    };
+
+    let mut challenge_manager = data.challenge_manager.lock().unwrap();
+    let challenge = match challenge_manager.create_challenge(user.id, recipient, options, color) {
+        Ok(challenge) => challenge,
+        Err(e) => return HttpResponse::BadRequest().body(e.to_string()),
+    };
+
+    if let Some(recipient) = recipient {
+        let event = LiveEvent::ChallengeReceived {
+            challenge: Box::new(challenge.clone()),
+        };
+        data.live_channel.lock().unwrap().send(recipient, &event);
+    }
+
+    let response = ChallengeResponse {
+        challenge: &challenge,
+        code: challenge.get_code(),
+    };
+    HttpResponse::Ok().body(serde_json::to_string(&response).unwrap())
+}
+
+#[derive(Deserialize)]
+struct AcceptQuery {
+    /// The one-time code of an open invite.
+    code: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AcceptResponse {
+    game_id: String,
+}
+
+/// Accept a challenge (or, with its code, an open invite). The game is created and both players
+/// are sent their seat tokens over the live channel.
+#[post("/challenge/{id}/accept")]
+async fn post_challenge_accept(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    challenge_id: web::Path<Uuid>,
+    query: web::Query<AcceptQuery>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let challenge = data.challenge_manager.lock().unwrap().accept_challenge(
+        challenge_id.into_inner(),
+        user.id,
+        query.code.as_deref(),
+    );
+    let challenge = match challenge {
+        Ok(challenge) => challenge,
+        Err(ChallengeError::ChallengeNotFoundError) => {
+            return HttpResponse::NotFound().body("No challenge found for the supplied ID")
+        }
+        Err(e) => return HttpResponse::Forbidden().body(e.to_string()),
+    };
+
+    let game_id = match challenge.color.resolve() {
+        White => start_game(&data, challenge.options, challenge.challenger, user.id),
+        Black => start_game(&data, challenge.options, user.id, challenge.challenger),
+    };
+    HttpResponse::Ok().body(serde_json::to_string(&AcceptResponse { game_id }).unwrap())
+}
+
+#[post("/challenge/{id}/decline")]
+async fn post_challenge_decline(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    challenge_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let challenge = data
+        .challenge_manager
+        .lock()
+        .unwrap()
+        .decline_challenge(challenge_id.into_inner(), user.id);
+    match challenge {
+        Ok(challenge) => {
+            let event = LiveEvent::ChallengeDeclined {
+                challenge_id: challenge.id,
+            };
+            data.live_channel
+                .lock()
+                .unwrap()
+                .send(challenge.challenger, &event);
+            HttpResponse::Ok().finish()
+        }
+        Err(e) => HttpResponse::NotFound().body(e.to_string()),
+    }
+}
+
+#[delete("/challenge/{id}")]
+async fn delete_challenge(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    challenge_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let mut challenge_manager = data.challenge_manager.lock().unwrap();
+    match challenge_manager.cancel_challenge(challenge_id.into_inner(), user.id) {
+        Ok(_) => HttpResponse::Ok().finish(),
+        Err(e) => HttpResponse::NotFound().body(e.to_string()),
+    }
+}
+
+#[get("/tournaments")]
+async fn get_tournaments(data: web::Data<AppState>) -> impl Responder {
+    let tournament_manager = data.tournament_manager.lock().unwrap();
+    let tournaments = tournament_manager.get_all_tournaments();
+    HttpResponse::Ok().body(serde_json::to_string(&tournaments).unwrap())
+}
+
+#[derive(Deserialize)]
+struct TournamentRequest {
+    name: String,
+    format: TournamentFormat,
+    #[serde(flatten)]
+    options: GameOptions,
+}
+
+/// Create a tournament organized by the logged-in user. The body specifies the name, format
+/// (e.g., `{"system": "swiss", "rounds": 5}`) and the settings for each game.
+#[put("/tournament")]
+async fn put_tournament(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    tournament: web::Json<TournamentRequest>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let TournamentRequest {
+        name,
+        format,
+        options,
+    } = tournament.into_inner();
+    if let Err(e) = validate_game_options(&options) {
+        return e;
+    }
+    let mut tournament_manager = data.tournament_manager.lock().unwrap();
+    let tournament = tournament_manager.create_tournament(name, user.id, format, options);
+    HttpResponse::Ok().body(serde_json::to_string(&tournament).unwrap())
+}
+
+#[get("/tournament/{id}")]
+async fn get_tournament(
+    data: web::Data<AppState>,
+    tournament_id: web::Path<Uuid>,
+) -> impl Responder {
+    match update_tournament(&data, tournament_id.into_inner()) {
+        Ok(tournament) => HttpResponse::Ok().body(serde_json::to_string(&tournament).unwrap()),
+        Err(e) => e,
+    }
+}
+
+#[get("/tournament/{id}/standings")]
+async fn get_tournament_standings(
+    data: web::Data<AppState>,
+    tournament_id: web::Path<Uuid>,
+) -> impl Responder {
+    match update_tournament(&data, tournament_id.into_inner()) {
+        Ok(tournament) => {
+            let standings = tournament.lock().unwrap().get_standings();
+            HttpResponse::Ok().body(serde_json::to_string(&standings).unwrap())
+        }
+        Err(e) => e,
+    }
+}
+
+/// Register the logged-in user for a tournament, seeded by their rating in the tournament's time
+/// control category.
+#[post("/tournament/{id}/join")]
+async fn post_tournament_join(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    tournament_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let tournament = match locate_tournament_by_id(&data, tournament_id.into_inner()) {
+        Ok(tournament) => tournament,
+        Err(e) => return e,
+    };
+    let mut tournament = tournament.lock().unwrap();
+    let rating = user
+        .get_rating(tournament.options.category())
+        .current
+        .rating;
+
+    match tournament.register(user.id, rating) {
+        Ok(_) => HttpResponse::Ok().body(serde_json::to_string(&*tournament).unwrap()),
+        Err(e) => HttpResponse::Conflict().body(e.to_string()),
+    }
+}
+
+/// Pair the next round of a tournament (on behalf of its organizer). The players are sent their
+/// seat tokens over the live channel.
+#[post("/tournament/{id}/round")]
+async fn post_tournament_round(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    tournament_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let tournament = match locate_tournament_by_id(&data, tournament_id.into_inner()) {
+        Ok(tournament) => tournament,
+        Err(e) => return e,
+    };
+
+    // The game manager is locked before the tournament, as in `update_tournament`, so that the
+    // two cannot deadlock.
+    let mut game_manager = data.game_manager.lock().unwrap();
+    let mut tournament = tournament.lock().unwrap();
+    if tournament.organizer != user.id {
+        return HttpResponse::Forbidden().body("Only the organizer can start rounds");
+    }
+
+    let seats = tournament.start_next_round(&mut game_manager);
+    drop(game_manager);
+    match seats {
+        Ok(seats) => {
+            data.live_channel.lock().unwrap().send_seats(seats);
+            HttpResponse::Ok().body(serde_json::to_string(&*tournament).unwrap())
+        }
+        Err(e) => HttpResponse::Conflict().body(e.to_string()),
+    }
+}
+
+#[get("/arenas")]
+async fn get_arenas(data: web::Data<AppState>) -> impl Responder {
+    let arena_manager = data.arena_manager.lock().unwrap();
+    let arenas = arena_manager.get_all_arenas();
+    HttpResponse::Ok().body(serde_json::to_string(&arenas).unwrap())
+}
+
+#[derive(Deserialize)]
+struct ArenaRequest {
+    name: String,
+
+    /// How long the arena lasts once it has started, in minutes.
+    duration: u64,
+
+    #[serde(flatten)]
+    options: GameOptions,
+}
+
+/// Create an arena organized by the logged-in user. The body specifies the name, duration and the
+/// settings for each game, which must include a time control.
+#[put("/arena")]
+async fn put_arena(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    arena: web::Json<ArenaRequest>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let ArenaRequest {
+        name,
+        duration,
+        options,
+    } = arena.into_inner();
+    if let Err(e) = validate_game_options(&options) {
+        return e;
+    }
+    let mut arena_manager = data.arena_manager.lock().unwrap();
+    match arena_manager.create_arena(name, user.id, options, duration) {
+        Ok(arena) => HttpResponse::Ok().body(serde_json::to_string(&arena).unwrap()),
+        Err(e) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[get("/arena/{id}")]
+async fn get_arena(data: web::Data<AppState>, arena_id: web::Path<Uuid>) -> impl Responder {
+    match locate_arena_by_id(&data, arena_id.into_inner()) {
+        Ok(arena) => HttpResponse::Ok().body(serde_json::to_string(&arena).unwrap()),
+        Err(e) => e,
+    }
+}
+
+#[get("/arena/{id}/standings")]
+async fn get_arena_standings(
+    data: web::Data<AppState>,
+    arena_id: web::Path<Uuid>,
+) -> impl Responder {
+    match locate_arena_by_id(&data, arena_id.into_inner()) {
+        Ok(arena) => {
+            let arena = arena.lock().unwrap();
+            HttpResponse::Ok().body(serde_json::to_string(&arena.get_standings()).unwrap())
+        }
+        Err(e) => e,
+    }
+}
+
+/// Add the logged-in user to an arena (or return them to it after withdrawing). They are paired
+/// as soon as another player is waiting.
+#[post("/arena/{id}/join")]
+async fn post_arena_join(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    arena_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    update_arena(&data, arena_id.into_inner(), |arena| {
+        let rating = user.get_rating(arena.options.category()).current.rating;
+        arena.join(user.id, rating)
+    })
+}
+
+#[post("/arena/{id}/withdraw")]
+async fn post_arena_withdraw(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    arena_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    update_arena(&data, arena_id.into_inner(), |arena| {
+        arena.withdraw(user.id)
+    })
+}
+
+/// Start an arena's timer (on behalf of its organizer).
+#[post("/arena/{id}/start")]
+async fn post_arena_start(
+    data: web::Data<AppState>,
+    request: HttpRequest,
+    arena_id: web::Path<Uuid>,
+) -> impl Responder {
+    let user = match authenticate_user(&request, &data) {
+        Some(user) => user,
+        None => return HttpResponse::Unauthorized().body("You must be logged in"),
+    };
+
+    let arena = match locate_arena_by_id(&data, arena_id.into_inner()) {
+        Ok(arena) => arena,
+        Err(e) => return e,
+    };
+    let mut arena = arena.lock().unwrap();
+    if arena.organizer != user.id {
+        return HttpResponse::Forbidden().body("Only the organizer can start the arena");
+    }
+
+    match arena.start(Utc::now()) {
+        Ok(_) => HttpResponse::Ok().body(serde_json::to_string(&*arena).unwrap()),
+        Err(e) => HttpResponse::Conflict().body(e.to_string()),
+    }
+}
+
+/// Apply a change to an arena, responding with the updated arena.
+fn update_arena<F>(data: &web::Data<AppState>, id: Uuid, change: F) -> HttpResponse
+where
+    F: FnOnce(&mut Arena) -> Result<(), ArenaError>,
+{
+    let arena = match locate_arena_by_id(data, id) {
+        Ok(arena) => arena,
+        Err(e) => return e,
+    };
+
+    let mut arena = arena.lock().unwrap();
+    match change(&mut arena) {
+        Ok(_) => HttpResponse::Ok().body(serde_json::to_string(&*arena).unwrap()),
+        Err(e) => HttpResponse::Conflict().body(e.to_string()),
+    }
+}
+
+fn locate_arena_by_id(
+    data: &web::Data<AppState>,
+    id: Uuid,
+) -> Result<Arc<Mutex<Arena>>, HttpResponse> {
+    let arena_manager = data.arena_manager.lock().unwrap();
+    arena_manager
+        .get_arena(id)
+        .ok_or_else(|| HttpResponse::NotFound().body("No arena found for the supplied ID"))
+}
+
+/// Locate a tournament and record the results of any of its games that have finished.
+fn update_tournament(
+    data: &web::Data<AppState>,
+    id: Uuid,
+) -> Result<Arc<Mutex<Tournament>>, HttpResponse> {
+    let tournament = locate_tournament_by_id(data, id)?;
+    {
+        let game_manager = data.game_manager.lock().unwrap();
+        tournament.lock().unwrap().update_results(&game_manager);
+    }
+    Ok(tournament)
+}
+
+fn locate_tournament_by_id(
+    data: &web::Data<AppState>,
+    id: Uuid,
+) -> Result<Arc<Mutex<Tournament>>, HttpResponse> {
+    let tournament_manager = data.tournament_manager.lock().unwrap();
+    tournament_manager
+        .get_tournament(id)
+        .ok_or_else(|| HttpResponse::NotFound().body("No tournament found for the supplied ID"))
+}
+
+/// Create a game between two users, seating both players and sending each of them their seat
+/// token over the live channel. Returns the ID of the new game.
+fn start_game(
+    data: &web::Data<AppState>,
+    options: GameOptions,
+    white: Uuid,
+    black: Uuid,
+) -> String {
+    let game = data
+        .game_manager
+        .lock()
+        .unwrap()
+        .new_game_with_options(options);
+    let mut game = game.lock().unwrap();
+    let game_id = game.get_id().cloned().unwrap();
+
+    let mut live_channel = data.live_channel.lock().unwrap();
+    for (color, user_id) in [(White, white), (Black, black)] {
+        let token = game
+            .claim_seat(color, Some(user_id))
+            .expect("seats of a new game should be free");
+        let event = LiveEvent::GameStarted {
+            game_id: game_id.clone(),
+            color,
+            token,
+        };
+        live_channel.send(user_id, &event);
+    }
+
+    game_id
+}
+
+fn get_session_token(request: &HttpRequest) -> Option<&str> {
+    request
+        .headers()
+        .get(SESSION_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Determine the logged-in user from the session token supplied in the `X-Session-Token` header.
+fn authenticate_user(request: &HttpRequest, data: &web::Data<AppState>) -> Option<User> {
+    let token = get_session_token(request)?;
+    let user_manager = data.user_manager.lock().unwrap();
+    user_manager.get_user_by_session(token).cloned()
+}
+
+/// Determine which seat the request is acting on behalf of, from the seat token supplied in the
+/// `Authorization: Bearer <token>` header.
+fn authenticate_seat(request: &HttpRequest, game: &Game) -> Result<Color, HttpResponse> {
+    let token = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match token.and_then(|token| game.get_seat_color(token)) {
+        Some(color) => Ok(color),
+        None => Err(HttpResponse::Unauthorized().body("A valid seat token is required")),
+    }
+}
+
+/// Check that the specified [GameOptions] can be used to create a single game.
+fn validate_game_options(options: &GameOptions) -> Result<(), HttpResponse> {
+    if options.variant == Variant::Bughouse {
+        return Err(HttpResponse::BadRequest()
+            .body("Bughouse games are created in linked pairs with PUT /bughouse"));
+    }
+    validate_variant_options(options)
+}
+
+/// Check that the settings of the specified [GameOptions] are valid for their variant.
+fn validate_variant_options(options: &GameOptions) -> Result<(), HttpResponse> {
+    if let Some(index) = options.chess960_position {
+        if index >= chess960::POSITION_COUNT {
+            return Err(HttpResponse::BadRequest().body(format!(
+                "Chess960 position must be between 0 and {}",
+                chess960::POSITION_COUNT - 1
+            )));
+        }
+    }
+    if let Some(fen) = &options.starting_fen {
+        if let Err(e) = options.variant.rules().validate_position(fen) {
+            return Err(
+                HttpResponse::BadRequest().body(format!("Illegal starting position: {}", e))
+            );
+        }
+    }
+    if let Some(handicap) = &options.handicap {
+        if options.rated {
+            return Err(HttpResponse::BadRequest().body("Handicap games cannot be rated"));
+        }
+        if handicap.pieces.contains(&PieceKind::King) {
+            return Err(HttpResponse::BadRequest().body("The king cannot be given as odds"));
+        }
+        if handicap.time_control.is_some() && options.time_control.is_none() {
+            return Err(HttpResponse::BadRequest().body("Time odds require a time control"));
+        }
+    }
+    Ok(())
+}
+
+/// Get the [Viewer] a request is made by: the player whose seat token it carries, or otherwise a
+/// spectator.
+fn get_viewer(request: &HttpRequest, game: &Game) -> Viewer {
+    match authenticate_seat(request, game) {
+        Ok(color) => Viewer::Player(color),
+        Err(_) => Viewer::Spectator,
+    }
+}
+
+fn locate_game_by_id(
+    data: web::Data<AppState>,
+    id: String,
+) -> Result<(Uuid, Arc<Mutex<Game>>), HttpResponse> {
+    let uuid = Uuid::from_str(id.as_str());
+
+    match uuid {
+        Ok(uuid) => {
+            let game_manager = data.game_manager.lock().unwrap();
+            match game_manager.get_game(uuid) {
+                Some(game) => Ok((uuid, game)),
+                None => {
+                    Err(HttpResponse::NotFound().body("No game found for the supplied game ID"))
+                }
+            }
+        }
+        Err(_) => Err(HttpResponse::BadRequest().body("Invalid game ID")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::live::LiveChannel;
+    use actix_web::test::{call_and_read_body_json, call_service, init_service, TestRequest};
+    use actix_web::App;
+    use core::arena::ArenaManager;
+    use core::challenge::ChallengeManager;
+    use core::game_manager::GameManager;
+    use core::lobby::Lobby;
+    use core::tournament::TournamentManager;
+    use serde_json::{json, Value};
+
+    fn new_state() -> web::Data<AppState> {
+        web::Data::new(AppState {
+            game_manager: Mutex::new(GameManager::new()),
+            user_manager: Mutex::new(UserManager::new()),
+            lobby: Mutex::new(Lobby::new()),
+            challenge_manager: Mutex::new(ChallengeManager::new()),
+            tournament_manager: Mutex::new(TournamentManager::new()),
+            arena_manager: Mutex::new(ArenaManager::new()),
+            live_channel: Mutex::new(LiveChannel::new()),
+            book: None,
+        })
+    }
+
+    /// Run a test against the routes. `#[actix_web::test]` cannot be used, as the `core` crate
+    /// shadows the standard library's `core` that the macro expands to.
+    fn run<F: std::future::Future>(future: F) -> F::Output {
+        actix_web::rt::System::new().block_on(future)
+    }
+
+    #[test]
+    fn rated_game_ending_in_mate_updates_ratings() {
+        run(async {
+            let state = new_state();
+            let app = init_service(
+                App::new()
+                    .app_data(state.clone())
+                    .service(put_game)
+                    .service(post_register)
+                    .service(post_login)
+                    .service(post_seat)
+                    .service(post_move),
+            )
+            .await;
+
+            let request = TestRequest::put()
+                .uri("/game")
+                .set_payload(json!({ "rated": true }).to_string())
+                .to_request();
+            let game: Value = call_and_read_body_json(&app, request).await;
+            let game_id = game["id"].as_str().unwrap().to_string();
+
+            let mut seat_tokens = Vec::new();
+            for (username, color) in [("alice", "W"), ("bob", "B")] {
+                let credentials = json!({ "username": username, "password": "password" });
+                let request = TestRequest::post()
+                    .uri("/register")
+                    .set_json(&credentials)
+                    .to_request();
+                assert!(call_service(&app, request).await.status().is_success());
+                let request = TestRequest::post()
+                    .uri("/login")
+                    .set_json(&credentials)
+                    .to_request();
+                let login: Value = call_and_read_body_json(&app, request).await;
+
+                let request = TestRequest::post()
+                    .uri(&format!("/game/{}/seat/{}", game_id, color))
+                    .insert_header((SESSION_TOKEN_HEADER, login["token"].as_str().unwrap()))
+                    .to_request();
+                let seat: Value = call_and_read_body_json(&app, request).await;
+                seat_tokens.push(seat["token"].as_str().unwrap().to_string());
+            }
+
+            // Fool's mate: 1. f3 e5 2. g4 Qh4#
+            let moves = [
+                ((6, 5), (5, 5)),
+                ((1, 4), (3, 4)),
+                ((6, 6), (4, 6)),
+                ((0, 3), (4, 7)),
+            ];
+            for (ply, (from, to)) in moves.into_iter().enumerate() {
+                let request = TestRequest::post()
+                    .uri(&format!(
+                        "/game/{}/%5B{},{}%5D/move",
+                        game_id, from.0, from.1
+                    ))
+                    .insert_header((AUTHORIZATION, format!("Bearer {}", seat_tokens[ply % 2])))
+                    .set_json(json!([to.0, to.1]))
+                    .to_request();
+                assert!(call_service(&app, request).await.status().is_success());
+            }
+
+            let user_manager = state.user_manager.lock().unwrap();
+            let rating = |username| {
+                user_manager
+                    .find_user_by_username(username)
+                    .unwrap()
+                    .get_rating(TimeControlCategory::Classical)
+            };
+            assert_eq!(rating("alice").games, 1);
+            assert_eq!(rating("bob").games, 1);
+            assert!(rating("bob").current.rating > rating("alice").current.rating);
+        });
+    }
+
+    #[test]
+    fn only_creator_may_edit_setup_once_seat_is_taken() {
+        run(async {
+            let state = new_state();
+            let app = init_service(
+                App::new()
+                    .app_data(state.clone())
+                    .service(put_game)
+                    .service(post_register)
+                    .service(post_login)
+                    .service(post_seat)
+                    .service(delete_setup_piece),
+            )
+            .await;
+
+            let credentials = json!({ "username": "alice", "password": "password" });
+            let request = TestRequest::post()
+                .uri("/register")
+                .set_json(&credentials)
+                .to_request();
+            assert!(call_service(&app, request).await.status().is_success());
+            let request = TestRequest::post()
+                .uri("/login")
+                .set_json(&credentials)
+                .to_request();
+            let login: Value = call_and_read_body_json(&app, request).await;
+            let session_token = login["token"].as_str().unwrap().to_string();
+
+            let request = TestRequest::put()
+                .uri("/game")
+                .insert_header((SESSION_TOKEN_HEADER, session_token.as_str()))
+                .to_request();
+            let game: Value = call_and_read_body_json(&app, request).await;
+            let game_id = game["id"].as_str().unwrap().to_string();
+
+            // Remove the pawns on a2, b2 and c2 at various stages of claiming the seats.
+            let edit = |file: usize, session_token: Option<&str>| {
+                let request =
+                    TestRequest::delete().uri(&format!("/game/{}/setup/%5B6,{}%5D", game_id, file));
+                match session_token {
+                    Some(token) => request.insert_header((SESSION_TOKEN_HEADER, token)),
+                    None => request,
+                }
+                .to_request()
+            };
+            let claim = |color: &str| {
+                TestRequest::post()
+                    .uri(&format!("/game/{}/seat/{}", game_id, color))
+                    .to_request()
+            };
+
+            // Anyone may edit the position while both seats are free.
+            assert_eq!(call_service(&app, edit(0, None)).await.status(), 200);
+
+            // Once a seat is taken, only the creator may.
+            assert!(call_service(&app, claim("B")).await.status().is_success());
+            assert_eq!(call_service(&app, edit(1, None)).await.status(), 403);
+            let response = call_service(&app, edit(1, Some(&session_token))).await;
+            assert_eq!(response.status(), 200);
+
+            // Once both seats are taken, no one may.
+            assert!(call_service(&app, claim("W")).await.status().is_success());
+            let response = call_service(&app, edit(2, Some(&session_token))).await;
+            assert_eq!(response.status(), 409);
+        });
+    }
+
+    #[test]
+    fn only_players_may_annotate_a_game() {
+        run(async {
+            let app = init_service(
+                App::new()
+                    .app_data(new_state())
+                    .service(put_game)
+                    .service(post_seat)
+                    .service(post_move)
+                    .service(put_annotation),
+            )
+            .await;
+
+            let request = TestRequest::put().uri("/game").to_request();
+            let game: Value = call_and_read_body_json(&app, request).await;
+            let game_id = game["id"].as_str().unwrap().to_string();
+
+            let mut seat_tokens = Vec::new();
+            for color in ["W", "B"] {
+                let request = TestRequest::post()
+                    .uri(&format!("/game/{}/seat/{}", game_id, color))
+                    .to_request();
+                let seat: Value = call_and_read_body_json(&app, request).await;
+                seat_tokens.push(seat["token"].as_str().unwrap().to_string());
+            }
+
+            // 1. e4
+            let request = TestRequest::post()
+                .uri(&format!("/game/{}/%5B6,4%5D/move", game_id))
+                .insert_header((AUTHORIZATION, format!("Bearer {}", seat_tokens[0])))
+                .set_json(json!([4, 4]))
+                .to_request();
+            assert!(call_service(&app, request).await.status().is_success());
+
+            let annotate = |seat_token: Option<&str>| {
+                let request = TestRequest::put()
+                    .uri(&format!("/game/{}/ply/1/annotation", game_id))
+                    .set_json(json!({ "comment": "Best by test" }));
+                match seat_token {
+                    Some(token) => {
+                        request.insert_header((AUTHORIZATION, format!("Bearer {}", token)))
+                    }
+                    None => request,
+                }
+                .to_request()
+            };
+            assert_eq!(call_service(&app, annotate(None)).await.status(), 401);
+            let response = call_service(&app, annotate(Some("not-a-seat-token"))).await;
+            assert_eq!(response.status(), 401);
+            let response = call_service(&app, annotate(Some(&seat_tokens[1]))).await;
+            assert_eq!(response.status(), 200);
+        });
+    }
+
+    #[test]
+    fn bughouse_options_are_validated() {
+        run(async {
+            let app = init_service(App::new().app_data(new_state()).service(put_bughouse)).await;
+
+            let request = TestRequest::put().uri("/bughouse").to_request();
+            assert_eq!(call_service(&app, request).await.status(), 200);
+
+            let request = TestRequest::put()
+                .uri("/bughouse")
+                .set_payload(json!({ "chess960_position": 960 }).to_string())
+                .to_request();
+            assert_eq!(call_service(&app, request).await.status(), 400);
+        });
     }
 }
@@ -0,0 +1,40 @@
+use crate::AppState;
+use actix_web::rt::time;
+use actix_web::web;
+use chrono::Utc;
+use std::time::Duration;
+
+/// How often games are checked for players that have run out of time, and arenas are updated.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Periodically end games in which the player to move has run out of time, and update arenas
+/// (pairing waiting players and ending arenas whose time is up).
+pub async fn run(data: web::Data<AppState>) {
+    let mut interval = time::interval(TICK_INTERVAL);
+    loop {
+        interval.tick().await;
+        check_flags(&data);
+        update_arenas(&data);
+    }
+}
+
+fn check_flags(data: &AppState) {
+    let games = data.game_manager.lock().unwrap().get_all_games();
+    for game in games {
+        let game = game.lock().unwrap();
+        if game.check_flag() {
+            data.user_manager.lock().unwrap().record_game_result(&game);
+        }
+    }
+}
+
+fn update_arenas(data: &AppState) {
+    let arenas = data.arena_manager.lock().unwrap().get_all_arenas();
+    for arena in arenas {
+        let seats = {
+            let mut game_manager = data.game_manager.lock().unwrap();
+            arena.lock().unwrap().update(&mut game_manager, Utc::now())
+        };
+        data.live_channel.lock().unwrap().send_seats(seats);
+    }
+}
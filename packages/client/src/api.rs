@@ -0,0 +1,189 @@
+use core::fen::Fen;
+use core::game::{Color, Game, GameOptions, PieceKind, Variant};
+use core::moves::Position;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// The header used to supply a session token (as returned when logging in).
+const SESSION_TOKEN_HEADER: &str = "X-Session-Token";
+
+/// A game as returned by the server (only the fields the client uses).
+#[derive(Clone, Debug, Deserialize)]
+pub struct GameView {
+    pub id: Option<String>,
+    pub variant: Variant,
+    pub fen: Fen,
+    pub current_move: Color,
+    pub is_player_in_check: BTreeMap<Color, bool>,
+    pub result: Option<ResultView>,
+    pub draw_offer: Option<Color>,
+    pub clock: Option<ClockView>,
+
+    /// The pieces each player holds to drop, in variants with pockets.
+    #[serde(default)]
+    pub pockets: Option<BTreeMap<Color, Vec<PieceKind>>>,
+
+    /// Whether each seat has been claimed.
+    pub seats: BTreeMap<Color, bool>,
+}
+
+impl GameView {
+    /// Create a local game of the same variant in the current position, to check and describe
+    /// moves against.
+    pub fn to_game(&self) -> Game {
+        let options = GameOptions {
+            variant: self.variant,
+            starting_fen: Some(self.fen.clone()),
+            ..GameOptions::default()
+        };
+        Game::new_with_options(None, options)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ResultView {
+    pub winner: Option<Color>,
+    pub termination: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ClockView {
+    /// The time each player has left, in milliseconds.
+    pub remaining: BTreeMap<Color, i64>,
+    pub running: Option<Color>,
+}
+
+/// A seat claimed at a game, and the token used to act on its behalf.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Seat {
+    pub color: Color,
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    token: String,
+}
+
+/// A connection to the server's HTTP API.
+pub struct Api {
+    /// The base URL of the server (e.g., `http://127.0.0.1:8080`).
+    server: String,
+
+    agent: ureq::Agent,
+
+    /// The session token, if logged in.
+    session: Option<String>,
+}
+
+impl Api {
+    pub fn new(server: &str) -> Api {
+        Api {
+            server: server.trim_end_matches('/').to_string(),
+            agent: ureq::Agent::new(),
+            session: None,
+        }
+    }
+
+    pub fn login(&mut self, username: &str, password: &str) -> Result<(), String> {
+        let body = serde_json::json!({ "username": username, "password": password });
+        let response = self.send("POST", "/login", None, Some(body.to_string()))?;
+        let login: LoginResponse = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+        self.session = Some(login.token);
+        Ok(())
+    }
+
+    pub fn new_game(&self, options: &GameOptions) -> Result<GameView, String> {
+        let body = serde_json::to_string(options).unwrap();
+        let response = self.send("PUT", "/game", None, Some(body))?;
+        serde_json::from_str(&response).map_err(|e| e.to_string())
+    }
+
+    /// Get a game as seen by the specified seat, or by a spectator. In Dark Chess, each sees a
+    /// different part of the board.
+    pub fn get_game(&self, id: &str, seat: Option<&Seat>) -> Result<GameView, String> {
+        let response = self.send("GET", &format!("/game/{}", id), seat, None)?;
+        serde_json::from_str(&response).map_err(|e| e.to_string())
+    }
+
+    pub fn get_pgn(&self, id: &str) -> Result<String, String> {
+        self.send("GET", &format!("/game/{}/pgn", id), None, None)
+    }
+
+    pub fn claim_seat(&self, id: &str, color: Color) -> Result<Seat, String> {
+        let color = serde_json::to_string(&color).unwrap();
+        let path = format!("/game/{}/seat/{}", id, color.trim_matches('"'));
+        let response = self.send("POST", &path, None, None)?;
+        serde_json::from_str(&response).map_err(|e| e.to_string())
+    }
+
+    pub fn make_move(
+        &self,
+        id: &str,
+        seat: &Seat,
+        position: &Position,
+        new_position: &Position,
+    ) -> Result<(), String> {
+        // Positions are sent as `[rank, file]` (with the brackets escaped in the path).
+        let path = format!(
+            "/game/{}/%5B{},{}%5D/move",
+            id, position.rank, position.file
+        );
+        let body = serde_json::to_string(&[new_position.rank, new_position.file]).unwrap();
+        self.send("POST", &path, Some(seat), Some(body)).map(|_| ())
+    }
+
+    /// Drop a piece from the seat's pocket onto the board.
+    pub fn drop_piece(
+        &self,
+        id: &str,
+        seat: &Seat,
+        kind: PieceKind,
+        position: &Position,
+    ) -> Result<(), String> {
+        let path = format!("/game/{}/{}/drop", id, kind.char());
+        let body = serde_json::to_string(&[position.rank, position.file]).unwrap();
+        self.send("POST", &path, Some(seat), Some(body)).map(|_| ())
+    }
+
+    /// Perform an action on behalf of the seat (e.g., `resign` or `draw/offer`).
+    pub fn perform_action(&self, id: &str, seat: &Seat, action: &str) -> Result<(), String> {
+        let path = format!("/game/{}/{}", id, action);
+        self.send("POST", &path, Some(seat), None).map(|_| ())
+    }
+
+    /// Send a request, returning the body of the response (or an error describing the failure).
+    fn send(
+        &self,
+        method: &str,
+        path: &str,
+        seat: Option<&Seat>,
+        body: Option<String>,
+    ) -> Result<String, String> {
+        let mut request = self
+            .agent
+            .request(method, &format!("{}{}", self.server, path));
+        if let Some(session) = &self.session {
+            request = request.set(SESSION_TOKEN_HEADER, session);
+        }
+        if let Some(seat) = seat {
+            request = request.set("Authorization", &format!("Bearer {}", seat.token));
+        }
+
+        let response = match body {
+            Some(body) => request
+                .set("Content-Type", "application/json")
+                .send_string(&body),
+            None => request.call(),
+        };
+
+        match response {
+            Ok(response) => response.into_string().map_err(|e| e.to_string()),
+            Err(ureq::Error::Status(status, response)) => {
+                let message = response.into_string().unwrap_or_default();
+                Err(format!("{} ({})", message, status))
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
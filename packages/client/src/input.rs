@@ -0,0 +1,87 @@
+use core::game::{Game, PieceKind};
+use core::moves::Position;
+use core::pgn;
+use std::str::FromStr;
+
+/// Parse a move entered by the player, in UCI (e.g., `e2e4`, optionally with a dash) or SAN
+/// (e.g., `Nf3`). Returns [None] if it is not a legal move in the game's current position.
+pub fn parse_move(game: &Game, input: &str) -> Option<(Position, Position)> {
+    let input = input.trim();
+    let uci = input.replace('-', "");
+
+    let mv = match (uci.get(0..2), uci.get(2..4), uci.len()) {
+        (Some(from), Some(to), 4) => match (Position::from_str(from), Position::from_str(to)) {
+            (Ok(position), Ok(new_position)) => Some((position, new_position)),
+            _ => pgn::from_san(game, input),
+        },
+        _ => pgn::from_san(game, input),
+    }?;
+
+    // Castling may be entered as the king moving two squares, but is played onto the rook.
+    let board = *game.board.lock().unwrap();
+    let mv = match Game::get_castling_rook(&board, &mv.0, &mv.1) {
+        Some(rook) => (mv.0, rook),
+        None => mv,
+    };
+
+    game.get_legal_moves().contains(&mv).then_some(mv)
+}
+
+/// Parse a drop of a piece from the player's pocket entered by the player, as the letter of the
+/// piece, `@` and the square (e.g., `N@e4`, or `@e4` for a pawn). Whether the piece is in the
+/// pocket and may be dropped there is left to the server.
+pub fn parse_drop(input: &str) -> Option<(PieceKind, Position)> {
+    let (piece, square) = input.trim().split_once('@')?;
+    let kind = match piece.chars().collect::<Vec<char>>()[..] {
+        [] => PieceKind::Pawn,
+        [c] => PieceKind::from_char(c.to_ascii_uppercase())?,
+        _ => return None,
+    };
+    let position = Position::from_str(square).ok()?;
+    Some((kind, position))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::input::{parse_drop, parse_move};
+    use core::game::Game;
+    use core::game::PieceKind::{Knight, Pawn};
+    use core::moves::Position;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_parse_move() {
+        let game = Game::new();
+        let e2e4 = Some((
+            Position::from_str("e2").unwrap(),
+            Position::from_str("e4").unwrap(),
+        ));
+        assert_eq!(parse_move(&game, "e2e4"), e2e4);
+        assert_eq!(parse_move(&game, "E2-E4"), e2e4);
+        assert_eq!(parse_move(&game, "e4"), e2e4);
+        assert_eq!(
+            parse_move(&game, "Nf3"),
+            Some((
+                Position::from_str("g1").unwrap(),
+                Position::from_str("f3").unwrap()
+            ))
+        );
+
+        assert_eq!(parse_move(&game, "e2e5"), None);
+        assert_eq!(parse_move(&game, "Ke2"), None);
+        assert_eq!(parse_move(&game, "hello"), None);
+    }
+
+    #[test]
+    fn test_parse_drop() {
+        let e4 = Position::from_str("e4").unwrap();
+        assert_eq!(parse_drop("N@e4"), Some((Knight, e4)));
+        assert_eq!(parse_drop("n@E4"), Some((Knight, e4)));
+        assert_eq!(parse_drop("@e4"), Some((Pawn, e4)));
+
+        assert_eq!(parse_drop("e2e4"), None);
+        assert_eq!(parse_drop("X@e4"), None);
+        assert_eq!(parse_drop("NB@e4"), None);
+        assert_eq!(parse_drop("N@z9"), None);
+    }
+}
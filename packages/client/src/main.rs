@@ -0,0 +1,324 @@
+mod api;
+mod input;
+mod render;
+
+use crate::api::{Api, GameView, Seat};
+use core::game::Color::{Black, White};
+use core::game::{Color, GameOptions};
+use core::time_control::TimeControl;
+use std::io::{BufRead, Write};
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: client [options] <command>
+
+Plays a game on the server in the terminal.
+
+Commands:
+    new [--color <white|black>] [--time <minutes>+<increment>] [--rated]
+                             Create a game and take a seat (White by default)
+    join <game-id> [--color <white|black>]
+                             Take a seat at a game (whichever is free by default)
+    watch <game-id>          Follow a game as a spectator
+
+Options:
+    --server <url>           The server to connect to (default http://127.0.0.1:8080)
+    --login <user>:<pass>    Log in, so that the game is played as that user
+    --flip                   Show the board from the other side
+
+During a game, enter moves in SAN (e.g., Nf3) or UCI (e.g., g1f3), drops from the pocket as the
+piece, @ and the square (e.g., N@e4), or one of:
+    resign, draw, accept, decline, abort, flip, help, quit";
+
+const COMMANDS: &str = "Enter a move (e.g., Nf3 or g1f3), a drop (e.g., N@e4), or one of: \
+                        resign, draw (offer a draw), accept, decline, abort, flip, quit";
+
+/// How often the game is fetched while waiting for the opponent, and while watching.
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+enum Command {
+    New {
+        color: Color,
+        time_control: Option<TimeControl>,
+        rated: bool,
+    },
+    Join {
+        id: String,
+        color: Option<Color>,
+    },
+    Watch {
+        id: String,
+    },
+}
+
+struct Args {
+    server: String,
+    login: Option<(String, String)>,
+    flip: bool,
+    command: Command,
+}
+
+fn parse_color(s: &str) -> Result<Color, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "white" | "w" => Ok(White),
+        "black" | "b" => Ok(Black),
+        _ => Err(format!("invalid color: '{}'", s)),
+    }
+}
+
+/// Parse a time control given in minutes and seconds of increment (e.g., `5+3`).
+fn parse_time_control(s: &str) -> Result<TimeControl, String> {
+    let invalid = || format!("invalid time control: '{}' (expected e.g. 5+3)", s);
+    let (minutes, increment) = s.split_once('+').unwrap_or((s, "0"));
+    let minutes: f64 = minutes.parse().map_err(|_| invalid())?;
+    let increment = increment.parse().map_err(|_| invalid())?;
+    Ok(TimeControl::new((minutes * 60.0) as u64, increment))
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Args, String> {
+        let mut server = "http://127.0.0.1:8080".to_string();
+        let mut login = None;
+        let mut flip = false;
+        let mut color = None;
+        let mut time_control = None;
+        let mut rated = false;
+        let mut positional = Vec::new();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--server" => server = value()?.clone(),
+                "--login" => {
+                    let (username, password) = value()?
+                        .split_once(':')
+                        .ok_or("expected --login <user>:<password>")?;
+                    login = Some((username.to_string(), password.to_string()));
+                }
+                "--flip" => flip = true,
+                "--color" => color = Some(parse_color(value()?)?),
+                "--time" => time_control = Some(parse_time_control(value()?)?),
+                "--rated" => rated = true,
+                _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        let command = match positional.as_slice() {
+            [command] if command == "new" => Command::New {
+                color: color.unwrap_or(White),
+                time_control,
+                rated,
+            },
+            [command, id] if command == "join" => Command::Join {
+                id: id.clone(),
+                color,
+            },
+            [command, id] if command == "watch" => Command::Watch { id: id.clone() },
+            _ => return Err("expected a command (see --help)".to_string()),
+        };
+
+        Ok(Args {
+            server,
+            login,
+            flip,
+            command,
+        })
+    }
+}
+
+/// A game being shown in the terminal, from the point of view of a player or spectator.
+struct Session<'a> {
+    api: &'a Api,
+    id: String,
+
+    /// The seat held by the player, or [None] when spectating.
+    seat: Option<Seat>,
+
+    /// Whether the board is shown from Black's side.
+    flipped: bool,
+
+    /// A message shown below the board (e.g., why a move was rejected).
+    message: Option<String>,
+}
+
+impl Session<'_> {
+    fn draw(&self, game: &GameView) {
+        let pgn = self.api.get_pgn(&self.id).unwrap_or_default();
+        let board = game.to_game();
+        let board = *board.board.lock().unwrap();
+
+        // Clear the screen and move the cursor to the top left.
+        print!("\x1b[2J\x1b[H");
+        let role = match &self.seat {
+            Some(seat) if seat.color == White => "playing White",
+            Some(_) => "playing Black",
+            None => "spectating",
+        };
+        println!("Game {} ({})\n", self.id, role);
+        println!("{}", render::render_board(&board, self.flipped));
+        println!(
+            "{}\n",
+            render::render_status(game, self.seat.as_ref().map(|seat| seat.color))
+        );
+
+        let moves = render::movetext(&pgn);
+        if !moves.is_empty() {
+            println!("{}\n", moves);
+        }
+        if let Some(message) = &self.message {
+            println!("{}\n", message);
+        }
+        let _ = std::io::stdout().flush();
+    }
+
+    /// Show the game until it ends, prompting the player for their moves.
+    fn run(&mut self) -> Result<(), String> {
+        let stdin = std::io::stdin();
+        let mut lines = stdin.lock().lines();
+
+        loop {
+            let game = self.api.get_game(&self.id, self.seat.as_ref())?;
+            self.draw(&game);
+            if game.result.is_some() {
+                return Ok(());
+            }
+
+            let seat = match &self.seat {
+                Some(seat) if seat.color == game.current_move => seat.clone(),
+                _ => {
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+            };
+
+            print!("> ");
+            let _ = std::io::stdout().flush();
+            let Some(line) = lines.next() else {
+                return Ok(());
+            };
+            let line = line.map_err(|e| e.to_string())?;
+            self.message = None;
+
+            let action = match line.trim() {
+                "" => continue,
+                "quit" | "exit" => return Ok(()),
+                "help" => {
+                    self.message = Some(COMMANDS.to_string());
+                    continue;
+                }
+                "flip" => {
+                    self.flipped = !self.flipped;
+                    continue;
+                }
+                "resign" => "resign",
+                "draw" => "draw/offer",
+                "accept" => "draw/accept",
+                "decline" => "draw/decline",
+                "abort" => "abort",
+                drop if drop.contains('@') => {
+                    let Some((kind, position)) = input::parse_drop(drop) else {
+                        self.message = Some(format!("Invalid drop: {}", drop));
+                        continue;
+                    };
+                    if let Err(e) = self.api.drop_piece(&self.id, &seat, kind, &position) {
+                        self.message = Some(format!("The drop was rejected: {}", e));
+                    }
+                    continue;
+                }
+                mv => {
+                    let Some((position, new_position)) = input::parse_move(&game.to_game(), mv)
+                    else {
+                        self.message = Some(format!("Illegal move: {}", mv));
+                        continue;
+                    };
+                    if let Err(e) = self
+                        .api
+                        .make_move(&self.id, &seat, &position, &new_position)
+                    {
+                        self.message = Some(format!("The move was rejected: {}", e));
+                    }
+                    continue;
+                }
+            };
+
+            if let Err(e) = self.api.perform_action(&self.id, &seat, action) {
+                self.message = Some(e);
+            }
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let mut api = Api::new(&args.server);
+    if let Some((username, password)) = &args.login {
+        api.login(username, password)?;
+    }
+
+    let (id, seat) = match args.command {
+        Command::New {
+            color,
+            time_control,
+            rated,
+        } => {
+            let options = GameOptions {
+                rated,
+                time_control,
+                ..GameOptions::default()
+            };
+            let game = api.new_game(&options)?;
+            let id = game.id.ok_or("the server did not return a game ID")?;
+            let seat = api.claim_seat(&id, color)?;
+            (id, Some(seat))
+        }
+        Command::Join { id, color } => {
+            let color = match color {
+                Some(color) => color,
+                None => {
+                    let game = api.get_game(&id, None)?;
+                    [White, Black]
+                        .into_iter()
+                        .find(|color| !game.seats.get(color).copied().unwrap_or_default())
+                        .ok_or("both seats have been taken")?
+                }
+            };
+            let seat = api.claim_seat(&id, color)?;
+            (id, Some(seat))
+        }
+        Command::Watch { id } => (id, None),
+    };
+
+    // Players see the board from their own side by default.
+    let black = seat.as_ref().is_some_and(|seat| seat.color == Black);
+    let mut session = Session {
+        api: &api,
+        id,
+        seat,
+        flipped: black != args.flip,
+        message: None,
+    };
+    session.run()
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match Args::parse(&args).and_then(run) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use crate::api::{ClockView, GameView};
+use core::game::Color::{Black, White};
+use core::game::{Color, GameBoard, Piece, PieceKind};
+use std::collections::BTreeMap;
+
+/// ANSI escape codes for the background of light and dark squares, and the foreground of pieces.
+const LIGHT_SQUARE: &str = "\x1b[48;5;180m";
+const DARK_SQUARE: &str = "\x1b[48;5;137m";
+const PIECE: &str = "\x1b[38;5;16m";
+const RESET: &str = "\x1b[0m";
+
+/// Draw the board with Unicode pieces on colored squares, from White's side (or Black's side if
+/// `flipped`).
+pub fn render_board(board: &GameBoard, flipped: bool) -> String {
+    let mut ranks: Vec<usize> = (0..8).collect();
+    let mut files: Vec<usize> = (0..board.files()).collect();
+    if flipped {
+        ranks.reverse();
+        files.reverse();
+    }
+
+    let file_labels: String = files
+        .iter()
+        .map(|&file| format!(" {} ", char::from(b'a' + file as u8)))
+        .collect();
+
+    let mut output = format!("   {}\n", file_labels);
+    for &rank in &ranks {
+        output.push_str(&format!(" {} ", 8 - rank));
+        for &file in &files {
+            let background = if (rank + file) % 2 == 0 {
+                LIGHT_SQUARE
+            } else {
+                DARK_SQUARE
+            };
+            let symbol = board[rank][file].map_or(' ', |piece| piece.unicode_symbol());
+            output.push_str(&format!("{}{} {} {}", background, PIECE, symbol, RESET));
+        }
+        output.push_str(&format!(" {}\n", 8 - rank));
+    }
+    output.push_str(&format!("   {}\n", file_labels));
+    output
+}
+
+/// Format a time in milliseconds as minutes and seconds (e.g., `4:05`), with tenths of a second
+/// once under ten seconds.
+pub fn format_clock(remaining: i64) -> String {
+    let remaining = remaining.max(0);
+    if remaining < 10_000 {
+        return format!("0:0{:.1}", remaining as f64 / 1000.0);
+    }
+
+    let seconds = remaining / 1000;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn color_name(color: Color) -> &'static str {
+    match color {
+        White => "White",
+        Black => "Black",
+    }
+}
+
+fn render_clocks(clock: &ClockView) -> String {
+    [White, Black]
+        .into_iter()
+        .map(|color| {
+            let remaining = clock.remaining.get(&color).copied().unwrap_or_default();
+            let running = if clock.running == Some(color) {
+                " ◀"
+            } else {
+                ""
+            };
+            format!(
+                "{}: {}{}",
+                color_name(color),
+                format_clock(remaining),
+                running
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("    ")
+}
+
+/// Show the pieces each player holds to drop (e.g., `White: ♘ ♙    Black: -`).
+pub fn render_pockets(pockets: &BTreeMap<Color, Vec<PieceKind>>) -> String {
+    [White, Black]
+        .into_iter()
+        .map(|color| {
+            let pieces: Vec<String> = pockets
+                .get(&color)
+                .into_iter()
+                .flatten()
+                .map(|&kind| Piece::new(kind, color).unicode_symbol().to_string())
+                .collect();
+            let pieces = if pieces.is_empty() {
+                "-".to_string()
+            } else {
+                pieces.join(" ")
+            };
+            format!("{}: {}", color_name(color), pieces)
+        })
+        .collect::<Vec<String>>()
+        .join("    ")
+}
+
+/// Describe the state of the game: the clocks and pockets, whose move it is, check or checkmate,
+/// any draw offer and the result once the game has ended.
+pub fn render_status(game: &GameView, seat: Option<Color>) -> String {
+    let mut lines = Vec::new();
+    if let Some(clock) = &game.clock {
+        lines.push(render_clocks(clock));
+    }
+    if let Some(pockets) = &game.pockets {
+        lines.push(render_pockets(pockets));
+    }
+
+    let to_move = game.current_move;
+    let in_check = game
+        .is_player_in_check
+        .get(&to_move)
+        .copied()
+        .unwrap_or_default();
+
+    match &game.result {
+        Some(result) => {
+            let outcome = match result.winner {
+                Some(winner) => format!("{} wins", color_name(winner)),
+                None if result.termination == "aborted" => "Game aborted".to_string(),
+                None => "Draw".to_string(),
+            };
+            lines.push(format!(
+                "{} by {}",
+                outcome,
+                result.termination.replace('_', " ")
+            ));
+        }
+        None => {
+            let mover = match seat {
+                Some(color) if color == to_move => "Your move".to_string(),
+                _ => format!("{} to move", color_name(to_move)),
+            };
+            let check = if in_check { " (check!)" } else { "" };
+            lines.push(format!("{}{}", mover, check));
+
+            if let Some(offer) = game.draw_offer {
+                lines.push(format!("{} offers a draw", color_name(offer)));
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Get the moves of a game from its PGN (i.e., everything after the tag pairs).
+pub fn movetext(pgn: &str) -> String {
+    pgn.lines()
+        .filter(|line| !line.starts_with('['))
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::render::{format_clock, movetext, render_board, render_pockets};
+    use core::game::Color::{Black, White};
+    use core::game::Game;
+    use core::game::PieceKind::{Knight, Pawn};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(format_clock(65_000), "1:05");
+        assert_eq!(format_clock(600_000), "10:00");
+        assert_eq!(format_clock(9_450), "0:09.4");
+        assert_eq!(format_clock(-20), "0:00.0");
+
+        let game = Game::new();
+        let board = render_board(&game.board.lock().unwrap(), false);
+        let lines: Vec<&str> = board.lines().collect();
+        assert!(lines[0].trim_start().starts_with("a "));
+        assert!(lines[1].starts_with(" 8 ") && lines[1].contains('♜'));
+        assert!(lines[8].starts_with(" 1 ") && lines[8].contains('♔'));
+
+        let flipped = render_board(&game.board.lock().unwrap(), true);
+        let lines: Vec<&str> = flipped.lines().collect();
+        assert!(lines[0].trim_start().starts_with("h "));
+        assert!(lines[1].starts_with(" 1 "));
+
+        let pgn = "[Event \"?\"]\n[Result \"*\"]\n\n1. e4 e5 *\n";
+        assert_eq!(movetext(pgn), "1. e4 e5 *");
+
+        let pockets = BTreeMap::from([(White, vec![Knight, Pawn]), (Black, vec![])]);
+        assert_eq!(render_pockets(&pockets), "White: ♘ ♙    Black: -");
+    }
+}
@@ -0,0 +1,376 @@
+use crate::annotation::Annotation;
+use crate::error::AnalysisError;
+use crate::game::Game;
+use crate::moves::Position;
+use chrono::serde::ts_milliseconds;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// The ID of the root node of every [Analysis] (the starting position, before any move).
+pub const ROOT_NODE: usize = 0;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AnalysisNode {
+    /// The move that led to this node, or [None] for the root node.
+    #[serde(rename = "move")]
+    pub mv: Option<(Position, Position)>,
+
+    /// The node that this node follows on from, or [None] for the root node.
+    pub parent: Option<usize>,
+
+    /// The continuations from this node. The first child is the main line, the rest are
+    /// alternative variations in order of preference.
+    pub children: Vec<usize>,
+
+    /// Annotations attached to the move (or, for the root node, the starting position).
+    pub annotation: Annotation,
+}
+
+/// An analysis board: a tree of moves where every node may have a main line and any number of
+/// alternative variations. Unlike a [Game], turns are not enforced against players; any move that
+/// is legal for the side to move at the cursor may be played.
+pub struct Analysis {
+    /// ID of the analysis in the [crate::game_manager::GameManager] (if the analysis belongs to a
+    /// [crate::game_manager::GameManager]).
+    id: Option<String>,
+
+    /// The [DateTime] the analysis was created.
+    created_at: DateTime<Utc>,
+
+    /// All nodes in the tree, by node ID.
+    nodes: BTreeMap<usize, AnalysisNode>,
+
+    /// The ID to assign to the next node added to the tree.
+    next_node_id: usize,
+
+    /// The ID of the node currently being looked at.
+    cursor: usize,
+}
+
+impl Serialize for Analysis {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Analysis<'a> {
+            id: &'a Option<String>,
+            #[serde(with = "ts_milliseconds")]
+            created_at: &'a DateTime<Utc>,
+            nodes: &'a BTreeMap<usize, AnalysisNode>,
+            cursor: usize,
+            game: Game,
+        }
+
+        let analysis = Analysis {
+            id: &self.id,
+            created_at: &self.created_at,
+            nodes: &self.nodes,
+            cursor: self.cursor,
+            game: self.get_current_game(),
+        };
+
+        analysis.serialize(serializer)
+    }
+}
+
+impl Default for Analysis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Analysis {
+    pub fn new() -> Analysis {
+        Analysis::new_with_id(None)
+    }
+
+    pub fn new_with_id(id: Option<String>) -> Analysis {
+        let mut nodes = BTreeMap::new();
+        nodes.insert(
+            ROOT_NODE,
+            AnalysisNode {
+                mv: None,
+                parent: None,
+                children: Vec::new(),
+                annotation: Annotation::default(),
+            },
+        );
+
+        Analysis {
+            id,
+            created_at: Utc::now(),
+            nodes,
+            next_node_id: ROOT_NODE + 1,
+            cursor: ROOT_NODE,
+        }
+    }
+
+    pub fn get_node(&self, id: usize) -> Option<&AnalysisNode> {
+        self.nodes.get(&id)
+    }
+
+    pub fn get_cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Move the cursor to the specified node.
+    pub fn set_cursor(&mut self, id: usize) -> Result<(), AnalysisError> {
+        if !self.nodes.contains_key(&id) {
+            return Err(AnalysisError::NodeNotFoundError);
+        }
+
+        self.cursor = id;
+        Ok(())
+    }
+
+    /// Get the moves leading from the starting position to the specified node.
+    pub fn get_line(&self, id: usize) -> Result<Vec<(Position, Position)>, AnalysisError> {
+        let mut line = Vec::new();
+        let mut node = self
+            .nodes
+            .get(&id)
+            .ok_or(AnalysisError::NodeNotFoundError)?;
+
+        while let Some(parent) = node.parent {
+            line.push(node.mv.expect("non-root node should have a move"));
+            node = &self.nodes[&parent];
+        }
+
+        line.reverse();
+        Ok(line)
+    }
+
+    /// Get the main line of the tree (following the first child from the root).
+    pub fn get_main_line(&self) -> Vec<(Position, Position)> {
+        let mut line = Vec::new();
+        let mut node = &self.nodes[&ROOT_NODE];
+
+        while let Some(child) = node.children.first() {
+            node = &self.nodes[child];
+            line.push(node.mv.expect("non-root node should have a move"));
+        }
+
+        line
+    }
+
+    /// Build the game as it stands at the specified node.
+    pub fn get_game_at(&self, id: usize) -> Result<Game, AnalysisError> {
+        let game = Game::new();
+        for (position, new_position) in self.get_line(id)? {
+            game.move_piece_at_position(&position, &new_position)
+                .map_err(AnalysisError::MoveError)?;
+        }
+        Ok(game)
+    }
+
+    /// Build the game as it stands at the cursor.
+    pub fn get_current_game(&self) -> Game {
+        self.get_game_at(self.cursor)
+            .expect("cursor should always point at a reachable node")
+    }
+
+    /// Play a move from the cursor. If the move already exists as a continuation of the cursor,
+    /// the cursor simply moves to it; otherwise a new variation is added after any existing ones.
+    /// Returns the ID of the node the cursor moved to.
+    pub fn play_move(
+        &mut self,
+        position: &Position,
+        new_position: &Position,
+    ) -> Result<usize, AnalysisError> {
+        let existing = self.nodes[&self.cursor]
+            .children
+            .iter()
+            .find(|child| self.nodes[child].mv == Some((*position, *new_position)));
+
+        if let Some(&existing) = existing {
+            self.cursor = existing;
+            return Ok(existing);
+        }
+
+        // Validate the move against the position at the cursor.
+        self.get_current_game()
+            .move_piece_at_position(position, new_position)
+            .map_err(AnalysisError::MoveError)?;
+
+        let id = self.next_node_id;
+        self.next_node_id += 1;
+
+        self.nodes.insert(
+            id,
+            AnalysisNode {
+                mv: Some((*position, *new_position)),
+                parent: Some(self.cursor),
+                children: Vec::new(),
+                annotation: Annotation::default(),
+            },
+        );
+        self.nodes.get_mut(&self.cursor).unwrap().children.push(id);
+
+        self.cursor = id;
+        Ok(id)
+    }
+
+    /// Get the index of the node amongst its siblings, along with its parent.
+    fn get_sibling_index(&self, id: usize) -> Result<(usize, usize), AnalysisError> {
+        let node = self
+            .nodes
+            .get(&id)
+            .ok_or(AnalysisError::NodeNotFoundError)?;
+        let parent = node.parent.ok_or(AnalysisError::RootNodeError)?;
+        let index = self.nodes[&parent]
+            .children
+            .iter()
+            .position(|child| *child == id)
+            .unwrap();
+
+        Ok((parent, index))
+    }
+
+    /// Attach an annotation to the specified node, replacing any existing annotation.
+    pub fn set_annotation(
+        &mut self,
+        id: usize,
+        annotation: Annotation,
+    ) -> Result<(), AnalysisError> {
+        annotation
+            .validate()
+            .map_err(AnalysisError::AnnotationError)?;
+        let node = self
+            .nodes
+            .get_mut(&id)
+            .ok_or(AnalysisError::NodeNotFoundError)?;
+        node.annotation = annotation;
+        Ok(())
+    }
+
+    /// Move the variation starting at the specified node one place up in order of preference. A
+    /// variation directly below the main line becomes the main line.
+    pub fn promote_variation(&mut self, id: usize) -> Result<(), AnalysisError> {
+        let (parent, index) = self.get_sibling_index(id)?;
+        if index > 0 {
+            let children = &mut self.nodes.get_mut(&parent).unwrap().children;
+            children.swap(index, index - 1);
+        }
+        Ok(())
+    }
+
+    /// Move the variation starting at the specified node one place down in order of preference.
+    /// Demoting the main line makes the first alternative the main line.
+    pub fn demote_variation(&mut self, id: usize) -> Result<(), AnalysisError> {
+        let (parent, index) = self.get_sibling_index(id)?;
+        let children = &mut self.nodes.get_mut(&parent).unwrap().children;
+        if index + 1 < children.len() {
+            children.swap(index, index + 1);
+        }
+        Ok(())
+    }
+
+    /// Delete the variation starting at the specified node (i.e., the node and all of its
+    /// continuations). If the cursor was within the deleted variation, it moves to the node's
+    /// parent.
+    pub fn delete_variation(&mut self, id: usize) -> Result<(), AnalysisError> {
+        let (parent, index) = self.get_sibling_index(id)?;
+        self.nodes.get_mut(&parent).unwrap().children.remove(index);
+
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            let node = self.nodes.remove(&id).unwrap();
+            if self.cursor == id {
+                self.cursor = parent;
+            }
+            pending.extend(node.children);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::analysis::{Analysis, ROOT_NODE};
+    use crate::annotation::Annotation;
+    use crate::error::{AnalysisError, AnnotationError};
+    use crate::moves::Position;
+    use std::str::FromStr;
+
+    fn play(analysis: &mut Analysis, from: &str, to: &str) -> usize {
+        analysis
+            .play_move(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_variations() {
+        let mut analysis = Analysis::new();
+        let e4 = play(&mut analysis, "E2", "E4");
+        let e5 = play(&mut analysis, "E7", "E5");
+
+        // Go back and add an alternative reply for black.
+        analysis.set_cursor(e4).unwrap();
+        let c5 = play(&mut analysis, "C7", "C5");
+        assert_eq!(analysis.get_node(e4).unwrap().children, vec![e5, c5]);
+        assert_eq!(analysis.get_main_line().len(), 2);
+
+        // Replaying an existing move just moves the cursor.
+        analysis.set_cursor(e4).unwrap();
+        assert_eq!(play(&mut analysis, "E7", "E5"), e5);
+
+        // Promote the sicilian to the main line.
+        analysis.promote_variation(c5).unwrap();
+        assert_eq!(analysis.get_node(e4).unwrap().children, vec![c5, e5]);
+        assert_eq!(
+            analysis.get_main_line()[1].1,
+            Position::from_str("C5").unwrap()
+        );
+
+        analysis.demote_variation(c5).unwrap();
+        assert_eq!(analysis.get_node(e4).unwrap().children, vec![e5, c5]);
+
+        // Deleting the variation containing the cursor moves it back to the parent.
+        analysis.set_cursor(e5).unwrap();
+        analysis.delete_variation(e5).unwrap();
+        assert_eq!(analysis.get_cursor(), e4);
+        assert_eq!(analysis.get_node(e4).unwrap().children, vec![c5]);
+        assert!(analysis.get_node(e5).is_none());
+
+        assert!(analysis.delete_variation(ROOT_NODE).is_err());
+    }
+
+    #[test]
+    fn test_illegal_move_is_rejected() {
+        let mut analysis = Analysis::new();
+        assert!(analysis
+            .play_move(
+                &Position::from_str("E7").unwrap(),
+                &Position::from_str("E5").unwrap(),
+            )
+            .is_err());
+        assert_eq!(analysis.get_node(ROOT_NODE).unwrap().children.len(), 0);
+    }
+
+    #[test]
+    fn test_annotation_comment_is_validated() {
+        let mut analysis = Analysis::new();
+        let e4 = play(&mut analysis, "E2", "E4");
+
+        let annotation = |comment: &str| Annotation {
+            comment: Some(comment.to_string()),
+            ..Annotation::default()
+        };
+        assert!(matches!(
+            analysis.set_annotation(e4, annotation("Best}\nby test")),
+            Err(AnalysisError::AnnotationError(
+                AnnotationError::InvalidCommentError
+            ))
+        ));
+        analysis.set_annotation(e4, annotation("Best}")).unwrap();
+        analysis
+            .set_annotation(e4, annotation("Best\nby test"))
+            .unwrap();
+    }
+}
@@ -0,0 +1,347 @@
+use crate::error::AnnotationError;
+use crate::moves::{Position, PositionParseErr};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Numeric Annotation Glyph (NAG), e.g., `$1` for a good move (`!`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Nag(pub u8);
+
+impl Nag {
+    /// The traditional move suffix glyphs and their NAG equivalents.
+    const GLYPHS: [(&'static str, u8); 6] = [
+        ("!", 1),
+        ("?", 2),
+        ("!!", 3),
+        ("??", 4),
+        ("!?", 5),
+        ("?!", 6),
+    ];
+}
+
+impl Display for Nag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match Nag::GLYPHS.iter().find(|(_, nag)| *nag == self.0) {
+            Some((glyph, _)) => write!(f, "{}", glyph),
+            None => write!(f, "${}", self.0),
+        }
+    }
+}
+
+impl FromStr for Nag {
+    type Err = AnnotationParseErr;
+
+    /// Parse either a move suffix glyph (e.g., `!?`) or a numeric NAG (e.g., `$5`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(number) = s.strip_prefix('$') {
+            return number.parse().map(Nag).map_err(|_| AnnotationParseErr);
+        }
+
+        Nag::GLYPHS
+            .iter()
+            .find(|(glyph, _)| *glyph == s)
+            .map(|(_, nag)| Nag(*nag))
+            .ok_or(AnnotationParseErr)
+    }
+}
+
+/// The colors available for square highlights and arrows, as used by the `[%csl]` and `[%cal]`
+/// PGN comment commands.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AnnotationColor {
+    Red,
+    Green,
+    Blue,
+    Yellow,
+}
+
+impl AnnotationColor {
+    fn from_char(c: char) -> Option<AnnotationColor> {
+        match c {
+            'R' => Some(AnnotationColor::Red),
+            'G' => Some(AnnotationColor::Green),
+            'B' => Some(AnnotationColor::Blue),
+            'Y' => Some(AnnotationColor::Yellow),
+            _ => None,
+        }
+    }
+
+    fn char(&self) -> char {
+        match *self {
+            AnnotationColor::Red => 'R',
+            AnnotationColor::Green => 'G',
+            AnnotationColor::Blue => 'B',
+            AnnotationColor::Yellow => 'Y',
+        }
+    }
+}
+
+/// A highlighted square, written as the color followed by the square (e.g., `Ge4`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Highlight {
+    pub color: AnnotationColor,
+    pub square: Position,
+}
+
+impl Display for Highlight {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            self.color.char(),
+            self.square.to_string().to_lowercase()
+        )
+    }
+}
+
+impl FromStr for Highlight {
+    type Err = AnnotationParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let color = s.chars().next().and_then(AnnotationColor::from_char);
+        match (color, s.get(1..)) {
+            (Some(color), Some(square)) if square.len() == 2 => Ok(Highlight {
+                color,
+                square: Position::from_str(square)?,
+            }),
+            _ => Err(AnnotationParseErr),
+        }
+    }
+}
+
+/// An arrow between two squares, written as the color followed by the squares (e.g., `Ge2e4`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Arrow {
+    pub color: AnnotationColor,
+    pub from: Position,
+    pub to: Position,
+}
+
+impl Display for Arrow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.color.char(),
+            self.from.to_string().to_lowercase(),
+            self.to.to_string().to_lowercase()
+        )
+    }
+}
+
+impl FromStr for Arrow {
+    type Err = AnnotationParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let color = s.chars().next().and_then(AnnotationColor::from_char);
+        match (color, s.get(1..3), s.get(3..)) {
+            (Some(color), Some(from), Some(to)) if to.len() == 2 => Ok(Arrow {
+                color,
+                from: Position::from_str(from)?,
+                to: Position::from_str(to)?,
+            }),
+            _ => Err(AnnotationParseErr),
+        }
+    }
+}
+
+macro_rules! serialize_with_display {
+    ($($t: ty),*) => {$(
+        impl Serialize for $t {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $t {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let s = String::deserialize(deserializer)?;
+                <$t>::from_str(&s).map_err(de::Error::custom)
+            }
+        }
+    )*};
+}
+
+serialize_with_display!(Nag, Highlight, Arrow);
+
+#[derive(Debug, PartialEq)]
+pub struct AnnotationParseErr;
+
+impl Display for AnnotationParseErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to parse annotation")
+    }
+}
+
+impl From<PositionParseErr> for AnnotationParseErr {
+    fn from(_: PositionParseErr) -> Self {
+        AnnotationParseErr
+    }
+}
+
+/// Annotations attached to a move: a text comment, NAGs and any squares or arrows drawn on the
+/// board.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Annotation {
+    pub comment: Option<String>,
+    pub nags: Vec<Nag>,
+    pub arrows: Vec<Arrow>,
+    pub highlights: Vec<Highlight>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.comment.is_none()
+            && self.nags.is_empty()
+            && self.arrows.is_empty()
+            && self.highlights.is_empty()
+    }
+
+    /// Check that the annotation can be written as PGN. A comment with a closing brace is written
+    /// as a rest-of-line comment, so it cannot also contain a line break.
+    pub fn validate(&self) -> Result<(), AnnotationError> {
+        if self
+            .comment
+            .as_ref()
+            .is_some_and(|comment| comment.contains('}') && comment.contains(['\n', '\r']))
+        {
+            return Err(AnnotationError::InvalidCommentError);
+        }
+        Ok(())
+    }
+
+    /// Write the comment (including the `[%csl]` and `[%cal]` commands for any highlights and
+    /// arrows), as it would appear between braces in PGN. Returns [None] if there is nothing to
+    /// write.
+    pub fn to_pgn_comment(&self) -> Option<String> {
+        let mut comment = String::new();
+
+        if !self.highlights.is_empty() {
+            let highlights: Vec<String> = self.highlights.iter().map(|h| h.to_string()).collect();
+            comment.push_str(&format!("[%csl {}]", highlights.join(",")));
+        }
+
+        if !self.arrows.is_empty() {
+            let arrows: Vec<String> = self.arrows.iter().map(|a| a.to_string()).collect();
+            comment.push_str(&format!("[%cal {}]", arrows.join(",")));
+        }
+
+        if let Some(text) = &self.comment {
+            if !comment.is_empty() {
+                comment.push(' ');
+            }
+            comment.push_str(text);
+        }
+
+        if comment.is_empty() {
+            None
+        } else {
+            Some(comment)
+        }
+    }
+
+    /// Add the contents of a PGN comment (the text between braces) to the annotation, extracting
+    /// any `[%csl]` and `[%cal]` commands. Other commands are kept as part of the comment text.
+    pub fn add_pgn_comment(&mut self, pgn_comment: &str) -> Result<(), AnnotationParseErr> {
+        let mut text = String::new();
+        let mut rest = pgn_comment;
+
+        while let Some(start) = rest.find("[%") {
+            let end = rest[start..].find(']').ok_or(AnnotationParseErr)? + start;
+            let command = &rest[start + 2..end];
+            let (name, arguments) = command.split_once(' ').unwrap_or((command, ""));
+            let arguments = arguments
+                .split(',')
+                .map(str::trim)
+                .filter(|a| !a.is_empty());
+
+            text.push_str(&rest[..start]);
+            match name {
+                "csl" => {
+                    for argument in arguments {
+                        self.highlights.push(Highlight::from_str(argument)?);
+                    }
+                }
+                "cal" => {
+                    for argument in arguments {
+                        self.arrows.push(Arrow::from_str(argument)?);
+                    }
+                }
+                _ => text.push_str(&rest[start..=end]),
+            }
+
+            rest = &rest[end + 1..];
+        }
+        text.push_str(rest);
+
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            self.comment = Some(match self.comment.take() {
+                Some(existing) => format!("{} {}", existing, text),
+                None => text,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::annotation::{Annotation, AnnotationColor, Arrow, Highlight, Nag};
+    use crate::moves::Position;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_nag_glyphs() {
+        assert_eq!(Nag::from_str("!?"), Ok(Nag(5)));
+        assert_eq!(Nag::from_str("$14"), Ok(Nag(14)));
+        assert!(Nag::from_str("!!!").is_err());
+        assert_eq!(Nag(2).to_string(), "?");
+        assert_eq!(Nag(14).to_string(), "$14");
+    }
+
+    #[test]
+    fn test_pgn_comment_round_trip() {
+        let mut annotation = Annotation::default();
+        annotation
+            .add_pgn_comment("[%csl Rd4,Ge5] Controls the centre [%cal Gg1f3]")
+            .unwrap();
+
+        assert_eq!(annotation.comment.as_deref(), Some("Controls the centre"));
+        assert_eq!(
+            annotation.highlights,
+            vec![
+                Highlight {
+                    color: AnnotationColor::Red,
+                    square: Position::from_str("D4").unwrap()
+                },
+                Highlight {
+                    color: AnnotationColor::Green,
+                    square: Position::from_str("E5").unwrap()
+                },
+            ]
+        );
+        assert_eq!(
+            annotation.arrows,
+            vec![Arrow {
+                color: AnnotationColor::Green,
+                from: Position::from_str("G1").unwrap(),
+                to: Position::from_str("F3").unwrap(),
+            }]
+        );
+
+        assert_eq!(
+            annotation.to_pgn_comment().as_deref(),
+            Some("[%csl Rd4,Ge5][%cal Gg1f3] Controls the centre")
+        );
+    }
+}
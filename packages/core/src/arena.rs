@@ -0,0 +1,498 @@
+use crate::error::ArenaError;
+use crate::game::Color::{Black, White};
+use crate::game::{Color, GameOptions, GameResult};
+use crate::game_manager::GameManager;
+use crate::tournament::{create_game, SeatAssignment};
+use chrono::serde::{ts_milliseconds, ts_milliseconds_option};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// The points scored for a win and a draw.
+const WIN_POINTS: u32 = 2;
+const DRAW_POINTS: u32 = 1;
+
+/// The number of consecutive wins after which a player is on a streak, and scores double points
+/// until they fail to win.
+const STREAK_WINS: usize = 2;
+
+/// The number of moves a player that berserked must make for a win to earn a bonus point.
+const BERSERK_MIN_MOVES: usize = 7;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArenaStatus {
+    /// Players may join, but no games have been paired.
+    Registration,
+    InProgress,
+    Finished,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ArenaPlayer {
+    pub user_id: Uuid,
+
+    /// The player's rating when they joined.
+    pub rating: f64,
+
+    pub points: u32,
+
+    /// The number of games the player has won in a row.
+    pub streak: usize,
+
+    /// Whether the player is waiting to be paired (or playing). Players that withdraw keep their
+    /// points, but are not paired again unless they rejoin.
+    pub active: bool,
+}
+
+impl ArenaPlayer {
+    /// Check whether the player's next game scores double points.
+    pub fn is_on_streak(&self) -> bool {
+        self.streak >= STREAK_WINS
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ArenaGame {
+    pub game_id: Uuid,
+    pub white: Uuid,
+    pub black: Uuid,
+
+    /// The points scored by the white and black players, once the game has finished.
+    pub points: Option<(u32, u32)>,
+}
+
+/// A tournament in which players are paired again as soon as their game ends, until the arena's
+/// time runs out.
+#[derive(Clone, Debug, Serialize)]
+pub struct Arena {
+    pub id: Uuid,
+
+    pub name: String,
+
+    /// The ID of the user that created the arena, who is responsible for starting it.
+    pub organizer: Uuid,
+
+    /// The settings that each game in the arena is created with.
+    pub options: GameOptions,
+
+    /// How long the arena lasts once it has started, in minutes.
+    pub duration: u64,
+
+    pub status: ArenaStatus,
+
+    pub players: Vec<ArenaPlayer>,
+
+    /// Every game that has been paired, oldest first.
+    pub games: Vec<ArenaGame>,
+
+    /// The [DateTime] the arena started, if it has.
+    #[serde(with = "ts_milliseconds_option")]
+    pub started_at: Option<DateTime<Utc>>,
+
+    /// The [DateTime] the arena was created.
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl Arena {
+    pub fn new(
+        id: Uuid,
+        name: String,
+        organizer: Uuid,
+        options: GameOptions,
+        duration: u64,
+    ) -> Result<Arena, ArenaError> {
+        // Berserking halves a player's clock, so every game must be timed.
+        if options.time_control.is_none() {
+            return Err(ArenaError::UntimedError);
+        }
+
+        Ok(Arena {
+            id,
+            name,
+            organizer,
+            options,
+            duration,
+            status: ArenaStatus::Registration,
+            players: Vec::new(),
+            games: Vec::new(),
+            started_at: None,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Add a player to the arena, or return a player that withdrew to the pairing pool. Players
+    /// can join at any time until the arena has finished.
+    pub fn join(&mut self, user_id: Uuid, rating: f64) -> Result<(), ArenaError> {
+        if self.status == ArenaStatus::Finished {
+            return Err(ArenaError::ArenaFinishedError);
+        }
+
+        match self.get_player_mut(user_id) {
+            Some(player) if player.active => Err(ArenaError::AlreadyJoinedError),
+            Some(player) => {
+                player.active = true;
+                Ok(())
+            }
+            None => {
+                self.players.push(ArenaPlayer {
+                    user_id,
+                    rating,
+                    points: 0,
+                    streak: 0,
+                    active: true,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Stop pairing a player. Their current game (if any) still counts.
+    pub fn withdraw(&mut self, user_id: Uuid) -> Result<(), ArenaError> {
+        match self.get_player_mut(user_id) {
+            Some(player) if player.active => {
+                player.active = false;
+                Ok(())
+            }
+            _ => Err(ArenaError::NotJoinedError),
+        }
+    }
+
+    /// Start the arena's timer at the specified [DateTime].
+    pub fn start(&mut self, now: DateTime<Utc>) -> Result<(), ArenaError> {
+        if self.status != ArenaStatus::Registration {
+            return Err(ArenaError::ArenaStartedError);
+        }
+
+        self.status = ArenaStatus::InProgress;
+        self.started_at = Some(now);
+        Ok(())
+    }
+
+    /// Get the [DateTime] the arena ends, if it has started.
+    pub fn get_ends_at(&self) -> Option<DateTime<Utc>> {
+        self.started_at
+            .map(|started_at| started_at + TimeDelta::minutes(self.duration as i64))
+    }
+
+    fn get_player_mut(&mut self, user_id: Uuid) -> Option<&mut ArenaPlayer> {
+        self.players
+            .iter_mut()
+            .find(|player| player.user_id == user_id)
+    }
+
+    /// Check whether the specified player has a game in progress.
+    pub fn is_playing(&self, user_id: Uuid) -> bool {
+        self.games
+            .iter()
+            .any(|game| game.points.is_none() && (game.white == user_id || game.black == user_id))
+    }
+
+    /// Get the players, ordered by points (then by rating).
+    pub fn get_standings(&self) -> Vec<&ArenaPlayer> {
+        let mut standings: Vec<&ArenaPlayer> = self.players.iter().collect();
+        standings.sort_by(|a, b| b.points.cmp(&a.points).then(b.rating.total_cmp(&a.rating)));
+        standings
+    }
+
+    /// Bring the arena up to date at the specified [DateTime]: score any games that have
+    /// finished, end the arena if its time has run out, and otherwise pair every player that is
+    /// waiting for a game. Returns the seats of the players in any new games.
+    pub fn update(
+        &mut self,
+        game_manager: &mut GameManager,
+        now: DateTime<Utc>,
+    ) -> Vec<SeatAssignment> {
+        if self.status != ArenaStatus::InProgress {
+            return Vec::new();
+        }
+
+        self.update_results(game_manager);
+
+        // Games still in progress when the time runs out are not scored.
+        if self.get_ends_at().is_some_and(|ends_at| now >= ends_at) {
+            self.status = ArenaStatus::Finished;
+            return Vec::new();
+        }
+
+        let mut seats = Vec::new();
+        for (white, black) in self.pair_waiting_players() {
+            let game_id = create_game(game_manager, &self.options, white, black, &mut seats);
+            self.games.push(ArenaGame {
+                game_id,
+                white,
+                black,
+                points: None,
+            });
+        }
+        seats
+    }
+
+    fn update_results(&mut self, game_manager: &GameManager) {
+        for index in 0..self.games.len() {
+            let arena_game = &self.games[index];
+            if arena_game.points.is_some() {
+                continue;
+            }
+
+            let Some(game) = game_manager.get_game(arena_game.game_id) else {
+                continue;
+            };
+            let game = game.lock().unwrap();
+            let Some(result) = game.get_result() else {
+                continue;
+            };
+
+            let (white, black) = (arena_game.white, arena_game.black);
+            let moves = game.get_move_count();
+            let white_points = self.score(white, result, White, game.is_berserk(White), moves);
+            let black_points = self.score(black, result, Black, game.is_berserk(Black), moves);
+            self.games[index].points = Some((white_points, black_points));
+        }
+    }
+
+    /// Score a finished game for the player that played as the specified color, updating their
+    /// points and win streak.
+    fn score(
+        &mut self,
+        user_id: Uuid,
+        result: GameResult,
+        color: Color,
+        berserk: bool,
+        moves: usize,
+    ) -> u32 {
+        let Some(player) = self.get_player_mut(user_id) else {
+            return 0;
+        };
+
+        // Aborted games score nothing, and leave the streak as it was.
+        let Some(score) = result.score(color) else {
+            return 0;
+        };
+
+        let mut points = match score {
+            1.0 => WIN_POINTS,
+            0.5 => DRAW_POINTS,
+            _ => 0,
+        };
+        if player.is_on_streak() {
+            points *= 2;
+        }
+
+        // A berserk win earns a bonus point, as long as the player made enough moves.
+        let player_moves = (moves + usize::from(color == White)) / 2;
+        if score == 1.0 && berserk && player_moves >= BERSERK_MIN_MOVES {
+            points += 1;
+        }
+
+        player.streak = if score == 1.0 { player.streak + 1 } else { 0 };
+        player.points += points;
+        points
+    }
+
+    /// Pair the active players that are not currently playing, in order of standing. Each player
+    /// is paired with the closest-ranked waiting player that was not their last opponent, if there
+    /// is one. With an odd number of waiting players, the lowest ranked waits for the next game to
+    /// finish.
+    fn pair_waiting_players(&self) -> Vec<(Uuid, Uuid)> {
+        let mut waiting: Vec<Uuid> = self
+            .get_standings()
+            .into_iter()
+            .filter(|player| player.active && !self.is_playing(player.user_id))
+            .map(|player| player.user_id)
+            .collect();
+
+        let mut pairings = Vec::new();
+        while waiting.len() >= 2 {
+            let player = waiting.remove(0);
+            let last_opponent = self.get_last_opponent(player);
+            let index = waiting
+                .iter()
+                .position(|opponent| Some(*opponent) != last_opponent)
+                .unwrap_or(0);
+            let opponent = waiting.remove(index);
+
+            // The player that has played white less often gets white.
+            if self.get_color_balance(opponent) < self.get_color_balance(player) {
+                pairings.push((opponent, player));
+            } else {
+                pairings.push((player, opponent));
+            }
+        }
+        pairings
+    }
+
+    fn get_last_opponent(&self, user_id: Uuid) -> Option<Uuid> {
+        self.games.iter().rev().find_map(|game| {
+            if game.white == user_id {
+                Some(game.black)
+            } else if game.black == user_id {
+                Some(game.white)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Get the number of games the specified player has played as white, minus the number they
+    /// have played as black.
+    fn get_color_balance(&self, user_id: Uuid) -> i32 {
+        self.games
+            .iter()
+            .map(|game| match user_id {
+                id if id == game.white => 1,
+                id if id == game.black => -1,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+pub struct ArenaManager {
+    arenas: BTreeMap<Uuid, Arc<Mutex<Arena>>>,
+}
+
+impl ArenaManager {
+    pub fn new() -> ArenaManager {
+        ArenaManager {
+            arenas: BTreeMap::new(),
+        }
+    }
+
+    pub fn create_arena(
+        &mut self,
+        name: String,
+        organizer: Uuid,
+        options: GameOptions,
+        duration: u64,
+    ) -> Result<Arc<Mutex<Arena>>, ArenaError> {
+        let id = Uuid::new_v4();
+        let arena = Arena::new(id, name, organizer, options, duration)?;
+        let arena_ref = Arc::new(Mutex::new(arena));
+
+        self.arenas.insert(id, arena_ref.clone());
+        Ok(arena_ref)
+    }
+
+    pub fn get_all_arenas(&self) -> Vec<Arc<Mutex<Arena>>> {
+        self.arenas.values().cloned().collect()
+    }
+
+    pub fn get_arena(&self, id: Uuid) -> Option<Arc<Mutex<Arena>>> {
+        self.arenas.get(&id).cloned()
+    }
+}
+
+impl Default for ArenaManager {
+    fn default() -> Self {
+        ArenaManager::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arena::{Arena, ArenaStatus};
+    use crate::game::Color::{Black, White};
+    use crate::game::GameOptions;
+    use crate::game_manager::GameManager;
+    use crate::moves::Position;
+    use crate::time_control::TimeControl;
+    use chrono::{TimeDelta, Utc};
+    use std::str::FromStr;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_arena() {
+        assert!(Arena::new(
+            Uuid::new_v4(),
+            "Hourly Bullet".to_string(),
+            Uuid::new_v4(),
+            GameOptions::default(),
+            60
+        )
+        .is_err());
+
+        let options = GameOptions {
+            time_control: Some(TimeControl::new(60, 0)),
+            ..GameOptions::default()
+        };
+        let mut arena = Arena::new(
+            Uuid::new_v4(),
+            "Hourly Bullet".to_string(),
+            Uuid::new_v4(),
+            options,
+            60,
+        )
+        .unwrap();
+        let mut game_manager = GameManager::new();
+        let start = Utc::now();
+
+        let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        arena.join(alice, 1700.0).unwrap();
+        arena.join(bob, 1600.0).unwrap();
+        assert!(arena.join(alice, 1700.0).is_err());
+        assert!(arena.update(&mut game_manager, start).is_empty());
+        arena.start(start).unwrap();
+
+        // Alice beats Bob three times in a row, berserking in the last game.
+        for round in 0..3 {
+            let seats = arena.update(&mut game_manager, start);
+            assert_eq!(seats.len(), 2);
+
+            let arena_game = arena.games.last().unwrap();
+            let (white, black) = (arena_game.white, arena_game.black);
+            assert!(white == alice || black == alice);
+            assert!(white == bob || black == bob);
+
+            let game = game_manager.get_game(arena_game.game_id).unwrap();
+            let game = game.lock().unwrap();
+            let alice_color = if white == alice { White } else { Black };
+            if round == 2 {
+                game.berserk(alice_color).unwrap();
+                for (from, to) in [("B1", "C3"), ("B8", "C6"), ("C3", "B1"), ("C6", "B8")]
+                    .iter()
+                    .cycle()
+                    .take(14)
+                {
+                    game.move_piece_at_position(
+                        &Position::from_str(from).unwrap(),
+                        &Position::from_str(to).unwrap(),
+                    )
+                    .unwrap();
+                }
+            }
+            game.resign(alice_color.opponent()).unwrap();
+        }
+
+        // 2 + 2 for the first two wins, then 4 (on a streak) + 1 (berserk) for the third. Players
+        // can join at any time, and are not paired with their last opponent if they can avoid it.
+        arena.join(carol, 1500.0).unwrap();
+        assert_eq!(arena.update(&mut game_manager, start).len(), 2);
+        let standings = arena.get_standings();
+        assert_eq!(standings[0].user_id, alice);
+        assert_eq!(standings[0].points, 9);
+        assert_eq!(standings[0].streak, 3);
+        assert_eq!(standings[1].points, 0);
+
+        let arena_game = arena.games.last().unwrap().clone();
+        assert!(arena.is_playing(carol));
+        assert!(!arena.is_playing(bob));
+        assert!(arena_game.white == carol || arena_game.black == carol);
+
+        // Withdrawn players are not paired, and the arena ends when its time runs out.
+        arena.withdraw(bob).unwrap();
+        assert!(arena.withdraw(bob).is_err());
+        let game = game_manager.get_game(arena_game.game_id).unwrap();
+        game.lock().unwrap().abort().unwrap();
+        arena.update(&mut game_manager, start);
+        assert!(!arena.is_playing(bob));
+        assert_eq!(arena.games.len(), 5);
+
+        arena.update(&mut game_manager, start + TimeDelta::minutes(60));
+        assert_eq!(arena.status, ArenaStatus::Finished);
+        assert!(arena.join(bob, 1600.0).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use crate::error::ChallengeError;
+use crate::game::GameOptions;
+use crate::lobby::ColorPreference;
+use chrono::serde::ts_milliseconds;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// An invitation to play a game with the specified settings, either addressed to a specific user
+/// or open to whoever holds its one-time code (e.g., from a shared link).
+#[derive(Clone, Debug, Serialize)]
+pub struct Challenge {
+    pub id: Uuid,
+
+    /// The ID of the user that created the challenge.
+    pub challenger: Uuid,
+
+    /// The ID of the user the challenge is addressed to, or [None] if it is an open invite.
+    pub recipient: Option<Uuid>,
+
+    /// The one-time code that must be presented to accept an open invite. This is only ever
+    /// shown to the challenger.
+    #[serde(skip)]
+    code: Option<String>,
+
+    pub options: GameOptions,
+
+    /// The color the challenger would like to play as.
+    pub color: ColorPreference,
+
+    /// The [DateTime] the challenge was created.
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl Challenge {
+    /// Get the one-time code for an open invite.
+    pub fn get_code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+}
+
+pub struct ChallengeManager {
+    challenges: BTreeMap<Uuid, Challenge>,
+}
+
+impl ChallengeManager {
+    pub fn new() -> ChallengeManager {
+        ChallengeManager {
+            challenges: BTreeMap::new(),
+        }
+    }
+
+    /// Create a challenge from the challenger to the specified recipient, or an open invite with
+    /// a one-time code if there is no recipient.
+    pub fn create_challenge(
+        &mut self,
+        challenger: Uuid,
+        recipient: Option<Uuid>,
+        options: GameOptions,
+        color: ColorPreference,
+    ) -> Result<Challenge, ChallengeError> {
+        if recipient == Some(challenger) {
+            return Err(ChallengeError::OwnChallengeError);
+        }
+
+        let challenge = Challenge {
+            id: Uuid::new_v4(),
+            challenger,
+            recipient,
+            code: recipient
+                .is_none()
+                .then(|| Uuid::new_v4().simple().to_string()),
+            options,
+            color,
+            created_at: Utc::now(),
+        };
+
+        self.challenges.insert(challenge.id, challenge.clone());
+        Ok(challenge)
+    }
+
+    pub fn get_challenge(&self, id: Uuid) -> Option<&Challenge> {
+        self.challenges.get(&id)
+    }
+
+    /// Get the challenges the specified user has created or received.
+    pub fn get_challenges_for_user(&self, user_id: Uuid) -> Vec<&Challenge> {
+        self.challenges
+            .values()
+            .filter(|c| c.challenger == user_id || c.recipient == Some(user_id))
+            .collect()
+    }
+
+    /// Accept a challenge on behalf of the specified user, who must either be its recipient or
+    /// (for an open invite) present its code. The challenge is removed, so it can only be
+    /// accepted once.
+    pub fn accept_challenge(
+        &mut self,
+        id: Uuid,
+        user_id: Uuid,
+        code: Option<&str>,
+    ) -> Result<Challenge, ChallengeError> {
+        let challenge = self
+            .challenges
+            .get(&id)
+            .ok_or(ChallengeError::ChallengeNotFoundError)?;
+
+        if challenge.challenger == user_id {
+            return Err(ChallengeError::OwnChallengeError);
+        }
+
+        match (challenge.recipient, &challenge.code) {
+            (Some(recipient), _) if recipient != user_id => {
+                return Err(ChallengeError::NotRecipientError)
+            }
+            (None, Some(expected)) if code != Some(expected.as_str()) => {
+                return Err(ChallengeError::InvalidCodeError)
+            }
+            _ => {}
+        }
+
+        Ok(self.challenges.remove(&id).unwrap())
+    }
+
+    /// Decline a challenge addressed to the specified user.
+    pub fn decline_challenge(
+        &mut self,
+        id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Challenge, ChallengeError> {
+        match self.challenges.get(&id) {
+            Some(challenge) if challenge.recipient == Some(user_id) => {
+                Ok(self.challenges.remove(&id).unwrap())
+            }
+            Some(_) => Err(ChallengeError::NotRecipientError),
+            None => Err(ChallengeError::ChallengeNotFoundError),
+        }
+    }
+
+    /// Withdraw a challenge on behalf of its challenger.
+    pub fn cancel_challenge(
+        &mut self,
+        id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Challenge, ChallengeError> {
+        match self.challenges.get(&id) {
+            Some(challenge) if challenge.challenger == user_id => {
+                Ok(self.challenges.remove(&id).unwrap())
+            }
+            Some(_) => Err(ChallengeError::NotChallengerError),
+            None => Err(ChallengeError::ChallengeNotFoundError),
+        }
+    }
+}
+
+impl Default for ChallengeManager {
+    fn default() -> Self {
+        ChallengeManager::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::challenge::ChallengeManager;
+    use crate::game::GameOptions;
+    use crate::lobby::ColorPreference;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_challenges() {
+        let mut challenge_manager = ChallengeManager::new();
+        let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let options = GameOptions::default();
+        let color = ColorPreference::White;
+        assert!(challenge_manager
+            .create_challenge(alice, Some(alice), options.clone(), color)
+            .is_err());
+
+        // Addressed challenges can only be accepted or declined by their recipient.
+        let challenge = challenge_manager
+            .create_challenge(alice, Some(bob), options.clone(), color)
+            .unwrap();
+        assert!(challenge.get_code().is_none());
+        assert_eq!(challenge_manager.get_challenges_for_user(bob).len(), 1);
+        assert!(challenge_manager
+            .accept_challenge(challenge.id, carol, None)
+            .is_err());
+        assert!(challenge_manager
+            .decline_challenge(challenge.id, alice)
+            .is_err());
+        assert!(challenge_manager
+            .decline_challenge(challenge.id, bob)
+            .is_ok());
+        assert!(challenge_manager
+            .accept_challenge(challenge.id, bob, None)
+            .is_err());
+
+        // Open invites can be accepted once, by anyone with the code.
+        let invite = challenge_manager
+            .create_challenge(alice, None, options, color)
+            .unwrap();
+        let code = invite.get_code().unwrap().to_string();
+        assert!(challenge_manager
+            .accept_challenge(invite.id, carol, Some("wrong"))
+            .is_err());
+        assert!(challenge_manager
+            .accept_challenge(invite.id, alice, Some(&code))
+            .is_err());
+        assert!(challenge_manager
+            .accept_challenge(invite.id, carol, Some(&code))
+            .is_ok());
+        assert!(challenge_manager
+            .accept_challenge(invite.id, bob, Some(&code))
+            .is_err());
+    }
+}
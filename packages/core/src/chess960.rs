@@ -0,0 +1,169 @@
+use crate::fen::Fen;
+use crate::game::Color::{Black, White};
+use crate::game::PieceKind::{Bishop, King, Knight, Pawn, Queen, Rook};
+use crate::game::{GameBoard, Piece, PieceKind};
+use rand::Rng;
+
+/// The number of Chess960 starting positions.
+pub const POSITION_COUNT: usize = 960;
+
+/// The index of the standard starting position (RNBQKBNR) among the Chess960 starting positions.
+pub const STANDARD_POSITION: usize = 518;
+
+/// The placements of the two knights among the five squares left after placing the bishops and
+/// the queen, by the knights' part of the position index.
+const KNIGHT_PLACEMENTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// Get the back rank (from the a-file to the h-file) of the Chess960 starting position with the
+/// specified index (from 0 to 959, in Scharnagl's numbering), or [None] if the index is out of
+/// range.
+pub fn get_back_rank(index: usize) -> Option<[PieceKind; 8]> {
+    if index >= POSITION_COUNT {
+        return None;
+    }
+
+    let mut rank: [Option<PieceKind>; 8] = [None; 8];
+    let mut n = index;
+
+    // The bishops are placed on opposite colors: first the light-squared bishop (on the b, d, f
+    // or h-file), then the dark-squared bishop (on the a, c, e or g-file).
+    rank[2 * (n % 4) + 1] = Some(Bishop);
+    n /= 4;
+    rank[2 * (n % 4)] = Some(Bishop);
+    n /= 4;
+
+    // The remaining pieces fill the empty squares in order.
+    let place = |rank: &mut [Option<PieceKind>; 8], nth_empty: usize, kind: PieceKind| {
+        let file = (0..8)
+            .filter(|&file| rank[file].is_none())
+            .nth(nth_empty)
+            .unwrap();
+        rank[file] = Some(kind);
+    };
+
+    place(&mut rank, n % 6, Queen);
+    n /= 6;
+
+    // The second knight is placed after the first, so it skips one fewer empty square.
+    let (first, second) = KNIGHT_PLACEMENTS[n];
+    place(&mut rank, first, Knight);
+    place(&mut rank, second - 1, Knight);
+
+    // The king goes between the rooks on the three squares left.
+    place(&mut rank, 0, Rook);
+    place(&mut rank, 0, King);
+    place(&mut rank, 0, Rook);
+
+    Some(rank.map(Option::unwrap))
+}
+
+/// Get the Chess960 starting position with the specified index, or [None] if the index is out of
+/// range. Both players can castle with either rook.
+pub fn get_starting_fen(index: usize) -> Option<Fen> {
+    let back_rank = get_back_rank(index)?;
+
+    let mut board = GameBoard::empty(8);
+    for file in 0..8 {
+        board[0][file] = Some(Piece::new(back_rank[file], Black));
+        board[1][file] = Some(Piece::new(Pawn, Black));
+        board[6][file] = Some(Piece::new(Pawn, White));
+        board[7][file] = Some(Piece::new(back_rank[file], White));
+    }
+
+    let rook_files: Vec<usize> = (0..8).filter(|&file| back_rank[file] == Rook).collect();
+    let castling = [White, Black]
+        .into_iter()
+        .flat_map(|color| rook_files.iter().rev().map(move |&file| (color, file)))
+        .collect();
+
+    Some(Fen {
+        board,
+        turn: White,
+        castling,
+        en_passant: None,
+        halfmove_clock: 0,
+        fullmove_number: 1,
+    })
+}
+
+/// Choose the index of a Chess960 starting position at random.
+pub fn random_index() -> usize {
+    rand::thread_rng().gen_range(0..POSITION_COUNT)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chess960::{get_back_rank, get_starting_fen, POSITION_COUNT, STANDARD_POSITION};
+    use crate::fen::{Fen, STARTING_FEN};
+    use crate::game::PieceKind::{Bishop, King, Rook};
+    use crate::game::{Game, GameOptions, Variant};
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_starting_positions() {
+        assert_eq!(
+            get_starting_fen(STANDARD_POSITION).unwrap(),
+            Fen::from_str(STARTING_FEN).unwrap()
+        );
+        assert_eq!(
+            get_starting_fen(0).unwrap().to_string(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+        );
+        assert_eq!(
+            get_starting_fen(959).unwrap().to_string(),
+            "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1"
+        );
+        assert_eq!(get_back_rank(POSITION_COUNT), None);
+
+        // Every position is distinct, with the bishops on opposite colors and the king between
+        // the rooks.
+        let mut back_ranks = HashSet::new();
+        for index in 0..POSITION_COUNT {
+            let back_rank = get_back_rank(index).unwrap();
+            assert!(back_ranks.insert(format!("{:?}", back_rank)));
+
+            let files = |kind| (0..8).filter(move |&file| back_rank[file] == kind);
+            let bishops: Vec<usize> = files(Bishop).collect();
+            assert_ne!(bishops[0] % 2, bishops[1] % 2);
+
+            let rooks: Vec<usize> = files(Rook).collect();
+            let king = files(King).next().unwrap();
+            assert!(rooks[0] < king && king < rooks[1]);
+        }
+    }
+
+    #[test]
+    fn test_chess960_game() {
+        let options = GameOptions {
+            variant: Variant::Chess960,
+            chess960_position: Some(0),
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        assert_eq!(
+            Fen::from_game(&game).to_string(),
+            get_starting_fen(0).unwrap().to_string()
+        );
+
+        // Without an index, a random starting position is chosen and recorded.
+        let options = GameOptions {
+            variant: Variant::Chess960,
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        let index = game.get_options().chess960_position.unwrap();
+        assert_eq!(Fen::from_game(&game), get_starting_fen(index).unwrap());
+    }
+}
@@ -0,0 +1,354 @@
+use crate::book::Book;
+use crate::game::Color::White;
+use crate::game::PieceKind::{Bishop, Knight, Pawn};
+use crate::game::{Color, Game, GameBoard, Piece};
+use crate::moves::Position;
+use crate::rules::{Rules, VariantState};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// The score of a position in which the player to move has been checkmated, from their point of
+/// view. Mates found sooner score further from zero.
+const MATE_SCORE: i32 = 100_000;
+
+/// The value of one pawn, in the units scores are given in (centipawns).
+const PAWN_VALUE: i32 = 100;
+
+/// The result of a search: the best move found and its score, in centipawns from the point of
+/// view of the player to move.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct SearchResult {
+    #[serde(rename = "move")]
+    pub mv: (Position, Position),
+    pub score: i32,
+}
+
+/// A simple built-in engine: a fixed-depth alpha-beta search, extended with captures, over an
+/// evaluation of material and piece placement. It plays by the rules of the game's variant, but
+/// never drops pieces (in Crazyhouse).
+#[derive(Clone, Debug)]
+pub struct Engine {
+    /// The number of plies to search (before only considering captures).
+    pub depth: u32,
+
+    /// The opening book to play from while the game is in it, if any. Book moves are chosen at
+    /// random by weight, so that the engine's openings vary.
+    pub book: Option<Arc<Book>>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Engine::new(3)
+    }
+}
+
+/// A position reached in the search: the board, and the state the variant's rules keep besides
+/// it (e.g., the number of checks each player has given in three-check).
+#[derive(Clone)]
+struct Node {
+    board: GameBoard,
+    state: VariantState,
+}
+
+impl Node {
+    /// Get the position after the piece at `position` moves to `new_position`.
+    fn play(&self, rules: &dyn Rules, position: &Position, new_position: &Position) -> Node {
+        let mut state = self.state.clone();
+        let board = rules.apply_move(&self.board, &mut state, position, new_position);
+        Node { board, state }
+    }
+}
+
+impl Engine {
+    pub fn new(depth: u32) -> Engine {
+        Engine { depth, book: None }
+    }
+
+    /// Choose a move for the player to move in the specified game (from the book, if the position
+    /// is in it), or [None] if they have no legal moves.
+    pub fn best_move(&self, game: &Game) -> Option<(Position, Position)> {
+        if let Some(mv) = self.book.as_ref().and_then(|book| book.choose_move(game)) {
+            return Some(mv);
+        }
+        self.search(game).map(|result| result.mv)
+    }
+
+    /// Search the current position of the specified game. Returns [None] if the player to move
+    /// has no legal moves.
+    pub fn search(&self, game: &Game) -> Option<SearchResult> {
+        let rules = game.get_rules();
+        let node = Node {
+            board: *game.board.lock().unwrap(),
+            state: game.get_variant_state(),
+        };
+        let color = game.get_current_move();
+
+        let mut best: Option<SearchResult> = None;
+        let mut alpha = -MATE_SCORE - 1;
+        for (position, new_position) in Engine::ordered_moves(rules, &node.board, color) {
+            let after = node.play(rules, &position, &new_position);
+            let score = -Engine::negamax(
+                rules,
+                &after,
+                color.opponent(),
+                self.depth.saturating_sub(1),
+                1,
+                -MATE_SCORE - 1,
+                -alpha,
+            );
+
+            if best.is_none_or(|best| score > best.score) {
+                best = Some(SearchResult {
+                    mv: (position, new_position),
+                    score,
+                });
+                alpha = alpha.max(score);
+            }
+        }
+        best
+    }
+
+    fn negamax(
+        rules: &dyn Rules,
+        node: &Node,
+        color: Color,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if depth == 0 {
+            return Engine::quiesce(rules, node, color, ply, alpha, beta);
+        }
+
+        let moves = Engine::ordered_moves(rules, &node.board, color);
+        if let Some(score) = Engine::outcome_score(rules, node, color, &moves, ply) {
+            return score;
+        }
+
+        for (position, new_position) in moves {
+            let after = node.play(rules, &position, &new_position);
+            let score = -Engine::negamax(
+                rules,
+                &after,
+                color.opponent(),
+                depth - 1,
+                ply + 1,
+                -beta,
+                -alpha,
+            );
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    /// Search only captures, so that the evaluation is not taken in the middle of an exchange.
+    fn quiesce(
+        rules: &dyn Rules,
+        node: &Node,
+        color: Color,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        let moves = Engine::ordered_moves(rules, &node.board, color);
+        if let Some(score) = Engine::outcome_score(rules, node, color, &moves, ply) {
+            return score;
+        }
+
+        // The player can usually do at least as well as the current position by not capturing.
+        let standing = Engine::evaluate(&node.board, color);
+        if standing >= beta {
+            return beta;
+        }
+        alpha = alpha.max(standing);
+
+        for (position, new_position) in moves {
+            if !Engine::is_capture(&node.board, color, &new_position) {
+                // The moves are ordered with captures first.
+                break;
+            }
+
+            let after = node.play(rules, &position, &new_position);
+            let score = -Engine::quiesce(rules, &after, color.opponent(), ply + 1, -beta, -alpha);
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    /// Get the score of a position in which the game is over (e.g., the player to move has been
+    /// checkmated), or [None] if the game goes on.
+    fn outcome_score(
+        rules: &dyn Rules,
+        node: &Node,
+        color: Color,
+        moves: &[(Position, Position)],
+        ply: i32,
+    ) -> Option<i32> {
+        let outcome = rules.get_outcome(&node.board, &node.state, color, moves)?;
+        Some(match outcome.winner {
+            Some(winner) if winner == color => MATE_SCORE - ply,
+            Some(_) => -MATE_SCORE + ply,
+            None => 0,
+        })
+    }
+
+    /// Get the legal moves for the specified player, with captures of the most valuable pieces
+    /// first.
+    fn ordered_moves(
+        rules: &dyn Rules,
+        board: &GameBoard,
+        color: Color,
+    ) -> Vec<(Position, Position)> {
+        let mut moves = rules.get_legal_moves(board, color);
+        moves.sort_by_key(|(_, new_position)| {
+            if !Engine::is_capture(board, color, new_position) {
+                return 1;
+            }
+            let captured = board[new_position.rank][new_position.file].unwrap();
+            -(captured.kind.material_value() as i32)
+        });
+        moves
+    }
+
+    /// Check whether the specified player moving to `new_position` captures a piece (rather than
+    /// castling with their own rook).
+    fn is_capture(board: &GameBoard, color: Color, new_position: &Position) -> bool {
+        board[new_position.rank][new_position.file].is_some_and(|piece| piece.color != color)
+    }
+
+    /// Evaluate a position from the point of view of the specified player.
+    pub fn evaluate(board: &GameBoard, color: Color) -> i32 {
+        let mut score = 0;
+        for (rank, row) in board.iter().enumerate() {
+            for (file, square) in row.iter().enumerate() {
+                let Some(piece) = square else {
+                    continue;
+                };
+
+                let value = Engine::piece_value(piece, rank, file, board.files());
+                score += if piece.color == color { value } else { -value };
+            }
+        }
+        score
+    }
+
+    /// Get the value of a piece on the specified square of a board with the specified number of
+    /// files: its material value, plus a bonus for advanced pawns and for knights and bishops near
+    /// the center.
+    fn piece_value(piece: &Piece, rank: usize, file: usize, files: usize) -> i32 {
+        let material = piece.kind.material_value() as i32 * PAWN_VALUE;
+
+        // The distance from the center, from 0 (d4, d5, e4, e5 on an 8-file board) to 3 (the
+        // corners of an 8-file board).
+        let center_distance = |x: usize, size: usize| {
+            let half = size / 2;
+            if x < half {
+                half - 1 - x
+            } else {
+                x - half
+            }
+        };
+        let distance = center_distance(rank, 8).max(center_distance(file, files)) as i32;
+
+        let placement = match piece.kind {
+            Pawn => {
+                let advanced = if piece.color == White {
+                    6 - rank as i32
+                } else {
+                    rank as i32 - 1
+                };
+                advanced * 5
+            }
+            Knight | Bishop => (3 - distance) * 10,
+            _ => 0,
+        };
+
+        material + placement
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::book::Book;
+    use crate::engine::{Engine, MATE_SCORE};
+    use crate::fen::Fen;
+    use crate::game::{Game, GameOptions};
+    use crate::moves::Position;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    fn game_from_fen(fen: &str) -> Game {
+        let options = GameOptions {
+            starting_fen: Some(Fen::from_str(fen).unwrap()),
+            ..GameOptions::default()
+        };
+        Game::new_with_options(None, options)
+    }
+
+    #[test]
+    fn test_search() {
+        // Black mates in one with Qh4.
+        let game = game_from_fen("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2");
+        let result = Engine::new(2).search(&game).unwrap();
+        assert_eq!(
+            result.mv,
+            (
+                Position::from_str("D8").unwrap(),
+                Position::from_str("H4").unwrap()
+            )
+        );
+        assert_eq!(result.score, MATE_SCORE - 1);
+
+        // White wins the undefended queen.
+        let game = game_from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1");
+        let mv = Engine::new(2).best_move(&game).unwrap();
+        assert_eq!(
+            mv,
+            (
+                Position::from_str("D2").unwrap(),
+                Position::from_str("D5").unwrap()
+            )
+        );
+
+        // There is no move once the game is over.
+        let game = game_from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+        assert!(Engine::default().best_move(&game).is_none());
+    }
+
+    #[test]
+    fn test_book() {
+        // A book with a2-a3 as the only move in the starting position (from a2, row 1, to a3).
+        let mut bytes = 0x463b96181691fc9c_u64.to_be_bytes().to_vec();
+        bytes.extend((1_u16 << 9 | 2 << 3).to_be_bytes());
+        bytes.extend(1_u16.to_be_bytes());
+        bytes.extend(0_u32.to_be_bytes());
+
+        let engine = Engine {
+            book: Some(Arc::new(Book::from_bytes(&bytes).unwrap())),
+            ..Engine::new(1)
+        };
+        let game = Game::new();
+        assert_eq!(
+            engine.best_move(&game),
+            Some((
+                Position::from_str("A2").unwrap(),
+                Position::from_str("A3").unwrap()
+            ))
+        );
+
+        // Out of the book, the engine searches.
+        game.move_piece_at_position(
+            &Position::from_str("E2").unwrap(),
+            &Position::from_str("E4").unwrap(),
+        )
+        .unwrap();
+        assert!(engine.best_move(&game).is_some());
+    }
+}
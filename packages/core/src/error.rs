@@ -5,14 +5,338 @@ pub enum MoveError {
     PieceNotFoundError,
     IllegalMoveError,
     OutOfTurnError,
+    GameOverError,
+    PieceNotInPocketError,
+    IllegalPositionError(SetupError),
 }
 
 impl Display for MoveError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match *self {
+        match self {
             MoveError::PieceNotFoundError => write!(f, "no piece found at the specified position"),
             MoveError::IllegalMoveError => write!(f, "illegal move"),
             MoveError::OutOfTurnError => write!(f, "cannot move out of turn"),
+            MoveError::GameOverError => write!(f, "the game is over"),
+            MoveError::PieceNotInPocketError => {
+                write!(f, "the player has no piece of that kind to drop")
+            }
+            MoveError::IllegalPositionError(e) => {
+                write!(f, "the game cannot start from an illegal position: {}", e)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ForkError {
+    GameNotFoundError,
+    InvalidPlyError,
+    ReplayError(MoveError),
+}
+
+impl Display for ForkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForkError::GameNotFoundError => write!(f, "no game found for the specified ID"),
+            ForkError::InvalidPlyError => write!(f, "ply is beyond the end of the game"),
+            ForkError::ReplayError(e) => write!(f, "failed to replay move: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AnalysisError {
+    NodeNotFoundError,
+    RootNodeError,
+    MoveError(MoveError),
+    AnnotationError(AnnotationError),
+}
+
+impl Display for AnalysisError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisError::NodeNotFoundError => write!(f, "no node found for the specified ID"),
+            AnalysisError::RootNodeError => write!(f, "cannot modify the root node"),
+            AnalysisError::MoveError(e) => write!(f, "{}", e),
+            AnalysisError::AnnotationError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AnnotationError {
+    InvalidPlyError,
+    InvalidCommentError,
+}
+
+impl Display for AnnotationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            AnnotationError::InvalidPlyError => write!(f, "no move found at the specified ply"),
+            AnnotationError::InvalidCommentError => {
+                write!(
+                    f,
+                    "a comment cannot contain both a closing brace ('}}') and a line break"
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PgnError {
+    InvalidSyntaxError(String),
+    InvalidMoveError(String),
+    UnsupportedError(String),
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgnError::InvalidSyntaxError(token) => write!(f, "invalid PGN near '{}'", token),
+            PgnError::InvalidMoveError(san) => write!(f, "illegal or ambiguous move '{}'", san),
+            PgnError::UnsupportedError(feature) => {
+                write!(f, "unsupported PGN feature: {}", feature)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ActionError {
+    GameOverError,
+    NoDrawOfferError,
+    AbortNotAllowedError,
+    BerserkNotAllowedError,
+}
+
+impl Display for ActionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ActionError::GameOverError => write!(f, "the game is over"),
+            ActionError::NoDrawOfferError => write!(f, "the opponent has not offered a draw"),
+            ActionError::AbortNotAllowedError => {
+                write!(f, "cannot abort once both players have moved")
+            }
+            ActionError::BerserkNotAllowedError => {
+                write!(f, "can only berserk in a timed game, before making a move")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SeatError {
+    SeatTakenError,
+}
+
+impl Display for SeatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            SeatError::SeatTakenError => write!(f, "the seat has already been claimed"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AccountError {
+    InvalidUsernameError,
+    UsernameTakenError,
+    WeakPasswordError,
+    InvalidCredentialsError,
+}
+
+impl Display for AccountError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            AccountError::InvalidUsernameError => {
+                write!(
+                    f,
+                    "usernames may only contain letters, numbers and underscores"
+                )
+            }
+            AccountError::UsernameTakenError => write!(f, "the username is already taken"),
+            AccountError::WeakPasswordError => {
+                write!(f, "passwords must be at least 8 characters long")
+            }
+            AccountError::InvalidCredentialsError => write!(f, "invalid username or password"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SeekError {
+    SeekNotFoundError,
+    NotOwnerError,
+}
+
+impl Display for SeekError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            SeekError::SeekNotFoundError => write!(f, "the seek does not exist"),
+            SeekError::NotOwnerError => write!(f, "the seek belongs to another player"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum FenError {
+    InvalidSyntaxError,
+    InvalidBoardError,
+    InvalidTurnError,
+    InvalidCastlingError,
+    InvalidEnPassantError,
+    InvalidClockError,
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            FenError::InvalidSyntaxError => write!(f, "a FEN must have between 4 and 6 fields"),
+            FenError::InvalidBoardError => write!(f, "invalid piece placement"),
+            FenError::InvalidTurnError => write!(f, "the player to move must be 'w' or 'b'"),
+            FenError::InvalidCastlingError => write!(f, "invalid castling rights"),
+            FenError::InvalidEnPassantError => write!(f, "invalid en passant square"),
+            FenError::InvalidClockError => write!(f, "invalid halfmove clock or move number"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SetupError {
+    GameStartedError,
+    InvalidSquareError,
+    KingCountError,
+    PawnOnBackRankError,
+    TooManyPiecesError,
+    InvalidCastlingError,
+    InvalidEnPassantError,
+    OpponentInCheckError,
+    ImpossibleCheckError,
+}
+
+impl Display for SetupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            SetupError::GameStartedError => {
+                write!(f, "the position cannot be edited once the game has started")
+            }
+            SetupError::InvalidSquareError => write!(f, "the square is not on the board"),
+            SetupError::KingCountError => write!(f, "each player must have exactly one king"),
+            SetupError::PawnOnBackRankError => {
+                write!(f, "pawns cannot stand on the first or last rank")
+            }
+            SetupError::TooManyPiecesError => {
+                write!(f, "a player has more pieces than a game can have")
+            }
+            SetupError::InvalidCastlingError => {
+                write!(
+                    f,
+                    "castling requires an unmoved king and rook on the back rank"
+                )
+            }
+            SetupError::InvalidEnPassantError => {
+                write!(f, "no pawn can have just moved past the en passant square")
+            }
+            SetupError::OpponentInCheckError => write!(f, "the player not to move is in check"),
+            SetupError::ImpossibleCheckError => {
+                write!(
+                    f,
+                    "the player to move is in check by an impossible set of pieces"
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ChallengeError {
+    ChallengeNotFoundError,
+    OwnChallengeError,
+    NotRecipientError,
+    NotChallengerError,
+    InvalidCodeError,
+}
+
+impl Display for ChallengeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ChallengeError::ChallengeNotFoundError => write!(f, "the challenge does not exist"),
+            ChallengeError::OwnChallengeError => write!(f, "players cannot challenge themselves"),
+            ChallengeError::NotRecipientError => {
+                write!(f, "the challenge is addressed to another player")
+            }
+            ChallengeError::NotChallengerError => {
+                write!(f, "the challenge was created by another player")
+            }
+            ChallengeError::InvalidCodeError => write!(f, "the invite code is invalid"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TournamentError {
+    RegistrationClosedError,
+    AlreadyRegisteredError,
+    NotEnoughPlayersError,
+    RoundInProgressError,
+    TournamentFinishedError,
+}
+
+impl Display for TournamentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            TournamentError::RegistrationClosedError => {
+                write!(f, "registration for the tournament has closed")
+            }
+            TournamentError::AlreadyRegisteredError => {
+                write!(f, "the player is already registered for the tournament")
+            }
+            TournamentError::NotEnoughPlayersError => {
+                write!(f, "a tournament needs at least two players")
+            }
+            TournamentError::RoundInProgressError => {
+                write!(f, "the current round has not finished")
+            }
+            TournamentError::TournamentFinishedError => write!(f, "the tournament has finished"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ArenaError {
+    UntimedError,
+    ArenaStartedError,
+    ArenaFinishedError,
+    AlreadyJoinedError,
+    NotJoinedError,
+}
+
+impl Display for ArenaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ArenaError::UntimedError => write!(f, "arena games must have a time control"),
+            ArenaError::ArenaStartedError => write!(f, "the arena has already started"),
+            ArenaError::ArenaFinishedError => write!(f, "the arena has finished"),
+            ArenaError::AlreadyJoinedError => write!(f, "the player has already joined the arena"),
+            ArenaError::NotJoinedError => write!(f, "the player is not in the arena"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BookError {
+    ReadError(String),
+    InvalidLengthError,
+}
+
+impl Display for BookError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BookError::ReadError(e) => write!(f, "failed to read book: {}", e),
+            BookError::InvalidLengthError => {
+                write!(f, "a book must consist of whole 16-byte entries")
+            }
         }
     }
 }
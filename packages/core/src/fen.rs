@@ -0,0 +1,500 @@
+use crate::error::FenError;
+use crate::game::Color::{Black, White};
+use crate::game::PieceKind::{King, Pawn, Rook};
+use crate::game::{Color, Game, GameBoard, Piece, PieceKind, MAX_FILES, RANKS};
+use crate::moves::{Move, Position};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter, Write};
+use std::str::FromStr;
+
+/// The standard starting position.
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// A position in Forsyth-Edwards Notation (FEN).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fen {
+    /// The pieces on the board. Pawns that are not on their starting rank, and kings and rooks
+    /// that can no longer castle, are treated as having already moved.
+    pub board: GameBoard,
+
+    /// The player to move.
+    pub turn: Color,
+
+    /// The rooks that can still be castled with, as the color and file of each rook.
+    pub castling: Vec<(Color, usize)>,
+
+    /// The square a pawn skipped over with a double step on the previous move, if any.
+    pub en_passant: Option<Position>,
+
+    /// The number of plies since the last capture or pawn move.
+    pub halfmove_clock: usize,
+
+    /// The number of the current move, starting at 1 and incremented after Black moves.
+    pub fullmove_number: usize,
+}
+
+impl Fen {
+    /// Get the FEN for the current position of a game.
+    pub fn from_game(game: &Game) -> Fen {
+        let board = *game.board.lock().unwrap();
+
+        // Castling is possible with any rook on the back rank that has not moved, if the king
+        // has not moved either.
+        let mut castling = Vec::new();
+        for color in [White, Black] {
+            let back_rank = Fen::back_rank(color);
+            let unmoved = |kind: PieceKind, file: usize| matches!(board[back_rank][file], Some(piece) if piece.kind == kind && piece.color == color && piece.move_count == 0);
+            if !(0..board.files()).any(|file| unmoved(King, file)) {
+                continue;
+            }
+
+            for file in (0..board.files()).rev() {
+                if unmoved(Rook, file) {
+                    castling.push((color, file));
+                }
+            }
+        }
+
+        // A pawn that has just made a double step skips over a square. Before the first move,
+        // the square is the one given in the starting position.
+        let en_passant = match game.get_moves().last() {
+            None => game.get_starting_position().en_passant,
+            Some(Move::Normal(from, to)) => {
+                let double_step = from.rank.abs_diff(to.rank) == 2 && from.file == to.file;
+                board[to.rank][to.file]
+                    .filter(|piece| piece.kind == Pawn && double_step)
+                    .map(|_| Position::new((from.rank + to.rank) / 2, from.file))
+            }
+            Some(_) => None,
+        };
+
+        Fen {
+            board,
+            turn: game.get_current_move(),
+            castling,
+            en_passant,
+            halfmove_clock: game.get_halfmove_clock(),
+            fullmove_number: game.get_fullmove_number(),
+        }
+    }
+
+    /// Get the index of the rank (in the [GameBoard]) that the specified player's pieces start on.
+    fn back_rank(color: Color) -> usize {
+        match color {
+            White => 7,
+            Black => 0,
+        }
+    }
+
+    /// Parse the piece placement. The board is as wide as its ranks, which must all have the same
+    /// number of files (up to [MAX_FILES]).
+    fn parse_board(placement: &str) -> Result<GameBoard, FenError> {
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != RANKS {
+            return Err(FenError::InvalidBoardError);
+        }
+
+        let mut squares: Vec<Vec<Option<Piece>>> = Vec::new();
+        for pieces in ranks {
+            let mut rank = Vec::new();
+            let mut chars = pieces.chars().peekable();
+            while let Some(c) = chars.next() {
+                if let Some(digit) = c.to_digit(10) {
+                    // Runs of more than 9 empty squares take two digits (e.g., `10`).
+                    let mut empty = digit as usize;
+                    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        empty = empty * 10 + digit as usize;
+                        chars.next();
+                    }
+                    if empty == 0 || rank.len() + empty > MAX_FILES {
+                        return Err(FenError::InvalidBoardError);
+                    }
+                    rank.resize(rank.len() + empty, None);
+                    continue;
+                }
+
+                let kind = PieceKind::from_char(c.to_ascii_uppercase())
+                    .ok_or(FenError::InvalidBoardError)?;
+                let color = if c.is_ascii_uppercase() { White } else { Black };
+                if rank.len() >= MAX_FILES {
+                    return Err(FenError::InvalidBoardError);
+                }
+                rank.push(Some(Piece::new(kind, color)));
+            }
+
+            if rank.is_empty()
+                || squares
+                    .first()
+                    .is_some_and(|first| first.len() != rank.len())
+            {
+                return Err(FenError::InvalidBoardError);
+            }
+            squares.push(rank);
+        }
+
+        let mut board = GameBoard::empty(squares[0].len());
+        for (rank, squares) in board.iter_mut().zip(squares) {
+            rank.copy_from_slice(&squares);
+        }
+        Ok(board)
+    }
+
+    /// Get the file of the specified player's king on their back rank, if it is there.
+    fn king_file(board: &GameBoard, color: Color) -> Option<usize> {
+        let back_rank = Fen::back_rank(color);
+        (0..board.files()).find(|&file| {
+            matches!(board[back_rank][file], Some(Piece { kind: King, color: c, .. }) if c == color)
+        })
+    }
+
+    /// Get the file of the specified player's outermost rook on their back rank on the kingside
+    /// (or queenside) of their king.
+    fn outermost_rook_file(board: &GameBoard, color: Color, kingside: bool) -> Option<usize> {
+        let back_rank = Fen::back_rank(color);
+        let king_file = Fen::king_file(board, color)?;
+        let is_rook = |&file: &usize| matches!(board[back_rank][file], Some(Piece { kind: Rook, color: c, .. }) if c == color);
+
+        if kingside {
+            (king_file + 1..board.files()).rev().find(is_rook)
+        } else {
+            (0..king_file).find(is_rook)
+        }
+    }
+
+    /// Parse castling rights in either X-FEN (`KQkq`, with the file of the rook given instead when
+    /// it is not the outermost rook on that side) or Shredder-FEN (the files of the rooks, e.g.,
+    /// `HAha`).
+    pub(crate) fn parse_castling(
+        castling: &str,
+        board: &GameBoard,
+    ) -> Result<Vec<(Color, usize)>, FenError> {
+        if castling == "-" {
+            return Ok(Vec::new());
+        }
+
+        let mut rights = Vec::new();
+        for c in castling.chars() {
+            let color = if c.is_ascii_uppercase() { White } else { Black };
+            let file = match c.to_ascii_lowercase() {
+                // Without a rook on that side, the right is kept as in standard chess.
+                'k' => Fen::outermost_rook_file(board, color, true).unwrap_or(board.files() - 1),
+                'q' => Fen::outermost_rook_file(board, color, false).unwrap_or(0),
+                file @ 'a'..='z' if ((file as u8 - b'a') as usize) < board.files() => {
+                    file as usize - 'a' as usize
+                }
+                _ => return Err(FenError::InvalidCastlingError),
+            };
+
+            let right = (color, file);
+            if rights.contains(&right) {
+                return Err(FenError::InvalidCastlingError);
+            }
+            rights.push(right);
+        }
+
+        Ok(rights)
+    }
+
+    /// Get the castling rights, as X-FEN or Shredder-FEN.
+    fn get_castling_string(&self, shredder: bool) -> String {
+        if self.castling.is_empty() {
+            return "-".to_string();
+        }
+
+        let mut castling = String::new();
+        for color in [White, Black] {
+            let mut files: Vec<usize> = self
+                .castling
+                .iter()
+                .filter(|(c, _)| *c == color)
+                .map(|(_, file)| *file)
+                .collect();
+            files.sort_by(|a, b| b.cmp(a));
+
+            for file in files {
+                let king_file = Fen::king_file(&self.board, color);
+                let kingside = king_file.is_none_or(|king_file| file > king_file);
+                let outermost = Fen::outermost_rook_file(&self.board, color, kingside)
+                    .is_none_or(|outermost| outermost == file);
+
+                let c = match (shredder, outermost, kingside) {
+                    (false, true, true) => 'K',
+                    (false, true, false) => 'Q',
+                    _ => (b'A' + file as u8) as char,
+                };
+                castling.push(match color {
+                    White => c,
+                    Black => c.to_ascii_lowercase(),
+                });
+            }
+        }
+        castling
+    }
+
+    /// Get the FEN with the castling rights in Shredder-FEN (e.g., `HAha` rather than `KQkq`).
+    pub fn to_shredder_string(&self) -> String {
+        let fen = self.to_string();
+        let mut fields: Vec<String> = fen.split(' ').map(str::to_string).collect();
+        fields[2] = self.get_castling_string(true);
+        fields.join(" ")
+    }
+
+    /// Mark the pieces that must have moved already: pawns off their starting rank, and kings and
+    /// rooks without castling rights.
+    pub(crate) fn set_move_counts(&mut self) {
+        for (rank, row) in self.board.iter_mut().enumerate() {
+            for (file, square) in row.iter_mut().enumerate() {
+                let Some(piece) = square else {
+                    continue;
+                };
+
+                let back_rank = Fen::back_rank(piece.color);
+                let can_castle = |file: Option<usize>| {
+                    rank == back_rank
+                        && self
+                            .castling
+                            .iter()
+                            .any(|(color, f)| *color == piece.color && file.is_none_or(|x| x == *f))
+                };
+
+                let unmoved = match piece.kind {
+                    Pawn => rank == 6 && piece.color == White || rank == 1 && piece.color == Black,
+                    King => can_castle(None),
+                    Rook => can_castle(Some(file)),
+                    _ => true,
+                };
+                piece.move_count = if unmoved { 0 } else { 1 };
+            }
+        }
+    }
+}
+
+impl Default for Fen {
+    fn default() -> Self {
+        Fen::from_str(STARTING_FEN).unwrap()
+    }
+}
+
+impl FromStr for Fen {
+    type Err = FenError;
+
+    /// Parse a FEN string. The halfmove clock and fullmove number may be omitted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(FenError::InvalidSyntaxError);
+        }
+
+        let board = Fen::parse_board(fields[0])?;
+
+        let turn = match fields[1] {
+            "w" => White,
+            "b" => Black,
+            _ => return Err(FenError::InvalidTurnError),
+        };
+
+        let castling = Fen::parse_castling(fields[2], &board)?;
+
+        let en_passant = match fields[3] {
+            "-" => None,
+            square => {
+                let position =
+                    Position::from_str(square).map_err(|_| FenError::InvalidEnPassantError)?;
+                if position.rank != 2 && position.rank != 5 || !board.contains(&position) {
+                    return Err(FenError::InvalidEnPassantError);
+                }
+                Some(position)
+            }
+        };
+
+        let parse_number = |field: Option<&&str>, default: usize| match field {
+            Some(field) => field.parse().map_err(|_| FenError::InvalidClockError),
+            None => Ok(default),
+        };
+        let halfmove_clock = parse_number(fields.get(4), 0)?;
+        let fullmove_number = parse_number(fields.get(5), 1)?;
+        if fullmove_number == 0 {
+            return Err(FenError::InvalidClockError);
+        }
+
+        let mut fen = Fen {
+            board,
+            turn,
+            castling,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+        };
+        fen.set_move_counts();
+        Ok(fen)
+    }
+}
+
+impl Display for Fen {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (rank, row) in self.board.iter().enumerate() {
+            if rank > 0 {
+                f.write_char('/')?;
+            }
+
+            let mut empty = 0;
+            for square in row {
+                match square {
+                    Some(piece) => {
+                        if empty > 0 {
+                            write!(f, "{}", empty)?;
+                            empty = 0;
+                        }
+
+                        let c = piece.kind.char();
+                        f.write_char(match piece.color {
+                            White => c,
+                            Black => c.to_ascii_lowercase(),
+                        })?;
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                write!(f, "{}", empty)?;
+            }
+        }
+
+        let turn = match self.turn {
+            White => 'w',
+            Black => 'b',
+        };
+        write!(f, " {} ", turn)?;
+
+        f.write_str(&self.get_castling_string(false))?;
+
+        match self.en_passant {
+            Some(square) => write!(f, " {}", square.to_string().to_lowercase())?,
+            None => f.write_str(" -")?,
+        }
+
+        write!(f, " {} {}", self.halfmove_clock, self.fullmove_number)
+    }
+}
+
+impl Serialize for Fen {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Fen {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Fen::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chess960;
+    use crate::fen::{Fen, STARTING_FEN};
+    use crate::game::Color::{Black, White};
+    use crate::game::{Game, GameOptions};
+    use crate::moves::Position;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_round_trip() {
+        for fen in [
+            STARTING_FEN,
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+            "8/5k2/8/8/3K4/8/8/8 w - - 49 80",
+            "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1",
+        ] {
+            assert_eq!(Fen::from_str(fen).unwrap().to_string(), fen);
+        }
+
+        assert_eq!(
+            Fen::from_str("8/8/8/8/8/8/8/K6k w - -")
+                .unwrap()
+                .to_string(),
+            "8/8/8/8/8/8/8/K6k w - - 0 1"
+        );
+
+        for invalid in [
+            "",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KKkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e4 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0",
+        ] {
+            assert!(Fen::from_str(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_chess960_castling() {
+        // K and Q stand for the outermost rook on either side; other rooks are named by file.
+        let fen = Fen::from_str("rk2r3/8/8/8/8/8/8/RK2R2R w EQk - 0 1").unwrap();
+        assert_eq!(fen.castling, vec![(White, 4), (White, 0), (Black, 4)]);
+        assert_eq!(fen.to_string(), "rk2r3/8/8/8/8/8/8/RK2R2R w EQk - 0 1");
+        assert_eq!(
+            fen.to_shredder_string(),
+            "rk2r3/8/8/8/8/8/8/RK2R2R w EAe - 0 1"
+        );
+
+        let fen = chess960::get_starting_fen(0).unwrap();
+        assert_eq!(
+            fen.to_shredder_string(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"
+        );
+        assert_eq!(Fen::from_str(&fen.to_shredder_string()).unwrap(), fen);
+    }
+
+    #[test]
+    fn test_from_game() {
+        let game = Game::new();
+        assert_eq!(Fen::from_game(&game).to_string(), STARTING_FEN);
+
+        for (from, to) in [("E2", "E4"), ("B8", "C6"), ("G1", "F3")] {
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            Fen::from_game(&game).to_string(),
+            "r1bqkbnr/pppppppp/2n5/8/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 2 2"
+        );
+
+        // Games can start from any position, with either player to move.
+        let fen = Fen::from_str("4k3/8/8/8/8/8/4P3/4K3 b - - 0 40").unwrap();
+        let options = GameOptions {
+            starting_fen: Some(fen.clone()),
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        assert_eq!(game.get_current_move(), Black);
+        assert_eq!(Fen::from_game(&game), fen);
+
+        // The en passant square of the starting position holds until the first move.
+        let fen = Fen::from_str("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+        let options = GameOptions {
+            starting_fen: Some(fen.clone()),
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        assert_eq!(Fen::from_game(&game), fen);
+        game.move_piece_at_position(
+            &Position::from_str("E1").unwrap(),
+            &Position::from_str("E2").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Fen::from_game(&game).en_passant, None);
+    }
+}
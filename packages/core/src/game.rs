@@ -1,16 +1,25 @@
-use crate::error::MoveError;
+use crate::annotation::Annotation;
+use crate::error::{ActionError, AnnotationError, ForkError, MoveError, SeatError, SetupError};
+use crate::fen::{Fen, STARTING_FEN};
 use crate::game::Color::{Black, White};
-use crate::game::PieceKind::{Bishop, King, Knight, Pawn, Queen, Rook};
-use crate::moves::Position;
+use crate::game::PieceKind::{
+    Amazon, Archbishop, Bishop, Chancellor, King, Knight, Pawn, Queen, Rook,
+};
+use crate::moves::{Move, Position};
+use crate::rules::{validate_en_passant, DarkChess, Rules, VariantState};
+use crate::time_control::{Clock, ClockState, TimeControl, TimeControlCategory};
 use chrono::serde::ts_milliseconds;
 use chrono::{DateTime, Utc};
 use serde::ser::SerializeSeq;
-use serde::{Serialize, Serializer};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeMap;
 use std::fmt::{self, Display, Formatter, Write};
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use uuid::Uuid;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PieceKind {
     King,
     Queen,
@@ -18,10 +27,20 @@ pub enum PieceKind {
     Knight,
     Rook,
     Pawn,
+
+    /// A fairy piece that moves as a bishop or a knight.
+    Archbishop,
+
+    /// A fairy piece that moves as a rook or a knight.
+    Chancellor,
+
+    /// A fairy piece that moves as a queen or a knight.
+    Amazon,
 }
 
 impl PieceKind {
-    fn from_char(name: char) -> Option<PieceKind> {
+    /// Get the kind of piece with the specified (uppercase) letter, as in FEN.
+    pub fn from_char(name: char) -> Option<PieceKind> {
         match name {
             'K' => Some(King),
             'Q' => Some(Queen),
@@ -29,11 +48,15 @@ impl PieceKind {
             'N' => Some(Knight),
             'R' => Some(Rook),
             'P' => Some(Pawn),
+            'A' => Some(Archbishop),
+            'C' => Some(Chancellor),
+            'M' => Some(Amazon),
             _ => None,
         }
     }
 
-    fn char(&self) -> char {
+    /// Get the (uppercase) letter of the kind of piece, as in FEN.
+    pub fn char(&self) -> char {
         match *self {
             King => 'K',
             Queen => 'Q',
@@ -41,12 +64,18 @@ impl PieceKind {
             Knight => 'N',
             Rook => 'R',
             Pawn => 'P',
+            Archbishop => 'A',
+            Chancellor => 'C',
+            Amazon => 'M',
         }
     }
 
-    fn material_value(&self) -> usize {
+    pub fn material_value(&self) -> usize {
         match *self {
+            Amazon => 12,
             Queen => 9,
+            Chancellor => 8,
+            Archbishop => 7,
             Rook => 5,
             Bishop => 3,
             Knight => 3,
@@ -65,6 +94,20 @@ impl Serialize for PieceKind {
     }
 }
 
+impl<'de> Deserialize<'de> for PieceKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let c = char::deserialize(deserializer)?;
+        PieceKind::from_char(c.to_ascii_uppercase()).ok_or_else(|| {
+            de::Error::custom(
+                "expected a kind of piece ('K', 'Q', 'R', 'B', 'N', 'P', 'A', 'C' or 'M')",
+            )
+        })
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Color {
     Black,
@@ -86,6 +129,13 @@ impl Color {
             White => 'W',
         }
     }
+
+    pub fn opponent(&self) -> Color {
+        match *self {
+            Black => White,
+            White => Black,
+        }
+    }
 }
 
 impl Serialize for Color {
@@ -97,7 +147,17 @@ impl Serialize for Color {
     }
 }
 
-#[derive(Copy, Clone, Debug, Serialize)]
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let c = char::deserialize(deserializer)?;
+        Color::from_char(c).ok_or_else(|| de::Error::custom("expected a color ('W' or 'B')"))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
 pub struct Piece {
     /// The kind of piece. This also indicates its value.
     pub kind: PieceKind,
@@ -117,7 +177,7 @@ impl Display for Piece {
 }
 
 impl Piece {
-    fn new(kind: PieceKind, color: Color) -> Piece {
+    pub fn new(kind: PieceKind, color: Color) -> Piece {
         Piece {
             kind,
             color,
@@ -140,6 +200,30 @@ impl Piece {
 
         panic!("Invalid piece notation: {}", notation);
     }
+
+    /// Get the Unicode chess symbol for the piece (e.g., ♔ for a white king).
+    pub fn unicode_symbol(&self) -> char {
+        match (self.color, self.kind) {
+            (White, King) => '♔',
+            (White, Queen) => '♕',
+            (White, Rook) => '♖',
+            (White, Bishop) => '♗',
+            (White, Knight) => '♘',
+            (White, Pawn) => '♙',
+            (Black, King) => '♚',
+            (Black, Queen) => '♛',
+            (Black, Rook) => '♜',
+            (Black, Bishop) => '♝',
+            (Black, Knight) => '♞',
+            (Black, Pawn) => '♟',
+            (White, Archbishop) => '\u{1FA50}',
+            (White, Chancellor) => '\u{1FA4F}',
+            (White, Amazon) => '\u{1FA4E}',
+            (Black, Archbishop) => '\u{1FA53}',
+            (Black, Chancellor) => '\u{1FA52}',
+            (Black, Amazon) => '\u{1FA51}',
+        }
+    }
 }
 
 #[macro_export]
@@ -149,17 +233,389 @@ macro_rules! p {
     };
 }
 
-pub type GameBoard = [[Option<Piece>; 8]; 8];
+/// The number of ranks on a board.
+pub const RANKS: usize = 8;
+
+/// The most files a board may have (e.g., 10 in Capablanca Chess).
+pub const MAX_FILES: usize = 10;
+
+/// A grid of pieces, with 8 ranks and up to [MAX_FILES] files (8 in standard chess). Indexing the
+/// board by rank gives the squares on that rank, so `board[rank][file]` is a square. Rank 0 is the
+/// 8th rank, and file 0 is the a-file.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GameBoard {
+    squares: [[Option<Piece>; MAX_FILES]; RANKS],
+    files: usize,
+}
+
+impl GameBoard {
+    /// Create an empty board with the specified number of files.
+    pub fn empty(files: usize) -> GameBoard {
+        assert!(
+            (1..=MAX_FILES).contains(&files),
+            "invalid number of files: {}",
+            files
+        );
+        GameBoard {
+            squares: [[None; MAX_FILES]; RANKS],
+            files,
+        }
+    }
+
+    /// Get the number of files on the board.
+    pub fn files(&self) -> usize {
+        self.files
+    }
+
+    /// Check whether the specified position is on the board.
+    pub fn contains(&self, position: &Position) -> bool {
+        position.rank < RANKS && position.file < self.files
+    }
+
+    /// Iterate over the ranks of the board, from the 8th rank to the 1st.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &[Option<Piece>]> + ExactSizeIterator {
+        let files = self.files;
+        self.squares.iter().map(move |rank| &rank[..files])
+    }
+
+    /// Iterate mutably over the ranks of the board, from the 8th rank to the 1st.
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut [Option<Piece>]> + ExactSizeIterator {
+        let files = self.files;
+        self.squares.iter_mut().map(move |rank| &mut rank[..files])
+    }
+}
+
+impl From<[[Option<Piece>; 8]; RANKS]> for GameBoard {
+    /// Create a standard 8x8 board.
+    fn from(squares: [[Option<Piece>; 8]; RANKS]) -> Self {
+        let mut board = GameBoard::empty(8);
+        for (rank, squares) in board.iter_mut().zip(squares) {
+            rank.copy_from_slice(&squares);
+        }
+        board
+    }
+}
+
+impl Index<usize> for GameBoard {
+    type Output = [Option<Piece>];
+
+    fn index(&self, rank: usize) -> &Self::Output {
+        &self.squares[rank][..self.files]
+    }
+}
+
+impl IndexMut<usize> for GameBoard {
+    fn index_mut(&mut self, rank: usize) -> &mut Self::Output {
+        &mut self.squares[rank][..self.files]
+    }
+}
+
+impl Serialize for GameBoard {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut ranks = serializer.serialize_seq(Some(RANKS))?;
+        for rank in self.iter() {
+            ranks.serialize_element(rank)?;
+        }
+        ranks.end()
+    }
+}
+
+/// How a game came to an end.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    Resignation,
+    DrawAgreement,
+    Timeout,
+
+    /// Ended by an arbiter (e.g., a draw by repetition in an engine match).
+    Adjudication,
+
+    /// A player gave check for the third time in a game of three-check.
+    ThreeChecks,
+
+    /// A player moved their king to the center in a game of King of the Hill.
+    KingOfTheHill,
+
+    /// A player's king was caught in an explosion in a game of Atomic.
+    Explosion,
+
+    /// A player has no pieces left (winning a game of Antichess, or losing a game of Horde).
+    NoPiecesLeft,
+
+    /// A player's king was captured in a game of Dark Chess.
+    KingCaptured,
+
+    /// The game on the other board of a Bughouse match ended, deciding the match.
+    PartnerBoard,
+    Aborted,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GameResult {
+    /// The player that won the game, or [None] if the game was drawn or aborted.
+    pub winner: Option<Color>,
+
+    /// How the game came to an end.
+    pub termination: Termination,
+}
+
+impl GameResult {
+    /// Get the result as it is written in PGN (e.g., `1-0`). Aborted games have no result (`*`).
+    pub fn to_pgn(&self) -> &'static str {
+        match (self.winner, self.termination) {
+            (_, Termination::Aborted) => "*",
+            (Some(White), _) => "1-0",
+            (Some(Black), _) => "0-1",
+            (None, _) => "1/2-1/2",
+        }
+    }
+
+    /// Get the score of the specified player (1 for a win, 0.5 for a draw and 0 for a loss), or
+    /// [None] if the game was aborted.
+    pub fn score(&self, color: Color) -> Option<f64> {
+        match (self.winner, self.termination) {
+            (_, Termination::Aborted) => None,
+            (Some(winner), _) if winner == color => Some(1.0),
+            (Some(_), _) => Some(0.0),
+            (None, _) => Some(0.5),
+        }
+    }
+}
+
+/// A player's seat at a game (i.e., playing as White or Black).
+#[derive(Clone, Debug)]
+pub struct Seat {
+    /// The secret token that the player must present to act on behalf of the seat.
+    token: String,
+
+    /// The ID of the user that claimed the seat, if they were logged in.
+    user_id: Option<Uuid>,
+}
+
+/// The game (and the point in that game) that a forked game branched from.
+#[derive(Clone, Debug, Serialize)]
+pub struct ForkOrigin {
+    /// ID of the game that was forked.
+    pub game_id: Option<String>,
+
+    /// The number of moves (plies) from the parent game that were carried over.
+    pub ply: usize,
+}
+
+/// The other board of a Bughouse match, which a game passes its captured pieces and its result
+/// to. Only the board's state is shared (not the [Game] itself), so that moves on the two boards
+/// never wait on each other.
+#[derive(Clone)]
+struct PartnerBoard {
+    /// ID of the game on the other board.
+    game_id: Option<String>,
+    state: Arc<Mutex<VariantState>>,
+    result: Arc<Mutex<Option<GameResult>>>,
+}
+
+/// The rules a game is played by.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    #[default]
+    Standard,
+
+    /// Fischer Random Chess: the pieces on the back rank start in one of 960 arrangements, and
+    /// castling moves the king and rook to the same squares as in standard chess.
+    Chess960,
+
+    /// A player also wins by giving check three times.
+    ThreeCheck,
+
+    /// A player also wins by moving their king to one of the four center squares.
+    KingOfTheHill,
+
+    /// Captured pieces go into the capturer's pocket, and may be dropped back onto the board.
+    Crazyhouse,
+
+    /// Captures explode, and a player wins by exploding the opponent's king.
+    Atomic,
+
+    /// Also known as Losing Chess: captures are compulsory, and a player wins by losing all their
+    /// pieces.
+    Antichess,
+
+    /// White's 36 pawns take on Black's usual army.
+    Horde,
+
+    /// Also known as Fog of War: each player only sees the squares their pieces can move to, and
+    /// wins by capturing the opponent's king.
+    DarkChess,
+
+    /// Two games of Crazyhouse played side by side by teams of two, in which captured pieces go
+    /// to the capturer's partner on the other board. A board cannot be replayed on its own (e.g.,
+    /// when it is forked) past a drop of a piece its partner captured.
+    Bughouse,
+
+    /// Played on a 10x8 board, with an archbishop (moving as a bishop or a knight) and a
+    /// chancellor (moving as a rook or a knight) for each player.
+    Capablanca,
+}
+
+impl Variant {
+    /// Get the name of the variant as it is written in the PGN `Variant` tag.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::Standard => "Standard",
+            Variant::Chess960 => "Chess960",
+            Variant::ThreeCheck => "Three-check",
+            Variant::KingOfTheHill => "King of the Hill",
+            Variant::Crazyhouse => "Crazyhouse",
+            Variant::Atomic => "Atomic",
+            Variant::Antichess => "Antichess",
+            Variant::Horde => "Horde",
+            Variant::DarkChess => "Dark Chess",
+            Variant::Bughouse => "Bughouse",
+            Variant::Capablanca => "Capablanca",
+        }
+    }
+}
+
+/// The settings a game is created with.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameOptions {
+    /// Whether the result of the game affects the players' ratings.
+    pub rated: bool,
+
+    /// The time control of the game, or [None] if the game is untimed.
+    pub time_control: Option<TimeControl>,
+
+    pub variant: Variant,
+
+    /// The index (from 0 to 959) of the starting position of a Chess960 game, or [None] to
+    /// choose one at random. Ignored if a starting FEN is given.
+    pub chess960_position: Option<usize>,
+
+    /// The position the game starts from, or [None] for the standard starting position.
+    pub starting_fen: Option<Fen>,
+
+    /// The number of plies spectators of a Dark Chess game are shown the board behind by, or
+    /// [None] to hide the board from spectators until the game is over.
+    pub spectator_delay: Option<usize>,
+
+    /// The odds one player gives the other, or [None] for an even game.
+    pub handicap: Option<Handicap>,
+}
+
+impl GameOptions {
+    /// Get the time control category a game with these options is rated in. Untimed games count
+    /// as classical.
+    pub fn category(&self) -> TimeControlCategory {
+        self.time_control
+            .map(|time_control| time_control.category())
+            .unwrap_or(TimeControlCategory::Classical)
+    }
+
+    /// Get the time control of the specified player's clock, which differs from
+    /// [GameOptions::time_control] for a player giving time odds.
+    pub fn get_time_control(&self, color: Color) -> Option<TimeControl> {
+        let time_control = self.time_control?;
+        match &self.handicap {
+            Some(handicap) if handicap.giver == color => {
+                Some(handicap.time_control.unwrap_or(time_control))
+            }
+            _ => Some(time_control),
+        }
+    }
+}
+
+/// The odds a stronger player gives to even out a game (e.g., a coach playing a junior): pieces
+/// they start without, moves their opponent makes before their first, and less time on their
+/// clock.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Handicap {
+    /// The player giving the odds.
+    pub giver: Color,
+
+    /// The kinds of pieces the giver starts without (e.g., `["Q"]` for queen odds). Of several
+    /// pieces of a kind, the one furthest to the queenside is removed, except for a pawn, which is
+    /// the f-pawn as is traditional. Ignored if a starting FEN is given.
+    pub pieces: Vec<PieceKind>,
+
+    /// The number of moves the giver's opponent makes in a row before the giver's first move, or 0
+    /// for none. None of these moves but the last may give check.
+    pub moves: usize,
+
+    /// The time control of the giver's clock, or [None] to use [GameOptions::time_control].
+    pub time_control: Option<TimeControl>,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Handicap {
+            giver: White,
+            pieces: Vec::new(),
+            moves: 0,
+            time_control: None,
+        }
+    }
+}
+
+impl Handicap {
+    /// Get the player receiving the odds.
+    pub fn receiver(&self) -> Color {
+        self.giver.opponent()
+    }
+
+    /// Get the number of moves the receiver makes in a row before the giver can reply, beyond the
+    /// one they would make anyway.
+    fn extra_moves(&self) -> usize {
+        self.moves.saturating_sub(1)
+    }
+
+    /// Get the specified position with the giver's pieces removed, and the receiver to move if
+    /// they receive move odds.
+    fn apply(&self, mut fen: Fen) -> Fen {
+        for &kind in &self.pieces {
+            if kind == King {
+                continue;
+            }
+
+            // Look for the piece from the giver's side of the board, preferring the f-pawn.
+            let mut ranks: Vec<usize> = (0..RANKS).collect();
+            if self.giver == White {
+                ranks.reverse();
+            }
+            let files = fen.board.files();
+            let preferred = if kind == Pawn { Some(5) } else { None };
+            let square = ranks.into_iter().find_map(|rank| {
+                let is_match = |file: &usize| {
+                    fen.board[rank][*file]
+                        .is_some_and(|piece| piece.kind == kind && piece.color == self.giver)
+                };
+                preferred
+                    .filter(is_match)
+                    .or_else(|| (0..files).find(is_match))
+                    .map(|file| (rank, file))
+            });
+
+            if let Some((rank, file)) = square {
+                fen.board[rank][file] = None;
+                fen.castling
+                    .retain(|&castling| castling != (self.giver, file));
+            }
+        }
 
-fn serialize_game_board<S>(board: &Arc<Mutex<GameBoard>>, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    let mut ranks = serializer.serialize_seq(Some(8))?;
-    for rank in board.lock().unwrap().iter() {
-        ranks.serialize_element(&rank)?;
+        if self.moves > 0 {
+            fen.turn = self.receiver();
+        }
+        fen
     }
-    ranks.end()
 }
 
 pub struct Game {
@@ -173,14 +629,53 @@ pub struct Game {
     /// The [Instant] the game was created.
     created_at: DateTime<Utc>,
 
+    /// The settings the game was created with.
+    options: GameOptions,
+
     /// The list of moves in the game.
-    moves: Arc<Mutex<Vec<(Position, Position)>>>,
+    moves: Arc<Mutex<Vec<Move>>>,
+
+    /// The number of plies since the last capture or pawn move.
+    halfmove_clock: Mutex<usize>,
+
+    /// The state besides the board that the variant's rules depend on. It is shared with the
+    /// partner board of a Bughouse match, which adds the pieces captured there to the pockets.
+    state: Arc<Mutex<VariantState>>,
+
+    /// The players' clocks, if the game is timed.
+    clock: Mutex<Option<Clock>>,
+
+    /// If this game was forked from another, the game and ply it branched from.
+    forked_from: Option<ForkOrigin>,
+
+    /// The result of the game, once it has ended. It is shared with the partner board of a
+    /// Bughouse match, which ends the game when its own game ends.
+    result: Arc<Mutex<Option<GameResult>>>,
+
+    /// The other board, if this game is one board of a Bughouse match.
+    partner: Option<PartnerBoard>,
+
+    /// The player with an outstanding draw offer, if any. The offer expires once their opponent
+    /// has made a move.
+    draw_offer: Mutex<Option<Color>>,
+
+    /// The seats that have been claimed by players. Spectators have no seat.
+    seats: BTreeMap<Color, Seat>,
+
+    /// The user who created the game, if they were logged in.
+    creator: Option<Uuid>,
+
+    /// Annotations attached to moves in the game, by ply (i.e., the first move is ply 1, and ply 0
+    /// is the starting position).
+    annotations: BTreeMap<usize, Annotation>,
 }
 
 impl fmt::Display for Game {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let board = self.board.lock().unwrap(); // Access the game board
-        let files = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H']; // File labels
+        let files: Vec<char> = (0..board.files())
+            .map(|file| char::from(b'A' + file as u8))
+            .collect(); // File labels
 
         // Initial newline for spacing
         writeln!(f, "\n")?;
@@ -216,21 +711,77 @@ impl fmt::Display for Game {
 }
 impl Serialize for Game {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.serialize_with_position(&Fen::from_game(self), serializer)
+    }
+}
+
+/// Who a game is shown to, which decides how much of a Dark Chess game they may see.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Viewer {
+    /// The player seated as the specified color.
+    Player(Color),
+
+    /// Anyone without a seat in the game.
+    Spectator,
+}
+
+/// A game as seen by a particular [Viewer]. It serializes like the [Game], but with the position
+/// from [Game::get_visible_position].
+pub struct GameView<'a> {
+    game: &'a Game,
+    viewer: Viewer,
+}
+
+impl Serialize for GameView<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let position = self.game.get_visible_position(self.viewer);
+        self.game.serialize_with_position(&position, serializer)
+    }
+}
+
+impl Game {
+    /// Serialize the game, showing the specified position in place of the current one.
+    fn serialize_with_position<S>(&self, position: &Fen, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         #[derive(Serialize)]
         struct Game<'a> {
             id: &'a Option<String>,
-            #[serde(serialize_with = "serialize_game_board")]
-            board: &'a Arc<Mutex<GameBoard>>,
+            board: &'a GameBoard,
             #[serde(with = "ts_milliseconds")]
             created_at: &'a DateTime<Utc>,
+            rated: bool,
+            time_control: Option<TimeControl>,
+            variant: Variant,
+            chess960_position: Option<usize>,
+            starting_fen: &'a Option<Fen>,
+            fen: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            checks: Option<BTreeMap<Color, usize>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pockets: Option<BTreeMap<Color, Vec<PieceKind>>>,
+            clock: Option<ClockState>,
             is_player_in_check: &'a BTreeMap<Color, bool>,
             moves_count: usize,
             current_move: Color,
+            forked_from: &'a Option<ForkOrigin>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            partner_game_id: Option<&'a String>,
+            annotations: &'a BTreeMap<usize, Annotation>,
+            result: Option<GameResult>,
+            draw_offer: Option<Color>,
+            seats: BTreeMap<Color, bool>,
+            players: BTreeMap<Color, Uuid>,
         }
 
+        let state = self.get_variant_state();
         let mut is_player_in_check = BTreeMap::new();
         is_player_in_check.insert(White, self.is_player_in_check(White));
         is_player_in_check.insert(Black, self.is_player_in_check(Black));
@@ -240,17 +791,81 @@ impl Serialize for Game {
 
         let game = Game {
             id: &self.id,
-            board: &self.board,
+            board: &position.board,
             created_at: &self.created_at,
+            rated: self.options.rated,
+            time_control: self.options.time_control,
+            variant: self.options.variant,
+            chess960_position: self.options.chess960_position,
+            starting_fen: &self.options.starting_fen,
+            fen: position.to_string(),
+            checks: (self.options.variant == Variant::ThreeCheck).then(|| {
+                [White, Black]
+                    .into_iter()
+                    .map(|color| (color, state.get_checks(color)))
+                    .collect()
+            }),
+            pockets: matches!(
+                self.options.variant,
+                Variant::Crazyhouse | Variant::Bughouse
+            )
+            .then(|| {
+                [White, Black]
+                    .into_iter()
+                    .map(|color| (color, state.get_pocket(color).to_vec()))
+                    .collect()
+            }),
+            clock: self.get_clock(),
             is_player_in_check: &is_player_in_check,
             moves_count,
             current_move,
+            forked_from: &self.forked_from,
+            partner_game_id: self
+                .partner
+                .as_ref()
+                .and_then(|partner| partner.game_id.as_ref()),
+            annotations: &self.annotations,
+            result: self.get_result(),
+            draw_offer: self.get_draw_offer(),
+            seats: [White, Black]
+                .into_iter()
+                .map(|color| (color, self.is_seat_claimed(color)))
+                .collect(),
+            players: self
+                .seats
+                .iter()
+                .filter_map(|(color, seat)| seat.user_id.map(|user_id| (*color, user_id)))
+                .collect(),
         };
 
         game.serialize(serializer)
     }
 }
 
+impl Clone for Game {
+    /// Create an independent copy of the game. The board and move list are copied rather than
+    /// shared, so moves made on the copy do not affect the original.
+    fn clone(&self) -> Self {
+        Game {
+            id: self.id.clone(),
+            board: Arc::new(Mutex::new(*self.board.lock().unwrap())),
+            created_at: self.created_at,
+            options: self.options.clone(),
+            moves: Arc::new(Mutex::new(self.moves.lock().unwrap().clone())),
+            halfmove_clock: Mutex::new(self.get_halfmove_clock()),
+            state: Arc::new(Mutex::new(self.get_variant_state())),
+            clock: Mutex::new(self.clock.lock().unwrap().clone()),
+            forked_from: self.forked_from.clone(),
+            result: Arc::new(Mutex::new(self.get_result())),
+            partner: None,
+            draw_offer: Mutex::new(self.get_draw_offer()),
+            seats: self.seats.clone(),
+            creator: self.creator,
+            annotations: self.annotations.clone(),
+        }
+    }
+}
+
 impl Default for Game {
     fn default() -> Self {
         Self::new()
@@ -263,8 +878,33 @@ impl Game {
     }
 
     pub fn new_with_id(id: Option<String>) -> Game {
+        Game::new_with_options(id, GameOptions::default())
+    }
+
+    pub fn new_with_options(id: Option<String>, mut options: GameOptions) -> Game {
+        if options.starting_fen.is_none() {
+            options.starting_fen = options.variant.rules().get_starting_fen(&mut options);
+
+            // The handicap is applied once, to the position the game starts from, so that forks
+            // and rematches (whose options already have the position) start from the same one.
+            if let Some(handicap) = &options.handicap {
+                if !handicap.pieces.is_empty() || handicap.moves > 0 {
+                    let fen = options
+                        .starting_fen
+                        .clone()
+                        .unwrap_or_else(|| Fen::from_str(STARTING_FEN).unwrap());
+                    options.starting_fen = Some(handicap.apply(fen));
+                }
+            }
+        }
+
+        if let Some(fen) = &options.starting_fen {
+            let board = Arc::new(Mutex::new(fen.board));
+            return Game::new_with_board(id, options.clone(), board);
+        }
+
         #[rustfmt::skip]
-        let board = Arc::new(Mutex::new([
+        let board = Arc::new(Mutex::new(GameBoard::from([
             [p!("BR"), p!("BN"), p!("BB"), p!("BQ"), p!("BK"), p!("BB"), p!("BN"), p!("BR")],
             [p!("BP"), p!("BP"), p!("BP"), p!("BP"), p!("BP"), p!("BP"), p!("BP"), p!("BP")],
             [None; 8],
@@ -273,128 +913,971 @@ impl Game {
             [None; 8],
             [p!("WP"), p!("WP"), p!("WP"), p!("WP"), p!("WP"), p!("WP"), p!("WP"), p!("WP")],
             [p!("WR"), p!("WN"), p!("WB"), p!("WQ"), p!("WK"), p!("WB"), p!("WN"), p!("WR")],
-        ]));
+        ])));
+
+        Game::new_with_board(id, options, board)
+    }
+
+    fn new_with_board(
+        id: Option<String>,
+        options: GameOptions,
+        board: Arc<Mutex<GameBoard>>,
+    ) -> Game {
+        let halfmove_clock = options
+            .starting_fen
+            .as_ref()
+            .map(|fen| fen.halfmove_clock)
+            .unwrap_or(0);
+        let clock = options
+            .get_time_control(White)
+            .zip(options.get_time_control(Black))
+            .map(|(white, black)| Clock::new_asymmetric(white, black));
 
         Game {
             id,
             board,
             created_at: Utc::now(),
+            options,
             moves: Arc::new(Mutex::new(Vec::new())),
+            halfmove_clock: Mutex::new(halfmove_clock),
+            state: Arc::new(Mutex::new(VariantState::default())),
+            clock: Mutex::new(clock),
+            forked_from: None,
+            result: Arc::new(Mutex::new(None)),
+            partner: None,
+            draw_offer: Mutex::new(None),
+            seats: BTreeMap::new(),
+            creator: None,
+            annotations: BTreeMap::new(),
         }
     }
 
-    pub fn get_tile_color(rank: usize, file: usize) -> Color {
-        if (rank % 2) == (file % 2) {
-            White
-        } else {
-            Black
+    /// Create a new game (with the specified ID) whose history (and annotations) are the first
+    /// `ply` moves of this game. This game is left untouched. Forked games are never rated or
+    /// timed.
+    pub fn fork(&self, id: Option<String>, ply: usize) -> Result<Game, ForkError> {
+        let moves = self.moves.lock().unwrap();
+        if ply > moves.len() {
+            return Err(ForkError::InvalidPlyError);
         }
+
+        let options = GameOptions {
+            rated: false,
+            time_control: None,
+            ..self.options.clone()
+        };
+        let mut game = Game::new_with_options(id, options);
+        for mv in moves.iter().take(ply) {
+            game.play(mv).map_err(ForkError::ReplayError)?;
+        }
+
+        game.forked_from = Some(ForkOrigin {
+            game_id: self.id.clone(),
+            ply,
+        });
+        game.annotations = self
+            .annotations
+            .range(..=ply)
+            .map(|(k, v)| (*k, v.clone()))
+            .collect();
+        Ok(game)
     }
 
-    pub fn get_piece_by_position(&self, position: &Position) -> Option<Piece> {
-        self.board.lock().unwrap()[position.rank][position.file]
+    /// Link two games as the boards of a Bughouse match, so that the pieces captured on each
+    /// board go to the pockets of the other, and the first to end ends the other.
+    pub(crate) fn link_partners(first: &mut Game, second: &mut Game) {
+        let partner = |game: &Game| PartnerBoard {
+            game_id: game.id.clone(),
+            state: game.state.clone(),
+            result: game.result.clone(),
+        };
+        first.partner = Some(partner(second));
+        second.partner = Some(partner(first));
     }
 
-    pub fn move_piece_at_position(
-        &self,
-        position: &Position,
-        new_position: &Position,
-    ) -> Result<(), MoveError> {
-        let piece = self.get_piece_by_position(position);
-        if piece.is_none() {
-            return Err(MoveError::PieceNotFoundError);
-        }
+    /// Get the rules of the variant the game is played by.
+    pub fn get_rules(&self) -> &'static dyn Rules {
+        self.options.variant.rules()
+    }
 
-        let mut piece = piece.unwrap();
-        if piece.color != self.get_current_move() {
-            return Err(MoveError::OutOfTurnError);
-        }
+    /// Get the game as seen by the specified viewer, for serializing.
+    pub fn view(&self, viewer: Viewer) -> GameView<'_> {
+        GameView { game: self, viewer }
+    }
 
-        let valid_moves = piece.get_valid_moves(self, position);
-        if !valid_moves.contains(new_position) {
-            return Err(MoveError::IllegalMoveError);
-        }
+    /// Check whether the moves of the game are hidden from players and spectators alike, as they
+    /// are in a game of Dark Chess that is not yet over.
+    pub fn hides_moves(&self) -> bool {
+        self.options.variant == Variant::DarkChess && self.get_result().is_none()
+    }
 
-        // If the player is in check, we should eliminate any moves that will result in the player
-        // still being in check afterward.
-        if self.is_player_in_check(piece.color) {
-            for valid_move in valid_moves {}
+    /// Get the position the specified viewer may see. Until a game of Dark Chess is over, a player
+    /// only sees the squares their pieces can move to, and spectators see the position as it was
+    /// [GameOptions::spectator_delay] plies ago (or an empty board). Otherwise, the viewer sees
+    /// the current position.
+    pub fn get_visible_position(&self, viewer: Viewer) -> Fen {
+        let mut position = Fen::from_game(self);
+        if !self.hides_moves() {
+            return position;
         }
 
-        piece.move_count += 1;
+        match viewer {
+            Viewer::Player(color) => {
+                position.board = DarkChess::hide_unseen(&position.board, color);
+                // The opponent's castling rights and double steps would give away their moves.
+                position
+                    .castling
+                    .retain(|&(rook_color, _)| rook_color == color);
+                position.en_passant = None;
+                position
+            }
+            Viewer::Spectator => {
+                let delayed = self.options.spectator_delay.and_then(|delay| {
+                    let ply = self.get_move_count().saturating_sub(delay);
+                    self.fork(None, ply).ok()
+                });
+                match delayed {
+                    Some(game) => Fen::from_game(&game),
+                    None => Fen {
+                        board: GameBoard::empty(position.board.files()),
+                        castling: Vec::new(),
+                        en_passant: None,
+                        ..position
+                    },
+                }
+            }
+        }
+    }
 
-        let mut board = self.board.lock().unwrap();
-        board[position.rank][position.file] = None;
-        board[new_position.rank][new_position.file] = Some(piece);
+    pub fn get_options(&self) -> &GameOptions {
+        &self.options
+    }
 
-        self.moves.lock().unwrap().push((*position, *new_position));
-        Ok(())
+    /// Get the position the game started from.
+    pub fn get_starting_position(&self) -> Fen {
+        self.options.starting_fen.clone().unwrap_or_default()
     }
 
-    pub fn get_piece(board: &GameBoard, position: &Position) -> Option<Piece> {
-        board[position.rank][position.file]
+    /// Check whether the position the game started from is legal by the rules of its variant. The
+    /// first move of a game is refused until it is.
+    pub fn validate_starting_position(&self) -> Result<(), SetupError> {
+        self.get_rules()
+            .validate_position(&self.get_starting_position())
     }
 
-    pub fn is_player_in_check(&self, color: Color) -> bool {
-        for rank in 0..8 {
-            for file in 0..8 {
-                let position = &Position { rank, file };
-                let piece = self.get_piece_by_position(position);
-                if piece.is_none() {
-                    continue;
-                }
+    /// Put a piece on a square of the starting position of a game that has not started (e.g., in
+    /// the board editor), or clear the square with [None]. Castling rights whose king or rook is
+    /// no longer in place are removed. The position is not validated, so that it can be set up one
+    /// square at a time.
+    pub fn set_piece(
+        &mut self,
+        position: &Position,
+        piece: Option<Piece>,
+    ) -> Result<(), SetupError> {
+        self.edit_starting_position(|fen| {
+            if !fen.board.contains(position) {
+                return Err(SetupError::InvalidSquareError);
+            }
+            fen.board[position.rank][position.file] = piece;
+
+            let board = fen.board;
+            let is_piece = |rank: usize, file: usize, kind: PieceKind, color: Color| {
+                matches!(board[rank][file], Some(piece) if piece.kind == kind && piece.color == color)
+            };
+            fen.castling.retain(|&(color, file)| {
+                let back_rank = if color == White { RANKS - 1 } else { 0 };
+                is_piece(back_rank, file, Rook, color)
+                    && (0..board.files()).any(|file| is_piece(back_rank, file, King, color))
+            });
+            Ok(())
+        })
+    }
 
-                let piece = piece.unwrap();
-                let moves = piece.get_valid_moves(self, position);
-                if moves.is_empty() {
-                    continue;
-                }
+    /// Set the player to move first in a game that has not started.
+    pub fn set_turn(&mut self, color: Color) -> Result<(), SetupError> {
+        self.edit_starting_position(|fen| {
+            fen.turn = color;
+            Ok(())
+        })
+    }
 
-                for position in moves {
-                    let target = self.get_piece_by_position(&position);
-                    if target.is_none() {
-                        continue;
-                    }
+    /// Set the castling rights of a game that has not started, in X-FEN (e.g., `KQkq`) or
+    /// Shredder-FEN (e.g., `HAha`).
+    pub fn set_castling(&mut self, castling: &str) -> Result<(), SetupError> {
+        self.edit_starting_position(|fen| {
+            fen.castling = Fen::parse_castling(castling, &fen.board)
+                .map_err(|_| SetupError::InvalidCastlingError)?;
+            Ok(())
+        })
+    }
 
-                    let target = target.unwrap();
-                    if target.color == color && target.kind == King {
-                        return true;
-                    }
-                }
+    /// Set the square a pawn skipped over with a double step just before a game that has not
+    /// started, or [None] if there is none. The pawn must already stand just past the square, with
+    /// its opponent to move.
+    pub fn set_en_passant(&mut self, en_passant: Option<Position>) -> Result<(), SetupError> {
+        self.edit_starting_position(|fen| {
+            if en_passant.is_some_and(|square| !fen.board.contains(&square)) {
+                return Err(SetupError::InvalidSquareError);
             }
+            fen.en_passant = en_passant;
+            validate_en_passant(fen)
+        })
+    }
+
+    /// Make a change to the starting position of a game that has not started, and reset the game
+    /// to the new position.
+    fn edit_starting_position<F>(&mut self, edit: F) -> Result<(), SetupError>
+    where
+        F: FnOnce(&mut Fen) -> Result<(), SetupError>,
+    {
+        if self.get_move_count() > 0 {
+            return Err(SetupError::GameStartedError);
         }
 
-        false
+        let mut fen = self.get_starting_position();
+        edit(&mut fen)?;
+        fen.set_move_counts();
+
+        *self.board.lock().unwrap() = fen.board;
+        *self.halfmove_clock.lock().unwrap() = fen.halfmove_clock;
+        self.options.starting_fen = Some(fen);
+        Ok(())
     }
 
-    pub fn get_move_count(&self) -> usize {
-        self.moves.lock().unwrap().len()
+    pub fn is_rated(&self) -> bool {
+        self.options.rated
     }
 
-    pub fn get_current_move(&self) -> Color {
-        if self.get_move_count() % 2 == 0 {
-            White
-        } else {
-            Black
-        }
+    /// Get the time control category the game is rated in.
+    pub fn get_category(&self) -> TimeControlCategory {
+        self.options.category()
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::game::Color::{Black, White};
-    use crate::game::PieceKind::{King, Pawn, Queen};
-    use crate::game::{Game, PieceKind};
-    use crate::moves::Position;
-    use std::str::FromStr;
+    pub fn get_id(&self) -> Option<&String> {
+        self.id.as_ref()
+    }
 
-    #[test]
-    fn check_bishop_value() {
-        assert_eq!(3, PieceKind::Bishop.material_value())
+    /// Get the ID of the user who created the game, if any.
+    pub fn get_creator(&self) -> Option<Uuid> {
+        self.creator
     }
 
-    #[test]
-    fn check_starting_board() {
+    pub fn set_creator(&mut self, user_id: Option<Uuid>) {
+        self.creator = user_id;
+    }
+
+    pub fn is_seat_claimed(&self, color: Color) -> bool {
+        self.seats.contains_key(&color)
+    }
+
+    /// Claim the seat for the specified color (on behalf of the specified user, if any), returning
+    /// the secret token that the player must present to act on behalf of that seat.
+    pub fn claim_seat(&mut self, color: Color, user_id: Option<Uuid>) -> Result<String, SeatError> {
+        if self.is_seat_claimed(color) {
+            return Err(SeatError::SeatTakenError);
+        }
+
+        let token = Uuid::new_v4().to_string();
+        self.seats.insert(
+            color,
+            Seat {
+                token: token.clone(),
+                user_id,
+            },
+        );
+        Ok(token)
+    }
+
+    /// Get the ID of the user seated as the specified color, if any.
+    pub fn get_player(&self, color: Color) -> Option<Uuid> {
+        self.seats.get(&color).and_then(|seat| seat.user_id)
+    }
+
+    /// Get the color of the seat that the specified token belongs to, if any.
+    pub fn get_seat_color(&self, token: &str) -> Option<Color> {
+        self.seats
+            .iter()
+            .find(|(_, seat)| seat.token == token)
+            .map(|(color, _)| *color)
+    }
+
+    pub fn get_annotation(&self, ply: usize) -> Option<&Annotation> {
+        self.annotations.get(&ply)
+    }
+
+    /// Attach an annotation to the move at the specified ply (i.e., the first move is ply 1, and
+    /// ply 0 is the starting position), replacing any existing annotation. An empty annotation
+    /// removes the existing one.
+    pub fn set_annotation(
+        &mut self,
+        ply: usize,
+        annotation: Annotation,
+    ) -> Result<(), AnnotationError> {
+        if ply > self.get_move_count() {
+            return Err(AnnotationError::InvalidPlyError);
+        }
+        annotation.validate()?;
+
+        if annotation.is_empty() {
+            self.annotations.remove(&ply);
+        } else {
+            self.annotations.insert(ply, annotation);
+        }
+        Ok(())
+    }
+
+    pub fn get_created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn get_tile_color(rank: usize, file: usize) -> Color {
+        if (rank % 2) == (file % 2) {
+            White
+        } else {
+            Black
+        }
+    }
+
+    pub fn get_piece_by_position(&self, position: &Position) -> Option<Piece> {
+        Game::get_piece(&self.board.lock().unwrap(), position)
+    }
+
+    pub fn move_piece_at_position(
+        &self,
+        position: &Position,
+        new_position: &Position,
+    ) -> Result<(), MoveError> {
+        self.check_flag();
+        if self.get_result().is_some() {
+            return Err(MoveError::GameOverError);
+        }
+        if self.get_move_count() == 0 {
+            self.validate_starting_position()
+                .map_err(MoveError::IllegalPositionError)?;
+        }
+
+        let piece = self.get_piece_by_position(position);
+        if piece.is_none() {
+            return Err(MoveError::PieceNotFoundError);
+        }
+
+        let piece = piece.unwrap();
+        if piece.color != self.get_current_move() {
+            return Err(MoveError::OutOfTurnError);
+        }
+
+        // Castling is written as the king moving onto the rook it castles with (or, as in
+        // standard chess, two squares towards it).
+        let rules = self.get_rules();
+        let board_before = *self.board.lock().unwrap();
+        let new_position = &Game::get_castling_rook(&board_before, position, new_position)
+            .unwrap_or(*new_position);
+
+        let legal_moves = rules.get_legal_moves(&board_before, piece.color);
+        if !legal_moves.contains(&(*position, *new_position)) {
+            return Err(MoveError::IllegalMoveError);
+        }
+
+        // The opponent of a player making extra moves for move odds could not get out of check.
+        if self.is_extra_move() {
+            let mut state = self.get_variant_state();
+            let after = rules.apply_move(&board_before, &mut state, position, new_position);
+            if rules.is_in_check(&after, piece.color.opponent()) {
+                return Err(MoveError::IllegalMoveError);
+            }
+        }
+
+        // Captures and pawn moves reset the halfmove clock.
+        let captured = board_before[new_position.rank][new_position.file];
+        let is_irreversible =
+            piece.kind == Pawn || captured.is_some_and(|captured| captured.color != piece.color);
+
+        let mut board = self.board.lock().unwrap();
+        let mut state = self.state.lock().unwrap();
+        *board = rules.apply_move(&board, &mut state, position, new_position);
+        drop(state);
+        drop(board);
+
+        // In Bughouse, the captured piece goes to the capturer's partner, who plays the captured
+        // piece's color on the other board.
+        if let (Some(partner), Some(captured)) = (&self.partner, captured) {
+            if captured.color != piece.color {
+                let mut state = partner.state.lock().unwrap();
+                state.add_to_pocket(captured.color, captured.kind);
+            }
+        }
+
+        self.complete_move(
+            Move::Normal(*position, *new_position),
+            piece.color,
+            is_irreversible,
+        );
+        Ok(())
+    }
+
+    /// Drop a piece of the specified kind from the pocket of the player to move onto the
+    /// specified (empty) square. Only possible in variants with pockets (i.e., Crazyhouse).
+    pub fn drop_piece(&self, kind: PieceKind, position: &Position) -> Result<(), MoveError> {
+        self.check_flag();
+        if self.get_result().is_some() {
+            return Err(MoveError::GameOverError);
+        }
+        if self.get_move_count() == 0 {
+            self.validate_starting_position()
+                .map_err(MoveError::IllegalPositionError)?;
+        }
+
+        let color = self.get_current_move();
+        let rules = self.get_rules();
+        let board_before = *self.board.lock().unwrap();
+        let state_before = self.get_variant_state();
+        if !state_before.get_pocket(color).contains(&kind) {
+            return Err(MoveError::PieceNotInPocketError);
+        }
+
+        let legal_drops = rules.get_legal_drops(&board_before, &state_before, color);
+        if !legal_drops.contains(&(kind, *position)) {
+            return Err(MoveError::IllegalMoveError);
+        }
+
+        let mut board = self.board.lock().unwrap();
+        let mut state = self.state.lock().unwrap();
+        *board = rules.apply_drop(&board, &mut state, color, kind, position);
+        drop(state);
+        drop(board);
+
+        // Dropping a pawn resets the halfmove clock, as moving one does.
+        self.complete_move(Move::Drop(kind, *position), color, kind == Pawn);
+        Ok(())
+    }
+
+    /// Get the pieces the player to move can drop from their pocket, and where.
+    pub fn get_legal_drops(&self) -> Vec<(PieceKind, Position)> {
+        let board = *self.board.lock().unwrap();
+        self.get_rules()
+            .get_legal_drops(&board, &self.get_variant_state(), self.get_current_move())
+    }
+
+    /// Record a move that has been made on the board by the specified player, then end the game
+    /// if the variant's rules say it is over (e.g., the opponent has been checkmated).
+    fn complete_move(&self, mv: Move, color: Color, is_irreversible: bool) {
+        let mut halfmove_clock = self.halfmove_clock.lock().unwrap();
+        if is_irreversible {
+            *halfmove_clock = 0;
+        } else {
+            *halfmove_clock += 1;
+        }
+        drop(halfmove_clock);
+
+        self.moves.lock().unwrap().push(mv);
+
+        // The clock starts once a player receiving move odds has made their extra moves.
+        let next = self.get_current_move();
+        if let Some(clock) = self.clock.lock().unwrap().as_mut() {
+            if next != color {
+                clock.press(color, Utc::now());
+            }
+        }
+
+        // Moving declines any draw offer made by the opponent.
+        let mut draw_offer = self.draw_offer.lock().unwrap();
+        if *draw_offer == Some(color.opponent()) {
+            *draw_offer = None;
+        }
+        drop(draw_offer);
+
+        let rules = self.get_rules();
+        let board = *self.board.lock().unwrap();
+        let state = self.get_variant_state();
+        let legal_replies = rules.get_legal_moves(&board, next);
+        if let Some(outcome) = rules.get_outcome(&board, &state, next, &legal_replies) {
+            self.end(outcome.winner, outcome.termination);
+        }
+    }
+
+    /// Make a move from a game's history (e.g., when replaying the game).
+    pub fn play(&self, mv: &Move) -> Result<(), MoveError> {
+        match mv {
+            Move::Normal(position, new_position) => {
+                self.move_piece_at_position(position, new_position)
+            }
+            Move::Drop(kind, position) => self.drop_piece(*kind, position),
+        }
+    }
+
+    pub fn get_result(&self) -> Option<GameResult> {
+        *self.result.lock().unwrap()
+    }
+
+    pub fn get_draw_offer(&self) -> Option<Color> {
+        *self.draw_offer.lock().unwrap()
+    }
+
+    /// End the game with the specified result.
+    fn end(&self, winner: Option<Color>, termination: Termination) {
+        *self.result.lock().unwrap() = Some(GameResult {
+            winner,
+            termination,
+        });
+        *self.draw_offer.lock().unwrap() = None;
+
+        // The match is decided on whichever board ends first: the partner of each player on this
+        // board plays the other color on the partner board, so they share the player's result.
+        if let Some(partner) = &self.partner {
+            let mut result = partner.result.lock().unwrap();
+            if result.is_none() {
+                *result = Some(GameResult {
+                    winner: winner.map(|winner| winner.opponent()),
+                    termination: match termination {
+                        Termination::Aborted => Termination::Aborted,
+                        _ => Termination::PartnerBoard,
+                    },
+                });
+            }
+        }
+    }
+
+    fn ensure_ongoing(&self) -> Result<(), ActionError> {
+        match self.get_result() {
+            Some(_) => Err(ActionError::GameOverError),
+            None => Ok(()),
+        }
+    }
+
+    /// Resign the game on behalf of the specified player.
+    pub fn resign(&self, color: Color) -> Result<(), ActionError> {
+        self.ensure_ongoing()?;
+        self.end(Some(color.opponent()), Termination::Resignation);
+        Ok(())
+    }
+
+    /// Offer a draw on behalf of the specified player. The offer stands until the opponent
+    /// accepts or declines it, or makes a move. If the opponent has already offered a draw, the
+    /// offer is accepted instead.
+    pub fn offer_draw(&self, color: Color) -> Result<(), ActionError> {
+        self.ensure_ongoing()?;
+
+        if self.get_draw_offer() == Some(color.opponent()) {
+            return self.accept_draw(color);
+        }
+
+        *self.draw_offer.lock().unwrap() = Some(color);
+        Ok(())
+    }
+
+    /// Accept the opponent's draw offer on behalf of the specified player.
+    pub fn accept_draw(&self, color: Color) -> Result<(), ActionError> {
+        self.ensure_ongoing()?;
+
+        if self.get_draw_offer() != Some(color.opponent()) {
+            return Err(ActionError::NoDrawOfferError);
+        }
+
+        self.end(None, Termination::DrawAgreement);
+        Ok(())
+    }
+
+    /// Decline the opponent's draw offer on behalf of the specified player.
+    pub fn decline_draw(&self, color: Color) -> Result<(), ActionError> {
+        self.ensure_ongoing()?;
+
+        let mut draw_offer = self.draw_offer.lock().unwrap();
+        if *draw_offer != Some(color.opponent()) {
+            return Err(ActionError::NoDrawOfferError);
+        }
+
+        *draw_offer = None;
+        Ok(())
+    }
+
+    /// Berserk on behalf of the specified player, giving up half their time (and their increment).
+    /// This is only possible in a timed game, before the player has made a move.
+    pub fn berserk(&self, color: Color) -> Result<(), ActionError> {
+        self.ensure_ongoing()?;
+
+        // The player has moved once the ply after their first move has been reached.
+        let has_moved = self.get_move_count() + usize::from(self.get_first_move() == color) >= 2;
+        let mut clock = self.clock.lock().unwrap();
+        match clock.as_mut() {
+            Some(clock) if !has_moved && !clock.is_berserk(color) => {
+                clock.berserk(color);
+                Ok(())
+            }
+            _ => Err(ActionError::BerserkNotAllowedError),
+        }
+    }
+
+    /// Check whether the specified player has berserked.
+    pub fn is_berserk(&self, color: Color) -> bool {
+        self.clock
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|clock| clock.is_berserk(color))
+    }
+
+    /// Get the current state of the players' clocks, if the game is timed.
+    pub fn get_clock(&self) -> Option<ClockState> {
+        self.clock
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|clock| clock.get_state(Utc::now()))
+    }
+
+    /// End the game if the player to move has run out of time. Returns whether the game ended
+    /// as a result.
+    pub fn check_flag(&self) -> bool {
+        if self.get_result().is_some() {
+            return false;
+        }
+
+        let color = self.get_current_move();
+        let flagged = self
+            .clock
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|clock| clock.is_flagged(color, Utc::now()));
+        if flagged {
+            self.end(Some(color.opponent()), Termination::Timeout);
+        }
+        flagged
+    }
+
+    /// End the game with the specified winner (or as a draw) on the authority of an arbiter.
+    pub fn adjudicate(&self, winner: Option<Color>) -> Result<(), ActionError> {
+        self.ensure_ongoing()?;
+        self.end(winner, Termination::Adjudication);
+        Ok(())
+    }
+
+    /// Abort the game. This is only possible before both players have made a move.
+    pub fn abort(&self) -> Result<(), ActionError> {
+        self.ensure_ongoing()?;
+
+        if self.get_move_count() >= 2 {
+            return Err(ActionError::AbortNotAllowedError);
+        }
+
+        self.end(None, Termination::Aborted);
+        Ok(())
+    }
+
+    /// Get the piece at the specified position on a board, or [None] if the square is empty or
+    /// off the board.
+    pub fn get_piece(board: &GameBoard, position: &Position) -> Option<Piece> {
+        if !board.contains(position) {
+            return None;
+        }
+        board[position.rank][position.file]
+    }
+
+    pub fn is_player_in_check(&self, color: Color) -> bool {
+        self.get_rules()
+            .is_in_check(&self.board.lock().unwrap(), color)
+    }
+
+    /// Check whether the specified player's king is attacked on the specified board.
+    pub fn is_in_check_on_board(board: &GameBoard, color: Color) -> bool {
+        for rank in 0..8 {
+            for file in 0..board.files() {
+                let piece = match board[rank][file] {
+                    Some(piece) if piece.color != color => piece,
+                    _ => continue,
+                };
+
+                let position = Position { rank, file };
+                for target in piece.get_valid_moves_on_board(board, &position) {
+                    if let Some(Piece {
+                        kind: King,
+                        color: c,
+                        ..
+                    }) = board[target.rank][target.file]
+                    {
+                        if c == color {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Get the legal moves for the player to move: the valid moves that do not leave their own
+    /// king in check.
+    pub fn get_legal_moves(&self) -> Vec<(Position, Position)> {
+        let board = *self.board.lock().unwrap();
+        self.get_rules()
+            .get_legal_moves(&board, self.get_current_move())
+    }
+
+    /// Get the legal moves for the specified player on the specified board under the standard
+    /// rules, ordered by the square moved from and then the square moved to.
+    pub fn get_legal_moves_on_board(board: &GameBoard, color: Color) -> Vec<(Position, Position)> {
+        let mut moves = Vec::new();
+        for rank in 0..8 {
+            for file in 0..board.files() {
+                let piece = match board[rank][file] {
+                    Some(piece) if piece.color == color => piece,
+                    _ => continue,
+                };
+
+                let position = Position { rank, file };
+                for new_position in piece.get_valid_moves_on_board(board, &position) {
+                    let after = Game::apply_move_on_board(board, &position, &new_position);
+                    if !Game::is_in_check_on_board(&after, color) {
+                        moves.push((position, new_position));
+                    }
+                }
+            }
+        }
+        moves.extend(Game::get_castling_moves_on_board(board, color));
+
+        moves.sort_by_key(|(from, to)| (from.rank, from.file, to.rank, to.file));
+        moves
+    }
+
+    /// Get a copy of the specified board after moving the piece at `position` to `new_position`
+    /// (capturing any piece already there). A king moving onto one of its own rooks castles with
+    /// it.
+    pub fn apply_move_on_board(
+        board: &GameBoard,
+        position: &Position,
+        new_position: &Position,
+    ) -> GameBoard {
+        let mut board = *board;
+        let Some(mut piece) = board[position.rank][position.file].take() else {
+            return board;
+        };
+        piece.move_count += 1;
+
+        let target = board[new_position.rank][new_position.file];
+        if let Some(mut rook) =
+            target.filter(|rook| piece.kind == King && rook.color == piece.color)
+        {
+            let (king_file, rook_file) =
+                Game::get_castling_destinations(&board, new_position.file > position.file);
+            rook.move_count += 1;
+            board[new_position.rank][new_position.file] = None;
+            board[position.rank][rook_file] = Some(rook);
+            board[position.rank][king_file] = Some(piece);
+            return board;
+        }
+
+        board[new_position.rank][new_position.file] = Some(piece);
+        board
+    }
+
+    /// Get the rook that the king at `position` castles with by moving to `new_position`, either
+    /// onto the rook itself or (as in standard chess) two squares towards it. Returns [None] if
+    /// the move is not castling, without checking whether castling is allowed.
+    pub fn get_castling_rook(
+        board: &GameBoard,
+        position: &Position,
+        new_position: &Position,
+    ) -> Option<Position> {
+        let king = Game::get_piece(board, position)?;
+        if king.kind != King || king.move_count != 0 || position.rank != new_position.rank {
+            return None;
+        }
+
+        let is_castling_rook = |file: usize| matches!(board[position.rank][file], Some(rook) if rook.kind == Rook && rook.color == king.color && rook.move_count == 0);
+        if is_castling_rook(new_position.file) {
+            return Some(*new_position);
+        }
+
+        let kingside = new_position.file > position.file;
+        let (king_to, _) = Game::get_castling_destinations(board, kingside);
+        if position.file.abs_diff(new_position.file) < 2 || new_position.file != king_to {
+            return None;
+        }
+        let file = if kingside {
+            (position.file + 1..board.files()).find(|&file| is_castling_rook(file))
+        } else {
+            (0..position.file)
+                .rev()
+                .find(|&file| is_castling_rook(file))
+        }?;
+        Some(Position::new(position.rank, file))
+    }
+
+    /// Get the files the king and rook end on when castling on the kingside (or queenside). As in
+    /// standard chess, the king ends on the second file from the edge of the board, with the rook
+    /// beside it towards the center (e.g., the i- and h-files on the kingside of a 10-file board).
+    pub fn get_castling_destinations(board: &GameBoard, kingside: bool) -> (usize, usize) {
+        if kingside {
+            (board.files() - 2, board.files() - 3)
+        } else {
+            (2, 3)
+        }
+    }
+
+    /// Get the castling moves available to the specified player, as the king moving onto the
+    /// rook it castles with. Castling requires that neither piece has moved, that the squares
+    /// between them and their destinations are empty, and that the king is not in check and does
+    /// not pass through or land on an attacked square.
+    pub fn get_castling_moves_on_board(
+        board: &GameBoard,
+        color: Color,
+    ) -> Vec<(Position, Position)> {
+        let back_rank = match color {
+            White => 7,
+            Black => 0,
+        };
+        let unmoved = |file: usize, kind: PieceKind| matches!(board[back_rank][file], Some(piece) if piece.kind == kind && piece.color == color && piece.move_count == 0);
+
+        let Some(king_file) = (0..board.files()).find(|&file| unmoved(file, King)) else {
+            return Vec::new();
+        };
+        if Game::is_in_check_on_board(board, color) {
+            return Vec::new();
+        }
+
+        let mut moves = Vec::new();
+        for rook_file in (0..board.files()).filter(|&file| unmoved(file, Rook)) {
+            let (king_to, rook_to) = Game::get_castling_destinations(board, rook_file > king_file);
+
+            // Only the king and rook may stand between them and their destinations.
+            let files = [king_file, rook_file, king_to, rook_to];
+            let (first, last) = (*files.iter().min().unwrap(), *files.iter().max().unwrap());
+            let is_clear = (first..=last).all(|file| {
+                file == king_file || file == rook_file || board[back_rank][file].is_none()
+            });
+            if !is_clear {
+                continue;
+            }
+
+            // The king may not pass through or land on an attacked square.
+            let mut without_pieces = *board;
+            let king = without_pieces[back_rank][king_file].take();
+            without_pieces[back_rank][rook_file] = None;
+            let is_attacked = (king_file.min(king_to)..=king_file.max(king_to)).any(|file| {
+                let mut board = without_pieces;
+                board[back_rank][file] = king;
+                Game::is_in_check_on_board(&board, color)
+            });
+            if !is_attacked {
+                moves.push((
+                    Position::new(back_rank, king_file),
+                    Position::new(back_rank, rook_file),
+                ));
+            }
+        }
+        moves
+    }
+
+    /// Check whether the player to move has been checkmated.
+    pub fn is_checkmate(&self) -> bool {
+        self.is_player_in_check(self.get_current_move()) && self.get_legal_moves().is_empty()
+    }
+
+    /// Check whether the player to move has been stalemated.
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_player_in_check(self.get_current_move()) && self.get_legal_moves().is_empty()
+    }
+
+    /// Get the list of moves made in the game so far.
+    pub fn get_moves(&self) -> Vec<Move> {
+        self.moves.lock().unwrap().clone()
+    }
+
+    pub fn get_move_count(&self) -> usize {
+        self.moves.lock().unwrap().len()
+    }
+
+    /// Get the player that moved first (White, unless the game started from a position with
+    /// Black to move or Black receives move odds).
+    fn get_first_move(&self) -> Color {
+        match &self.options.handicap {
+            Some(handicap) if handicap.moves > 0 => handicap.receiver(),
+            _ => self
+                .options
+                .starting_fen
+                .as_ref()
+                .map(|fen| fen.turn)
+                .unwrap_or(White),
+        }
+    }
+
+    /// Get the number of extra moves (beyond the first) the player receiving move odds makes
+    /// before their opponent's first move.
+    pub(crate) fn get_extra_moves(&self) -> usize {
+        self.options
+            .handicap
+            .as_ref()
+            .map_or(0, |handicap| handicap.extra_moves())
+    }
+
+    /// Get the number of plies made as if the players had always taken turns, i.e., counting the
+    /// moves a player receiving move odds makes in a row as one.
+    fn get_alternating_ply_count(&self) -> usize {
+        let count = self.get_move_count();
+        count - count.min(self.get_extra_moves())
+    }
+
+    /// Check whether the next move is one of the extra moves made by a player receiving move odds,
+    /// after which they move again.
+    fn is_extra_move(&self) -> bool {
+        self.get_move_count() < self.get_extra_moves()
+    }
+
+    pub fn get_current_move(&self) -> Color {
+        if self.get_alternating_ply_count().is_multiple_of(2) {
+            self.get_first_move()
+        } else {
+            self.get_first_move().opponent()
+        }
+    }
+
+    /// Get the number of plies since the last capture or pawn move.
+    pub fn get_halfmove_clock(&self) -> usize {
+        *self.halfmove_clock.lock().unwrap()
+    }
+
+    /// Get the state besides the board that the variant's rules depend on (e.g., the number of
+    /// checks each player has given in three-check).
+    pub fn get_variant_state(&self) -> VariantState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Get the number of the current move, which starts at 1 (unless the game started from a
+    /// later position) and is incremented after Black moves.
+    pub fn get_fullmove_number(&self) -> usize {
+        let initial = self
+            .options
+            .starting_fen
+            .as_ref()
+            .map(|fen| fen.fullmove_number)
+            .unwrap_or(1);
+
+        // Count the plies as if the game started with White to move.
+        let plies = self.get_alternating_ply_count() + usize::from(self.get_first_move() == Black);
+        initial + plies / 2
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::{MoveError, SetupError};
+    use crate::fen::Fen;
+    use crate::game::Color::{Black, White};
+    use crate::game::PieceKind::{King, Knight, Pawn, Queen, Rook};
+    use crate::game::{Color, Game, GameOptions, Handicap, Piece, PieceKind, Termination};
+    use crate::moves::{Move, Position};
+    use crate::time_control::TimeControl;
+    use std::str::FromStr;
+    use uuid::Uuid;
+
+    #[test]
+    fn check_bishop_value() {
+        assert_eq!(3, PieceKind::Bishop.material_value())
+    }
+
+    #[test]
+    fn check_starting_board() {
         let game: Game = Game::new();
 
         // Count that we have two queens at the end.
@@ -440,7 +1923,7 @@ mod test {
 
     #[test]
     fn test_is_king_in_check() {
-        let game = Game::new();
+        let mut game = Game::new();
         assert!(!game.is_player_in_check(White));
         assert!(!game.is_player_in_check(Black));
 
@@ -462,19 +1945,103 @@ mod test {
         assert_eq!(black_king.kind, King);
         assert_eq!(black_king.color, Black);
 
-        {
-            // Move the white queen and the black king next to each other in the middle of the board.
-            let mut board = game.board.lock().unwrap();
-            board[white_queen_position_original.rank][white_queen_position_original.file] = None;
-            board[black_king_position_original.rank][black_king_position_original.file] = None;
-            board[white_queen_position.rank][white_queen_position.file] = Some(white_queen);
-            board[black_king_position.rank][black_king_position.file] = Some(black_king);
-        }
+        // Move the white queen and the black king next to each other in the middle of the board.
+        game.set_piece(&white_queen_position_original, None)
+            .unwrap();
+        game.set_piece(&black_king_position_original, None).unwrap();
+        game.set_piece(&white_queen_position, Some(white_queen))
+            .unwrap();
+        game.set_piece(&black_king_position, Some(black_king))
+            .unwrap();
 
         assert!(!game.is_player_in_check(White));
         assert!(game.is_player_in_check(Black));
     }
 
+    #[test]
+    fn test_board_editor() {
+        let square = |square: &str| Position::from_str(square).unwrap();
+        let piece = |kind: PieceKind, color: Color| Some(Piece::new(kind, color));
+
+        let mut game = Game::new();
+        game.set_piece(&square("E8"), None).unwrap();
+        assert!(matches!(
+            game.validate_starting_position(),
+            Err(SetupError::KingCountError)
+        ));
+        assert!(matches!(
+            game.move_piece_at_position(&square("E2"), &square("E4")),
+            Err(MoveError::IllegalPositionError(SetupError::KingCountError))
+        ));
+
+        // Moving the king off its back rank takes away Black's castling rights.
+        game.set_piece(&square("E6"), piece(King, Black)).unwrap();
+        game.validate_starting_position().unwrap();
+        assert_eq!(
+            game.get_starting_position().to_string(),
+            "rnbq1bnr/pppppppp/4k3/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1"
+        );
+
+        game.set_piece(&square("A1"), piece(Pawn, White)).unwrap();
+        assert!(matches!(
+            game.validate_starting_position(),
+            Err(SetupError::PawnOnBackRankError)
+        ));
+        game.set_piece(&square("A1"), piece(Queen, White)).unwrap();
+        assert!(matches!(
+            game.validate_starting_position(),
+            Err(SetupError::TooManyPiecesError)
+        ));
+        game.set_piece(&square("A2"), None).unwrap();
+        game.validate_starting_position().unwrap();
+
+        // The king on e6 may not be in check with White to move, nor in check by two knights.
+        game.set_piece(&square("B1"), None).unwrap();
+        game.set_piece(&square("D4"), piece(Knight, White)).unwrap();
+        assert!(matches!(
+            game.validate_starting_position(),
+            Err(SetupError::OpponentInCheckError)
+        ));
+        game.set_turn(Black).unwrap();
+        game.validate_starting_position().unwrap();
+        game.set_piece(&square("G1"), None).unwrap();
+        game.set_piece(&square("F4"), piece(Knight, White)).unwrap();
+        assert!(matches!(
+            game.validate_starting_position(),
+            Err(SetupError::ImpossibleCheckError)
+        ));
+        game.set_piece(&square("F4"), None).unwrap();
+        game.set_piece(&square("D4"), None).unwrap();
+
+        game.set_castling("Kk").unwrap();
+        assert!(matches!(
+            game.validate_starting_position(),
+            Err(SetupError::InvalidCastlingError)
+        ));
+        game.set_castling("K").unwrap();
+        // The en passant square needs a pawn that has just skipped over it.
+        assert!(matches!(
+            game.set_en_passant(Some(square("E3"))),
+            Err(SetupError::InvalidEnPassantError)
+        ));
+        game.set_piece(&square("E2"), None).unwrap();
+        game.set_piece(&square("E4"), piece(Pawn, White)).unwrap();
+        assert!(matches!(
+            game.set_en_passant(Some(square("E6"))),
+            Err(SetupError::InvalidEnPassantError)
+        ));
+        game.set_en_passant(Some(square("E3"))).unwrap();
+        game.validate_starting_position().unwrap();
+        assert_eq!(game.get_starting_position().en_passant, Some(square("E3")));
+
+        game.move_piece_at_position(&square("E6"), &square("D6"))
+            .unwrap();
+        assert!(matches!(
+            game.set_turn(White),
+            Err(SetupError::GameStartedError)
+        ));
+    }
+
     #[test]
     fn test_turns() {
         let game = Game::new();
@@ -549,4 +2116,360 @@ mod test {
         assert_eq!(game.get_current_move(), Black);
         assert!(game.get_piece_by_position(&white_pawn_position_1).is_none());
     }
+
+    #[test]
+    fn test_fork() {
+        let game = Game::new_with_id(Some("parent".to_string()));
+
+        let moves = [("E2", "E4"), ("E7", "E5"), ("G1", "F3")];
+        for (from, to) in moves {
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let fork = game.fork(Some("child".to_string()), 2).unwrap();
+        assert_eq!(fork.get_move_count(), 2);
+        assert_eq!(fork.get_current_move(), White);
+        assert!(fork
+            .get_piece_by_position(&Position::from_str("F3").unwrap())
+            .is_none());
+        assert!(fork
+            .get_piece_by_position(&Position::from_str("E5").unwrap())
+            .is_some());
+
+        let origin = fork.forked_from.as_ref().unwrap();
+        assert_eq!(origin.game_id.as_deref(), Some("parent"));
+        assert_eq!(origin.ply, 2);
+
+        // The original game is left untouched.
+        assert_eq!(game.get_move_count(), 3);
+
+        // Forking beyond the end of the game fails.
+        assert!(game.fork(None, 4).is_err());
+    }
+
+    #[test]
+    fn test_resign() {
+        let game = Game::new();
+        game.resign(White).unwrap();
+
+        let result = game.get_result().unwrap();
+        assert_eq!(result.winner, Some(Black));
+        assert_eq!(result.termination, Termination::Resignation);
+        assert_eq!(result.to_pgn(), "0-1");
+
+        // No further moves or actions are possible.
+        assert!(game.resign(Black).is_err());
+        assert!(game
+            .move_piece_at_position(
+                &Position::from_str("E2").unwrap(),
+                &Position::from_str("E4").unwrap()
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_draw_offers() {
+        let game = Game::new();
+        let play = |from: &str, to: &str| {
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap()
+        };
+
+        // A player cannot accept their own offer.
+        game.offer_draw(White).unwrap();
+        assert!(game.accept_draw(White).is_err());
+
+        // The offer stands while the offering player moves...
+        play("E2", "E4");
+        assert_eq!(game.get_draw_offer(), Some(White));
+
+        // ...but expires once the opponent has moved.
+        play("E7", "E5");
+        assert_eq!(game.get_draw_offer(), None);
+        assert!(game.accept_draw(Black).is_err());
+
+        game.offer_draw(Black).unwrap();
+        game.decline_draw(White).unwrap();
+        assert_eq!(game.get_draw_offer(), None);
+
+        game.offer_draw(Black).unwrap();
+        game.accept_draw(White).unwrap();
+
+        let result = game.get_result().unwrap();
+        assert_eq!(result.winner, None);
+        assert_eq!(result.termination, Termination::DrawAgreement);
+        assert_eq!(result.to_pgn(), "1/2-1/2");
+    }
+
+    #[test]
+    fn test_abort() {
+        let game = Game::new();
+        game.move_piece_at_position(
+            &Position::from_str("E2").unwrap(),
+            &Position::from_str("E4").unwrap(),
+        )
+        .unwrap();
+        game.abort().unwrap();
+        assert_eq!(game.get_result().unwrap().termination, Termination::Aborted);
+
+        let game = Game::new();
+        for (from, to) in [("E2", "E4"), ("E7", "E5")] {
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap();
+        }
+        assert!(game.abort().is_err());
+        assert!(game.get_result().is_none());
+    }
+
+    #[test]
+    fn test_checkmate() {
+        let game = Game::new();
+        for (from, to) in [("F2", "F3"), ("E7", "E5"), ("G2", "G4")] {
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap();
+            assert!(game.get_result().is_none());
+        }
+        assert_eq!(game.get_legal_moves().len(), 30);
+
+        game.move_piece_at_position(
+            &Position::from_str("D8").unwrap(),
+            &Position::from_str("H4").unwrap(),
+        )
+        .unwrap();
+        assert!(game.is_checkmate());
+        assert!(game.get_legal_moves().is_empty());
+        let result = game.get_result().unwrap();
+        assert_eq!(result.winner, Some(Black));
+        assert_eq!(result.termination, Termination::Checkmate);
+    }
+
+    #[test]
+    fn test_castling() {
+        let position = |s: &str| Position::from_str(s).unwrap();
+        let game_from_fen = |fen: &str| {
+            let options = GameOptions {
+                starting_fen: Some(Fen::from_str(fen).unwrap()),
+                ..GameOptions::default()
+            };
+            Game::new_with_options(None, options)
+        };
+
+        // Castling is written as the king moving onto the rook, or two squares towards it.
+        let game = game_from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let legal_moves = game.get_legal_moves();
+        assert!(legal_moves.contains(&(position("E1"), position("H1"))));
+        assert!(legal_moves.contains(&(position("E1"), position("A1"))));
+
+        game.move_piece_at_position(&position("E1"), &position("G1"))
+            .unwrap();
+        assert_eq!(
+            game.get_piece_by_position(&position("G1")).unwrap().kind,
+            King
+        );
+        assert_eq!(
+            game.get_piece_by_position(&position("F1")).unwrap().kind,
+            Rook
+        );
+        assert_eq!(
+            game.get_moves(),
+            vec![Move::Normal(position("E1"), position("H1"))]
+        );
+        assert_eq!(game.get_halfmove_clock(), 1);
+
+        game.move_piece_at_position(&position("E8"), &position("A8"))
+            .unwrap();
+        assert_eq!(
+            game.get_piece_by_position(&position("C8")).unwrap().kind,
+            King
+        );
+        assert_eq!(
+            game.get_piece_by_position(&position("D8")).unwrap().kind,
+            Rook
+        );
+        assert_eq!(Fen::from_game(&game).castling, Vec::new());
+
+        // The king may not castle out of, through or into check, or past other pieces.
+        let game = game_from_fen("r3k2r/8/8/8/8/8/5r2/RN2K2R w KQkq - 0 1");
+        assert!(game
+            .move_piece_at_position(&position("E1"), &position("G1"))
+            .is_err());
+        assert!(game
+            .move_piece_at_position(&position("E1"), &position("A1"))
+            .is_err());
+        let game = game_from_fen("4k3/8/8/8/8/8/8/R3K2r w Q - 0 1");
+        assert!(game
+            .move_piece_at_position(&position("E1"), &position("A1"))
+            .is_err());
+
+        // In Chess960, the king and rook end on the same squares as in standard chess, wherever
+        // they start.
+        let game = game_from_fen("1k6/8/8/8/8/8/8/RK5R w HA - 0 1");
+        game.move_piece_at_position(&position("B1"), &position("A1"))
+            .unwrap();
+        assert_eq!(
+            game.get_piece_by_position(&position("C1")).unwrap().kind,
+            King
+        );
+        assert_eq!(
+            game.get_piece_by_position(&position("D1")).unwrap().kind,
+            Rook
+        );
+        assert!(game.get_piece_by_position(&position("A1")).is_none());
+        assert!(game.get_piece_by_position(&position("B1")).is_none());
+
+        let game = game_from_fen("4k3/8/8/8/8/8/8/5KR1 w G - 0 1");
+        game.move_piece_at_position(&position("F1"), &position("G1"))
+            .unwrap();
+        assert_eq!(
+            game.get_piece_by_position(&position("G1")).unwrap().kind,
+            King
+        );
+        assert_eq!(
+            game.get_piece_by_position(&position("F1")).unwrap().kind,
+            Rook
+        );
+    }
+
+    #[test]
+    fn test_berserk() {
+        let play = |game: &Game, from: &str, to: &str| {
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+        };
+
+        assert!(Game::new().berserk(White).is_err());
+
+        let options = GameOptions {
+            time_control: Some(TimeControl::new(180, 2)),
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        game.berserk(White).unwrap();
+        assert!(game.berserk(White).is_err());
+        assert!(game.is_berserk(White));
+        assert_eq!(game.get_clock().unwrap().remaining[&White], 90_000);
+
+        // Black may still berserk after White's first move, but not after their own.
+        play(&game, "E2", "E4").unwrap();
+        play(&game, "E7", "E5").unwrap();
+        assert!(game.berserk(Black).is_err());
+        assert!(!game.is_berserk(Black));
+
+        // A player whose time has run out loses on their next move (or when the flag is checked).
+        let options = GameOptions {
+            time_control: Some(TimeControl::new(0, 0)),
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        play(&game, "E2", "E4").unwrap();
+        assert!(play(&game, "E7", "E5").is_err());
+        let result = game.get_result().unwrap();
+        assert_eq!(result.winner, Some(White));
+        assert_eq!(result.termination, Termination::Timeout);
+        assert!(!game.check_flag());
+    }
+
+    #[test]
+    fn test_handicap() {
+        let play = |game: &Game, from: &str, to: &str| {
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+        };
+
+        // White gives queen and knight odds, two moves and half the time.
+        let options = GameOptions {
+            time_control: Some(TimeControl::new(300, 0)),
+            handicap: Some(Handicap {
+                giver: White,
+                pieces: vec![Queen, Knight],
+                moves: 2,
+                time_control: Some(TimeControl::new(150, 0)),
+            }),
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        assert_eq!(
+            game.get_options()
+                .starting_fen
+                .as_ref()
+                .unwrap()
+                .to_string(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/R1B1KBNR b KQkq - 0 1"
+        );
+        let clock = game.get_clock().unwrap();
+        assert_eq!(clock.remaining[&White], 150_000);
+        assert_eq!(clock.remaining[&Black], 300_000);
+
+        // Black moves twice before White's first move, and the first of these may not give check.
+        assert_eq!(game.get_current_move(), Black);
+        play(&game, "E7", "E5").unwrap();
+        assert_eq!(game.get_current_move(), Black);
+        assert!(game.get_clock().unwrap().running.is_none());
+        play(&game, "D7", "D5").unwrap();
+        assert_eq!(game.get_current_move(), White);
+        assert_eq!(game.get_fullmove_number(), 2);
+        play(&game, "E2", "E4").unwrap();
+        assert_eq!(game.get_current_move(), Black);
+
+        let options = GameOptions {
+            starting_fen: Some(Fen::from_str("4k3/8/8/8/8/8/5P2/R3K3 b - - 0 1").unwrap()),
+            handicap: Some(Handicap {
+                giver: Black,
+                moves: 3,
+                ..Handicap::default()
+            }),
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        assert_eq!(game.get_current_move(), White);
+        play(&game, "F2", "F4").unwrap();
+        assert!(matches!(
+            play(&game, "A1", "A8"),
+            Err(MoveError::IllegalMoveError)
+        ));
+        play(&game, "A1", "A7").unwrap();
+        play(&game, "A7", "A8").unwrap();
+        assert_eq!(game.get_current_move(), Black);
+
+        // The PGN starts from the position before White's last extra move.
+        let pgn = crate::pgn::write_game(&game);
+        assert!(pgn.contains("[FEN \"4k3/R7/8/8/5P2/8/8/4K3 w - - 1 1\"]"));
+        assert!(pgn.contains("1. Ra8+ *"));
+    }
+
+    #[test]
+    fn test_seats() {
+        let mut game = Game::new();
+        assert!(!game.is_seat_claimed(White));
+
+        let user_id = Uuid::new_v4();
+        let white_token = game.claim_seat(White, Some(user_id)).unwrap();
+        assert!(game.claim_seat(White, None).is_err());
+        let black_token = game.claim_seat(Black, None).unwrap();
+
+        assert_eq!(game.get_player(White), Some(user_id));
+        assert_eq!(game.get_player(Black), None);
+
+        assert_ne!(white_token, black_token);
+        assert_eq!(game.get_seat_color(&white_token), Some(White));
+        assert_eq!(game.get_seat_color(&black_token), Some(Black));
+        assert_eq!(game.get_seat_color("spectator"), None);
+    }
 }
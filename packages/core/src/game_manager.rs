@@ -1,42 +1,143 @@
-use crate::game::Game;
+use crate::analysis::Analysis;
+use crate::error::{ForkError, PgnError};
+use crate::game::Color::{Black, White};
+use crate::game::{Game, GameOptions, Variant};
+use crate::pgn;
 use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 pub struct GameManager {
     games: BTreeMap<Uuid, Arc<Mutex<Game>>>,
+    analyses: BTreeMap<Uuid, Arc<Mutex<Analysis>>>,
 }
 
 impl GameManager {
     pub fn new() -> GameManager {
         GameManager {
             games: BTreeMap::new(),
+            analyses: BTreeMap::new(),
         }
     }
 
     /// Create and return a new game instance.
     /// The created game will have a UUID associated with it.
     pub fn new_game(&mut self) -> Arc<Mutex<Game>> {
+        self.new_game_with_options(GameOptions::default())
+    }
+
+    /// Create and return a new game instance with the specified [GameOptions].
+    pub fn new_game_with_options(&mut self, options: GameOptions) -> Arc<Mutex<Game>> {
         let id = Uuid::new_v4();
-        let game = Game::new_with_id(Some(id.to_string()));
+        let game = Game::new_with_options(Some(id.to_string()), options);
         let game_ref = Arc::new(Mutex::new(game));
 
         self.games.insert(id, game_ref.clone());
         game_ref
     }
 
+    /// Create the two linked games of a Bughouse match with the specified [GameOptions] (played as
+    /// Bughouse whatever variant they specify). Each team plays White on one board and Black on
+    /// the other. Bughouse matches are unrated, as ratings are kept for individual players.
+    pub fn new_bughouse_match(&mut self, options: GameOptions) -> [Arc<Mutex<Game>>; 2] {
+        let options = GameOptions {
+            rated: false,
+            variant: Variant::Bughouse,
+            ..options
+        };
+        let ids = [Uuid::new_v4(), Uuid::new_v4()];
+        let [mut first, mut second] =
+            ids.map(|id| Game::new_with_options(Some(id.to_string()), options.clone()));
+        Game::link_partners(&mut first, &mut second);
+
+        let games = [first, second].map(|game| Arc::new(Mutex::new(game)));
+        for (id, game) in ids.iter().zip(&games) {
+            self.games.insert(*id, game.clone());
+        }
+        games
+    }
+
     /// Get the list of all games.
     pub fn get_all_games(&self) -> Vec<Arc<Mutex<Game>>> {
         self.games.values().cloned().collect()
     }
 
+    /// Get the list of games in which the specified user holds a seat.
+    pub fn get_games_for_user(&self, user_id: Uuid) -> Vec<Arc<Mutex<Game>>> {
+        self.games
+            .values()
+            .filter(|game| {
+                let game = game.lock().unwrap();
+                game.get_player(White) == Some(user_id) || game.get_player(Black) == Some(user_id)
+            })
+            .cloned()
+            .collect()
+    }
+
     pub fn get_game(&self, id: Uuid) -> Option<Arc<Mutex<Game>>> {
         self.games.get(&id).cloned()
     }
 
+    /// Create a new game, with a new UUID, whose history is the first `ply` moves of the game
+    /// with the specified ID. The original game is left untouched.
+    pub fn fork_game(&mut self, id: Uuid, ply: usize) -> Result<Arc<Mutex<Game>>, ForkError> {
+        let original = self.get_game(id).ok_or(ForkError::GameNotFoundError)?;
+
+        let fork_id = Uuid::new_v4();
+        let game = original
+            .lock()
+            .unwrap()
+            .fork(Some(fork_id.to_string()), ply)?;
+        let game_ref = Arc::new(Mutex::new(game));
+
+        self.games.insert(fork_id, game_ref.clone());
+        Ok(game_ref)
+    }
+
+    /// Import a game from PGN (including annotations on the main line).
+    /// The imported game will have a UUID associated with it.
+    pub fn import_game(&mut self, pgn: &str) -> Result<Arc<Mutex<Game>>, PgnError> {
+        let id = Uuid::new_v4();
+        let game = pgn::read_game(pgn, Some(id.to_string()))?;
+        let game_ref = Arc::new(Mutex::new(game));
+
+        self.games.insert(id, game_ref.clone());
+        Ok(game_ref)
+    }
+
     pub fn delete_game(&mut self, id: Uuid) {
         self.games.remove(&id);
     }
+
+    /// Create and return a new analysis board.
+    /// The created analysis will have a UUID associated with it.
+    pub fn new_analysis(&mut self) -> Arc<Mutex<Analysis>> {
+        let id = Uuid::new_v4();
+        let analysis = Analysis::new_with_id(Some(id.to_string()));
+        let analysis_ref = Arc::new(Mutex::new(analysis));
+
+        self.analyses.insert(id, analysis_ref.clone());
+        analysis_ref
+    }
+
+    /// Import an analysis board from PGN (including variations and annotations).
+    /// The imported analysis will have a UUID associated with it.
+    pub fn import_analysis(&mut self, pgn: &str) -> Result<Arc<Mutex<Analysis>>, PgnError> {
+        let id = Uuid::new_v4();
+        let analysis = pgn::read_analysis(pgn, Some(id.to_string()))?;
+        let analysis_ref = Arc::new(Mutex::new(analysis));
+
+        self.analyses.insert(id, analysis_ref.clone());
+        Ok(analysis_ref)
+    }
+
+    pub fn get_analysis(&self, id: Uuid) -> Option<Arc<Mutex<Analysis>>> {
+        self.analyses.get(&id).cloned()
+    }
+
+    pub fn delete_analysis(&mut self, id: Uuid) {
+        self.analyses.remove(&id);
+    }
 }
 
 impl Default for GameManager {
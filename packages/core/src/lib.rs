@@ -1,4 +1,19 @@
+pub mod analysis;
+pub mod annotation;
+pub mod arena;
+pub mod book;
+pub mod challenge;
+pub mod chess960;
+pub mod engine;
 pub mod error;
+pub mod fen;
 pub mod game;
 pub mod game_manager;
+pub mod lobby;
 pub mod moves;
+pub mod pgn;
+pub mod rating;
+pub mod rules;
+pub mod time_control;
+pub mod tournament;
+pub mod user_manager;
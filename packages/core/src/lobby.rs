@@ -0,0 +1,267 @@
+use crate::error::SeekError;
+use crate::game::Color::{Black, White};
+use crate::game::{Color, GameOptions};
+use chrono::serde::ts_milliseconds;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// The color a player would like to play as.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorPreference {
+    White,
+    Black,
+    #[default]
+    Random,
+}
+
+impl ColorPreference {
+    /// Get the preferred color, or [None] if the player has no preference.
+    pub fn color(&self) -> Option<Color> {
+        match *self {
+            ColorPreference::White => Some(White),
+            ColorPreference::Black => Some(Black),
+            ColorPreference::Random => None,
+        }
+    }
+
+    /// Get the preferred color, choosing one at random if the player has no preference.
+    pub fn resolve(&self) -> Color {
+        match self.color() {
+            Some(color) => color,
+            None if rand::random::<bool>() => White,
+            None => Black,
+        }
+    }
+}
+
+/// The (inclusive) range of ratings a player is willing to be paired against.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RatingRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl RatingRange {
+    pub fn contains(&self, rating: f64) -> bool {
+        self.min <= rating && rating <= self.max
+    }
+}
+
+/// A request from a player to be paired with any other player for a game with the specified
+/// settings.
+#[derive(Clone, Debug, Serialize)]
+pub struct Seek {
+    pub id: Uuid,
+
+    /// The ID of the user that posted the seek.
+    pub user_id: Uuid,
+
+    /// The user's rating in the time control category of the seek, at the time it was posted.
+    pub rating: f64,
+
+    pub options: GameOptions,
+
+    pub color: ColorPreference,
+
+    /// The ratings the user is willing to be paired against, or [None] for any rating.
+    pub rating_range: Option<RatingRange>,
+
+    /// The [DateTime] the seek was posted.
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl Seek {
+    pub fn new(
+        user_id: Uuid,
+        rating: f64,
+        options: GameOptions,
+        color: ColorPreference,
+        rating_range: Option<RatingRange>,
+    ) -> Seek {
+        Seek {
+            id: Uuid::new_v4(),
+            user_id,
+            rating,
+            options,
+            color,
+            rating_range,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Check whether this seek can be paired with another: the seeks must be from different
+    /// users, for the same game settings, with compatible color preferences and with each
+    /// player's rating within the other's range.
+    pub fn is_compatible_with(&self, other: &Seek) -> bool {
+        let colors_compatible = match (self.color.color(), other.color.color()) {
+            (Some(color), Some(other_color)) => color != other_color,
+            _ => true,
+        };
+        let in_range = |seek: &Seek, rating: f64| {
+            seek.rating_range
+                .map(|range| range.contains(rating))
+                .unwrap_or(true)
+        };
+
+        self.user_id != other.user_id
+            && self.options == other.options
+            && colors_compatible
+            && in_range(self, other.rating)
+            && in_range(other, self.rating)
+    }
+}
+
+/// Two seeks that have been paired, with the colors each player will play as.
+#[derive(Clone, Debug)]
+pub struct Pairing {
+    pub white: Seek,
+    pub black: Seek,
+}
+
+impl Pairing {
+    /// Pair two compatible seeks, honoring their color preferences (and choosing colors at
+    /// random if neither player has a preference).
+    fn new(first: Seek, second: Seek) -> Pairing {
+        let first_color = match second.color.color() {
+            Some(color) if first.color.color().is_none() => color.opponent(),
+            _ => first.color.resolve(),
+        };
+
+        match first_color {
+            White => Pairing {
+                white: first,
+                black: second,
+            },
+            Black => Pairing {
+                white: second,
+                black: first,
+            },
+        }
+    }
+}
+
+/// The open seeks that are waiting to be paired.
+pub struct Lobby {
+    seeks: BTreeMap<Uuid, Seek>,
+}
+
+impl Lobby {
+    pub fn new() -> Lobby {
+        Lobby {
+            seeks: BTreeMap::new(),
+        }
+    }
+
+    /// Post a seek. If a compatible seek is already waiting, the oldest such seek is removed from
+    /// the lobby and the two are paired. Otherwise, the seek waits in the lobby.
+    pub fn post_seek(&mut self, seek: Seek) -> Option<Pairing> {
+        let waiting = self
+            .seeks
+            .values()
+            .filter(|waiting| waiting.is_compatible_with(&seek))
+            .min_by_key(|waiting| waiting.created_at)
+            .map(|waiting| waiting.id);
+
+        match waiting.and_then(|id| self.seeks.remove(&id)) {
+            Some(waiting) => Some(Pairing::new(waiting, seek)),
+            None => {
+                self.seeks.insert(seek.id, seek);
+                None
+            }
+        }
+    }
+
+    /// Get the list of all open seeks, oldest first.
+    pub fn get_seeks(&self) -> Vec<&Seek> {
+        let mut seeks: Vec<&Seek> = self.seeks.values().collect();
+        seeks.sort_by_key(|seek| seek.created_at);
+        seeks
+    }
+
+    pub fn get_seek(&self, id: Uuid) -> Option<&Seek> {
+        self.seeks.get(&id)
+    }
+
+    /// Withdraw an open seek on behalf of the specified user.
+    pub fn cancel_seek(&mut self, id: Uuid, user_id: Uuid) -> Result<Seek, SeekError> {
+        match self.seeks.get(&id) {
+            Some(seek) if seek.user_id == user_id => Ok(self.seeks.remove(&id).unwrap()),
+            Some(_) => Err(SeekError::NotOwnerError),
+            None => Err(SeekError::SeekNotFoundError),
+        }
+    }
+}
+
+impl Default for Lobby {
+    fn default() -> Self {
+        Lobby::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::GameOptions;
+    use crate::lobby::{ColorPreference, Lobby, RatingRange, Seek};
+    use crate::time_control::TimeControl;
+    use uuid::Uuid;
+
+    fn blitz() -> GameOptions {
+        GameOptions {
+            rated: true,
+            time_control: Some(TimeControl::new(180, 2)),
+            ..GameOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_matching() {
+        let mut lobby = Lobby::new();
+        let (alice, bob, carol) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let range = Some(RatingRange {
+            min: 1400.0,
+            max: 1600.0,
+        });
+        let seek = Seek::new(alice, 1500.0, blitz(), ColorPreference::White, range);
+        assert!(lobby.post_seek(seek.clone()).is_none());
+
+        // Seeks from the same user, with different settings, conflicting colors or out of the
+        // rating range are not paired.
+        let own = Seek::new(alice, 1500.0, blitz(), ColorPreference::White, range);
+        let casual = Seek::new(
+            bob,
+            1500.0,
+            GameOptions::default(),
+            ColorPreference::Random,
+            None,
+        );
+        let white = Seek::new(bob, 1500.0, blitz(), ColorPreference::White, None);
+        let strong = Seek::new(bob, 1900.0, blitz(), ColorPreference::Random, None);
+        for other in [own, casual, white, strong] {
+            assert!(lobby.post_seek(other).is_none());
+        }
+        assert_eq!(lobby.get_seeks().len(), 5);
+
+        let pairing = lobby
+            .post_seek(Seek::new(
+                carol,
+                1550.0,
+                blitz(),
+                ColorPreference::Random,
+                None,
+            ))
+            .unwrap();
+        assert_eq!(pairing.white.id, seek.id);
+        assert_eq!(pairing.black.user_id, carol);
+        assert!(lobby.get_seek(seek.id).is_none());
+
+        assert!(lobby.cancel_seek(seek.id, alice).is_err());
+        let own = lobby.get_seeks()[0].id;
+        assert!(lobby.cancel_seek(own, bob).is_err());
+        assert!(lobby.cancel_seek(own, alice).is_ok());
+    }
+}
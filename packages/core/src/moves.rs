@@ -1,5 +1,7 @@
-use crate::game::PieceKind::{Bishop, King, Knight, Pawn, Queen, Rook};
-use crate::game::{Color, Game, GameBoard, Piece};
+use crate::game::PieceKind::{
+    Amazon, Archbishop, Bishop, Chancellor, King, Knight, Pawn, Queen, Rook,
+};
+use crate::game::{Color, Game, GameBoard, Piece, PieceKind, MAX_FILES, RANKS};
 use serde::de::{SeqAccess, Visitor};
 use serde::{de, Deserialize, Deserializer, Serialize};
 use std::fmt::{Display, Formatter};
@@ -7,7 +9,7 @@ use std::hash::Hasher;
 use std::str::FromStr;
 use std::{cmp::min, collections::HashSet, hash::Hash};
 
-#[derive(Eq, Clone, Copy, Serialize)]
+#[derive(Debug, Eq, Clone, Copy, Serialize)]
 pub struct Position {
     /// The rank (row) of the position on the chess board. Starting from 0.
     pub rank: usize,
@@ -31,10 +33,10 @@ impl Hash for Position {
 
 impl Position {
     fn validate(rank: usize, file: usize) {
-        if rank >= 8 {
+        if rank >= RANKS {
             panic!("invalid rank: {}", rank);
         }
-        if file >= 8 {
+        if file >= MAX_FILES {
             panic!("invalid file: {}", file);
         }
     }
@@ -74,16 +76,21 @@ impl FromStr for Position {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // Convert position string (e.g., "B2") to x and y indices
-        let col: usize = s.chars().next().unwrap().to_ascii_lowercase() as usize;
+        let mut chars = s.chars();
+        let (Some(col), Some(row), None) = (chars.next(), chars.next(), chars.next()) else {
+            return Err(PositionParseErr);
+        };
 
-        if col > ('h' as usize) {
+        // The j-file is the last on the widest board (see [MAX_FILES]).
+        let col = col.to_ascii_lowercase();
+        if !('a'..='j').contains(&col) {
             return Err(PositionParseErr);
         }
 
-        let col_value = col - 'a' as usize; // 0-indexed
-        let row: usize = s.chars().nth(1).unwrap().to_digit(10).unwrap() as usize; // 0-indexed
+        let col_value = col as usize - 'a' as usize; // 0-indexed
+        let row = row.to_digit(10).ok_or(PositionParseErr)? as usize;
 
-        if row > 8 {
+        if !(1..=8).contains(&row) {
             return Err(PositionParseErr);
         }
 
@@ -96,6 +103,18 @@ impl FromStr for Position {
     }
 }
 
+/// A move in a game's history.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Move {
+    /// The piece at the first position moves to the second (capturing any piece there, or
+    /// castling if the king moves onto its own rook).
+    Normal(Position, Position),
+
+    /// A piece of the specified kind is dropped from the player's pocket onto an empty square
+    /// (in Crazyhouse).
+    Drop(PieceKind, Position),
+}
+
 impl<'de> Deserialize<'de> for Position {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -107,7 +126,7 @@ impl<'de> Deserialize<'de> for Position {
             type Value = Position;
 
             fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("a tuple with two digits from 0 to 7: (rank, file)")
+                formatter.write_str("a tuple of two numbers: (rank, file)")
             }
 
             fn visit_seq<S>(self, mut seq: S) -> Result<Self::Value, S::Error>
@@ -133,8 +152,16 @@ impl<'de> Deserialize<'de> for Position {
 
 impl Piece {
     pub fn get_valid_moves(&self, game: &Game, current_position: &Position) -> HashSet<Position> {
-        let board = &game.board.lock().unwrap();
+        self.get_valid_moves_on_board(&game.board.lock().unwrap(), current_position)
+    }
 
+    /// Get the valid moves for the piece at the specified position on a board that does not
+    /// belong to a [Game] (e.g., a position being searched by an engine).
+    pub fn get_valid_moves_on_board(
+        &self,
+        board: &GameBoard,
+        current_position: &Position,
+    ) -> HashSet<Position> {
         match self.kind {
             King => self.explore_king(current_position, board),
             Queen => self.explore_queen(current_position, board),
@@ -142,10 +169,15 @@ impl Piece {
             Bishop => self.look_diagonal(current_position, board),
             Knight => self.explore_knight(current_position, board),
             Pawn => self.explore_pawn(current_position, board),
+            Archbishop => self.explore_archbishop(current_position, board),
+            Chancellor => self.explore_chancellor(current_position, board),
+            Amazon => self.explore_amazon(current_position, board),
         }
     }
 
-    fn check_position(
+    /// Add the square at the specified offset from `current_position` to `moves`, if it is on the
+    /// board and not occupied by a piece of the specified color (i.e., a single step or leap).
+    pub fn check_position(
         moves: &mut HashSet<Position>,
         board: &GameBoard,
         color: Color,
@@ -158,11 +190,11 @@ impl Piece {
         let new_rank = (rank as isize) + rank_delta;
         let new_file = (file as isize) + file_delta;
 
-        if !(0..8).contains(&new_rank) {
+        if !(0..RANKS as isize).contains(&new_rank) {
             return;
         }
 
-        if !(0..8).contains(&new_file) {
+        if !(0..board.files() as isize).contains(&new_file) {
             return;
         }
 
@@ -175,7 +207,9 @@ impl Piece {
         }
     }
 
-    fn check_position_for_pawn_take(
+    /// Add the square at the specified offset from `current_position` to `moves`, if it is
+    /// occupied by an opponent's piece (i.e., a capture that is not possible onto an empty square).
+    pub fn check_position_for_pawn_take(
         moves: &mut HashSet<Position>,
         board: &GameBoard,
         color: Color,
@@ -188,11 +222,11 @@ impl Piece {
         let new_rank = (rank as isize) + rank_delta;
         let new_file = (file as isize) + file_delta;
 
-        if !(0..8).contains(&new_rank) {
+        if !(0..RANKS as isize).contains(&new_rank) {
             return;
         }
 
-        if !(0..8).contains(&new_file) {
+        if !(0..board.files() as isize).contains(&new_file) {
             return;
         }
 
@@ -206,7 +240,12 @@ impl Piece {
         }
     }
 
-    fn explore_king(&self, current_position: &Position, board: &GameBoard) -> HashSet<Position> {
+    /// Get the squares a king can move to from the specified position (one step in any direction).
+    pub fn explore_king(
+        &self,
+        current_position: &Position,
+        board: &GameBoard,
+    ) -> HashSet<Position> {
         let mut moves: HashSet<Position> = HashSet::new();
 
         Self::check_position(&mut moves, board, self.color, current_position, -1, -1);
@@ -222,20 +261,37 @@ impl Piece {
         moves
     }
 
-    fn explore_queen(&self, current_position: &Position, board: &GameBoard) -> HashSet<Position> {
+    /// Get the squares a queen can move to from the specified position (any distance along ranks,
+    /// files and diagonals).
+    pub fn explore_queen(
+        &self,
+        current_position: &Position,
+        board: &GameBoard,
+    ) -> HashSet<Position> {
         let mut moves = self.look_sideways(current_position, board);
         moves.extend(self.look_up_and_down(current_position, board));
         moves.extend(self.look_diagonal(current_position, board));
         moves
     }
 
-    fn explore_rook(&self, current_position: &Position, board: &GameBoard) -> HashSet<Position> {
+    /// Get the squares a rook can move to from the specified position (any distance along ranks
+    /// and files).
+    pub fn explore_rook(
+        &self,
+        current_position: &Position,
+        board: &GameBoard,
+    ) -> HashSet<Position> {
         let mut moves = self.look_sideways(current_position, board);
         moves.extend(self.look_up_and_down(current_position, board));
         moves
     }
 
-    fn explore_knight(&self, current_position: &Position, board: &GameBoard) -> HashSet<Position> {
+    /// Get the squares a knight can move to from the specified position.
+    pub fn explore_knight(
+        &self,
+        current_position: &Position,
+        board: &GameBoard,
+    ) -> HashSet<Position> {
         let mut moves: HashSet<Position> = HashSet::new();
 
         Self::check_position(&mut moves, board, self.color, current_position, -2, -1);
@@ -247,12 +303,52 @@ impl Piece {
         Self::check_position(&mut moves, board, self.color, current_position, 1, -2);
         Self::check_position(&mut moves, board, self.color, current_position, 1, 2);
 
-        // TODO: Castling???
+        moves
+    }
 
+    /// Get the squares an archbishop can move to from the specified position (as a bishop or a
+    /// knight).
+    pub fn explore_archbishop(
+        &self,
+        current_position: &Position,
+        board: &GameBoard,
+    ) -> HashSet<Position> {
+        let mut moves = self.look_diagonal(current_position, board);
+        moves.extend(self.explore_knight(current_position, board));
+        moves
+    }
+
+    /// Get the squares a chancellor can move to from the specified position (as a rook or a
+    /// knight).
+    pub fn explore_chancellor(
+        &self,
+        current_position: &Position,
+        board: &GameBoard,
+    ) -> HashSet<Position> {
+        let mut moves = self.explore_rook(current_position, board);
+        moves.extend(self.explore_knight(current_position, board));
+        moves
+    }
+
+    /// Get the squares an amazon can move to from the specified position (as a queen or a
+    /// knight).
+    pub fn explore_amazon(
+        &self,
+        current_position: &Position,
+        board: &GameBoard,
+    ) -> HashSet<Position> {
+        let mut moves = self.explore_queen(current_position, board);
+        moves.extend(self.explore_knight(current_position, board));
         moves
     }
 
-    fn explore_pawn(&self, current_position: &Position, board: &GameBoard) -> HashSet<Position> {
+    /// Get the squares a pawn can move to from the specified position: forward onto an empty
+    /// square (or two on its first move), or diagonally forward to capture.
+    pub fn explore_pawn(
+        &self,
+        current_position: &Position,
+        board: &GameBoard,
+    ) -> HashSet<Position> {
         let _starting_rank: usize = if self.color == Color::White { 6 } else { 1 };
         let direction: isize = if self.color == Color::White { -1 } else { 1 };
 
@@ -293,16 +389,15 @@ impl Piece {
             -1,
         );
 
-        // Optional double move for first move
-        if self.move_count == 0 {
-            Self::check_position(
-                &mut moves,
-                board,
-                self.color,
-                current_position,
-                direction * 2,
-                0,
-            );
+        // Optional double move for first move, through and onto empty squares
+        if self.move_count == 0 && moves.contains(&ahead) {
+            let double = Position {
+                rank: (current_position.rank as isize + direction * 2) as usize,
+                file: current_position.file,
+            };
+            if double.rank < 8 && Game::get_piece(board, &double).is_none() {
+                moves.insert(double);
+            }
         }
 
         //TODO: en passant???
@@ -342,8 +437,8 @@ impl Piece {
                 color,
                 board,
             );
-            if valid_move.is_some() {
-                valid_moves.insert(valid_move.unwrap());
+            if let Some(valid_move) = valid_move {
+                valid_moves.insert(valid_move);
             }
             if break_out {
                 break;
@@ -360,8 +455,8 @@ impl Piece {
         for rank in ranks {
             let (break_out, valid_move) =
                 Piece::explore_pos_and_break(&Position { rank, file }, color, board);
-            if valid_move.is_some() {
-                valid_moves.insert(valid_move.unwrap());
+            if let Some(valid_move) = valid_move {
+                valid_moves.insert(valid_move);
             }
             if break_out {
                 break;
@@ -370,7 +465,13 @@ impl Piece {
         valid_moves
     }
 
-    fn look_sideways(&self, current_position: &Position, board: &GameBoard) -> HashSet<Position> {
+    /// Get the squares reachable from the specified position along its rank, up to and including
+    /// the first piece in each direction (if it belongs to the opponent).
+    pub fn look_sideways(
+        &self,
+        current_position: &Position,
+        board: &GameBoard,
+    ) -> HashSet<Position> {
         let mut valid_moves: HashSet<Position> = Piece::explore_rank(
             current_position.rank,
             (0..current_position.file).rev(),
@@ -379,14 +480,16 @@ impl Piece {
         );
         valid_moves.extend(Piece::explore_rank(
             current_position.rank,
-            current_position.file + 1..8,
+            current_position.file + 1..board.files(),
             self.color,
             board,
         ));
         valid_moves
     }
 
-    fn look_up_and_down(
+    /// Get the squares reachable from the specified position along its file, up to and including
+    /// the first piece in each direction (if it belongs to the opponent).
+    pub fn look_up_and_down(
         &self,
         current_position: &Position,
         board: &GameBoard,
@@ -408,8 +511,15 @@ impl Piece {
         valid_moves
     }
 
-    fn look_diagonal(&self, current_position: &Position, board: &GameBoard) -> HashSet<Position> {
+    /// Get the squares reachable from the specified position along its diagonals, up to and
+    /// including the first piece in each direction (if it belongs to the opponent).
+    pub fn look_diagonal(
+        &self,
+        current_position: &Position,
+        board: &GameBoard,
+    ) -> HashSet<Position> {
         let mut valid_moves: HashSet<Position> = HashSet::new();
+        let last_file = board.files() - 1;
 
         // Explore to top left
         for dev in 1..min(current_position.file, current_position.rank) + 1 {
@@ -430,7 +540,7 @@ impl Piece {
         }
 
         // Explore to bottom left
-        for dev in 0..min(current_position.file, 8 - current_position.rank) {
+        for dev in 1..min(current_position.file, 7 - current_position.rank) + 1 {
             let (break_out, valid_move) = Piece::explore_pos_and_break(
                 &Position {
                     rank: current_position.rank + dev,
@@ -448,7 +558,7 @@ impl Piece {
         }
 
         // Explore to top right
-        for dev in 1..min(8 - current_position.file, current_position.rank) {
+        for dev in 1..min(last_file - current_position.file, current_position.rank) + 1 {
             let (break_out, valid_move) = Piece::explore_pos_and_break(
                 &Position {
                     rank: current_position.rank - dev,
@@ -457,8 +567,8 @@ impl Piece {
                 self.color,
                 board,
             );
-            if valid_move.is_some() {
-                valid_moves.insert(valid_move.unwrap());
+            if let Some(valid_move) = valid_move {
+                valid_moves.insert(valid_move);
             }
             if break_out {
                 break;
@@ -466,7 +576,7 @@ impl Piece {
         }
 
         // Explore to bottom right
-        for dev in 1..min(8 - current_position.file, 8 - current_position.rank) {
+        for dev in 1..min(last_file - current_position.file, 7 - current_position.rank) + 1 {
             let (break_out, valid_move) = Piece::explore_pos_and_break(
                 &Position {
                     rank: current_position.rank + dev,
@@ -475,8 +585,8 @@ impl Piece {
                 self.color,
                 board,
             );
-            if valid_move.is_some() {
-                valid_moves.insert(valid_move.unwrap());
+            if let Some(valid_move) = valid_move {
+                valid_moves.insert(valid_move);
             }
             if break_out {
                 break;
@@ -489,14 +599,16 @@ impl Piece {
 
 #[cfg(test)]
 mod test {
+    use crate::fen::Fen;
     use crate::game::PieceKind::Bishop;
     use crate::game::{Color, Game, Piece};
     use crate::moves::Position;
+    use std::collections::HashSet;
     use std::str::FromStr;
 
     #[test]
     fn bishop_moves_test() {
-        let game = Game::new();
+        let mut game = Game::new();
 
         let bishop_position_original = Position { rank: 7, file: 2 };
         let bishop = game
@@ -504,12 +616,8 @@ mod test {
             .unwrap();
 
         let bishop_position = Position::from_str("C2").unwrap();
-        {
-            let mut board = game.board.lock().unwrap();
-            board[bishop_position_original.rank][bishop_position_original.file] = None;
-
-            board[bishop_position.rank][bishop_position.file] = Some(bishop);
-        }
+        game.set_piece(&bishop_position_original, None).unwrap();
+        game.set_piece(&bishop_position, Some(bishop)).unwrap();
 
         // output the board
 
@@ -536,16 +644,14 @@ mod test {
 
     #[test]
     fn king_moves_test_2() {
-        let game = Game::new();
+        let mut game = Game::new();
 
         let position = Position { rank: 0, file: 4 };
         let king = game.get_piece_by_position(&position).unwrap();
 
-        {
-            let mut board = game.board.lock().unwrap();
-            board[position.rank][position.file] = None;
-            board[position.rank + 3][position.file] = Some(king);
-        }
+        game.set_piece(&position, None).unwrap();
+        game.set_piece(&Position::new(position.rank + 3, position.file), Some(king))
+            .unwrap();
 
         let moves = king.get_valid_moves(&game, &Position { rank: 3, file: 4 });
         assert_eq!(moves.len(), 8);
@@ -569,4 +675,37 @@ mod test {
         let moves = pawn.get_valid_moves(&game, &new_position);
         assert_eq!(moves.len(), 1);
     }
+
+    #[test]
+    fn bishop_moves_reach_edges_test() {
+        let board = Fen::from_str("8/8/8/8/3B4/8/8/8 w - - 0 1").unwrap().board;
+        let position = Position::from_str("D4").unwrap();
+        let bishop = board[position.rank][position.file].unwrap();
+
+        // The bishop reaches the edge of the board in every direction (including down and to
+        // the left), but does not stay where it is.
+        let moves = bishop.get_valid_moves_on_board(&board, &position);
+        assert_eq!(moves.len(), 13);
+        for square in ["A1", "A7", "G1", "H8"] {
+            assert!(moves.contains(&Position::from_str(square).unwrap()));
+        }
+        assert!(!moves.contains(&position));
+    }
+
+    #[test]
+    fn pawn_double_step_test() {
+        let moves = |fen: &str| {
+            let board = Fen::from_str(fen).unwrap().board;
+            let position = Position::from_str("E2").unwrap();
+            let pawn = board[position.rank][position.file].unwrap();
+            pawn.get_valid_moves_on_board(&board, &position)
+        };
+
+        // The pawn can neither jump over a piece nor capture one by moving two squares ahead.
+        assert_eq!(moves("8/8/8/8/8/4n3/4P3/8 w - - 0 1"), HashSet::new());
+        assert_eq!(
+            moves("8/8/8/8/4n3/8/4P3/8 w - - 0 1"),
+            HashSet::from([Position::from_str("E3").unwrap()])
+        );
+    }
 }
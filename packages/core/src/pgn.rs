@@ -0,0 +1,817 @@
+use crate::analysis::{Analysis, ROOT_NODE};
+use crate::annotation::{Annotation, Nag};
+use crate::error::PgnError;
+use crate::fen::Fen;
+use crate::game::Color::White;
+use crate::game::PieceKind::Pawn;
+use crate::game::{Game, PieceKind, Variant};
+use crate::moves::{Move, Position};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// The maximum length of a line of PGN movetext, as recommended by the PGN export format.
+const MAX_LINE_LENGTH: usize = 79;
+
+/// Get the Standard Algebraic Notation (SAN) for moving the piece at `position` to
+/// `new_position` in the specified game (as it stands before the move).
+pub fn to_san(game: &Game, position: &Position, new_position: &Position) -> String {
+    let mut san = to_san_without_suffix(game, position, new_position);
+
+    let color = game.get_piece_by_position(position).unwrap().color;
+    let after = game.clone();
+    if after.move_piece_at_position(position, new_position).is_ok()
+        && after.is_player_in_check(color.opponent())
+    {
+        san.push('+');
+    }
+
+    san
+}
+
+/// Get the SAN for a move from a game's history, in the specified game (as it stands before the
+/// move).
+pub fn move_to_san(game: &Game, mv: &Move) -> String {
+    match mv {
+        Move::Normal(position, new_position) => to_san(game, position, new_position),
+        Move::Drop(kind, position) => drop_to_san(game, *kind, position),
+    }
+}
+
+/// Get the SAN for dropping a piece of the specified kind onto `position` in the specified game
+/// (e.g., `N@f3` in Crazyhouse).
+pub fn drop_to_san(game: &Game, kind: PieceKind, position: &Position) -> String {
+    let mut san = format!("{}@{}", kind.char(), position.to_string().to_lowercase());
+
+    let color = game.get_current_move();
+    let after = game.clone();
+    if after.drop_piece(kind, position).is_ok() && after.is_player_in_check(color.opponent()) {
+        san.push('+');
+    }
+
+    san
+}
+
+/// Get the SAN for a move without the check suffix.
+fn to_san_without_suffix(game: &Game, position: &Position, new_position: &Position) -> String {
+    let piece = game
+        .get_piece_by_position(position)
+        .expect("there should be a piece at the position being moved from");
+
+    let board = *game.board.lock().unwrap();
+    if let Some(rook) = Game::get_castling_rook(&board, position, new_position) {
+        let san = if rook.file > position.file {
+            "O-O"
+        } else {
+            "O-O-O"
+        };
+        return san.to_string();
+    }
+
+    let is_capture = game.get_piece_by_position(new_position).is_some();
+    let destination = new_position.to_string().to_lowercase();
+
+    let mut san = String::new();
+    if piece.kind == Pawn {
+        if is_capture {
+            san.push(file_char(position.file));
+            san.push('x');
+        }
+        san.push_str(&destination);
+        return san;
+    }
+
+    san.push(piece.kind.char());
+
+    // Find any other pieces of the same kind that could also reach the destination.
+    let mut ambiguous_files = false;
+    let mut ambiguous_ranks = false;
+    let mut is_ambiguous = false;
+    let files = game.board.lock().unwrap().files();
+    for rank in 0..8 {
+        for file in 0..files {
+            let other_position = Position { rank, file };
+            if other_position == *position {
+                continue;
+            }
+
+            let other = game.get_piece_by_position(&other_position);
+            if let Some(other) = other {
+                if other.kind != piece.kind || other.color != piece.color {
+                    continue;
+                }
+
+                if other
+                    .get_valid_moves(game, &other_position)
+                    .contains(new_position)
+                {
+                    is_ambiguous = true;
+                    ambiguous_files |= file == position.file;
+                    ambiguous_ranks |= rank == position.rank;
+                }
+            }
+        }
+    }
+
+    if is_ambiguous {
+        if !ambiguous_files {
+            san.push(file_char(position.file));
+        } else if !ambiguous_ranks {
+            san.push(rank_char(position.rank));
+        } else {
+            san.push(file_char(position.file));
+            san.push(rank_char(position.rank));
+        }
+    }
+
+    if is_capture {
+        san.push('x');
+    }
+    san.push_str(&destination);
+    san
+}
+
+/// Find the move described by the specified SAN in the game (as it stands before the move).
+/// Any check, mate or annotation suffixes are ignored. Returns [None] if no move (or more than
+/// one move) matches.
+pub fn from_san(game: &Game, san: &str) -> Option<(Position, Position)> {
+    let san = san.trim_end_matches(['+', '#', '!', '?']);
+    let color = game.get_current_move();
+
+    let castling = match san {
+        "O-O" | "0-0" => Some(true),
+        "O-O-O" | "0-0-0" => Some(false),
+        _ => None,
+    };
+    if let Some(kingside) = castling {
+        let board = *game.board.lock().unwrap();
+        return Game::get_castling_moves_on_board(&board, color)
+            .into_iter()
+            .find(|(king, rook)| (rook.file > king.file) == kingside);
+    }
+
+    let mut found = None;
+    let files = game.board.lock().unwrap().files();
+    for rank in 0..8 {
+        for file in 0..files {
+            let position = Position { rank, file };
+            let piece = match game.get_piece_by_position(&position) {
+                Some(piece) if piece.color == color => piece,
+                _ => continue,
+            };
+
+            for new_position in piece.get_valid_moves(game, &position) {
+                if to_san_without_suffix(game, &position, &new_position) == san {
+                    if found.is_some() {
+                        return None;
+                    }
+                    found = Some((position, new_position));
+                }
+            }
+        }
+    }
+
+    found
+}
+
+/// Export a [Game] as PGN, including any annotations on its moves.
+pub fn write_game(game: &Game) -> String {
+    write_game_with_tags(game, &[])
+}
+
+/// Export a [Game] as PGN with additional tags (e.g., the players' names), which take precedence
+/// over the tags derived from the game.
+pub fn write_game_with_tags(game: &Game, extra_tags: &[(&str, &str)]) -> String {
+    // Replay the moves from the starting position to write them in SAN. If Black moved first,
+    // the first move number is written as e.g., `1...`.
+    let replay = Game::new_with_options(None, game.get_options().clone());
+    let moves = game.get_moves();
+
+    // Movetext cannot express a player making several moves in a row, as one receiving move odds
+    // does, so the game is written from the position before the last of them.
+    let skipped = game.get_extra_moves().min(moves.len());
+    for mv in &moves[..skipped] {
+        replay.play(mv).expect("moves in the game should be legal");
+    }
+    let starting_fen = if skipped > 0 {
+        Some(Fen::from_game(&replay))
+    } else {
+        game.get_options().starting_fen.clone()
+    };
+
+    let mut tokens = Vec::new();
+    if let Some(annotation) = game.get_annotation(skipped) {
+        write_annotation(&mut tokens, annotation);
+    }
+
+    let mut force_move_number = true;
+    for (index, mv) in moves.iter().enumerate().skip(skipped) {
+        write_move_number(&mut tokens, &replay, force_move_number);
+        tokens.push(move_to_san(&replay, mv));
+        replay.play(mv).expect("moves in the game should be legal");
+
+        let annotation = game.get_annotation(index + 1);
+        if let Some(annotation) = annotation {
+            write_annotation(&mut tokens, annotation);
+        }
+        force_move_number = annotation.is_some_and(|a| a.comment.is_some());
+    }
+
+    let result = game.get_result().map_or("*", |result| result.to_pgn());
+    tokens.push(result.to_string());
+
+    let date = game.get_created_at().format("%Y.%m.%d").to_string();
+
+    let mut tags = vec![("Date", date), ("Result", result.to_string())];
+    let variant = game.get_options().variant;
+    if variant != Variant::Standard {
+        tags.push(("Variant", variant.name().to_string()));
+    }
+    if let Some(fen) = &starting_fen {
+        tags.push(("SetUp", "1".to_string()));
+        tags.push(("FEN", fen.to_string()));
+    }
+    let mut tags: Vec<(&str, &str)> = tags
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .filter(|(name, _)| !extra_tags.iter().any(|(extra, _)| extra == name))
+        .collect();
+    tags.extend_from_slice(extra_tags);
+
+    let mut pgn = String::new();
+    write_tags(&mut pgn, &tags);
+    pgn.push('\n');
+    write_movetext(&mut pgn, &tokens);
+    pgn
+}
+
+/// Export an [Analysis] as PGN, with alternative variations nested in parentheses.
+pub fn write_analysis(analysis: &Analysis) -> String {
+    let mut tokens = Vec::new();
+    write_annotation(
+        &mut tokens,
+        &analysis.get_node(ROOT_NODE).unwrap().annotation,
+    );
+    write_continuation(analysis, ROOT_NODE, &Game::new(), &mut tokens, false);
+    tokens.push("*".to_string());
+
+    let mut pgn = String::new();
+    write_tags(&mut pgn, &[("Result", "*")]);
+    pgn.push('\n');
+    write_movetext(&mut pgn, &tokens);
+    pgn
+}
+
+/// Import a PGN game as an [Analysis], including any variations and annotations. The cursor is
+/// left at the starting position.
+pub fn read_analysis(pgn: &str, id: Option<String>) -> Result<Analysis, PgnError> {
+    let mut analysis = Analysis::new_with_id(id);
+
+    // The nodes to return to at the end of each (nested) variation.
+    let mut variations = Vec::new();
+
+    for token in tokenize(pgn)? {
+        let cursor = analysis.get_cursor();
+        match token {
+            Token::Tag(name, value) => {
+                if name == "FEN" || (name == "SetUp" && value == "1") {
+                    return Err(PgnError::UnsupportedError(
+                        "custom starting positions".to_string(),
+                    ));
+                }
+            }
+            Token::Move(san) => {
+                let (position, new_position) = from_san(&analysis.get_current_game(), &san)
+                    .ok_or_else(|| PgnError::InvalidMoveError(san.clone()))?;
+                analysis
+                    .play_move(&position, &new_position)
+                    .map_err(|_| PgnError::InvalidMoveError(san))?;
+            }
+            Token::Comment(comment) => {
+                let mut annotation = analysis.get_node(cursor).unwrap().annotation.clone();
+                annotation
+                    .add_pgn_comment(&comment)
+                    .map_err(|_| PgnError::InvalidSyntaxError(comment.clone()))?;
+                // Comment text is collapsed onto one line, so it can always be written back out.
+                analysis.set_annotation(cursor, annotation).unwrap();
+            }
+            Token::Nag(nag) => {
+                let mut annotation = analysis.get_node(cursor).unwrap().annotation.clone();
+                annotation.nags.push(nag);
+                analysis.set_annotation(cursor, annotation).unwrap();
+            }
+            Token::StartVariation => {
+                // A variation is an alternative to the move that was just played.
+                let parent = analysis
+                    .get_node(cursor)
+                    .unwrap()
+                    .parent
+                    .ok_or_else(|| PgnError::InvalidSyntaxError("(".to_string()))?;
+                variations.push(cursor);
+                analysis.set_cursor(parent).unwrap();
+            }
+            Token::EndVariation => {
+                let cursor = variations
+                    .pop()
+                    .ok_or_else(|| PgnError::InvalidSyntaxError(")".to_string()))?;
+                analysis.set_cursor(cursor).unwrap();
+            }
+            Token::Result(_) => break,
+        }
+    }
+
+    if !variations.is_empty() {
+        return Err(PgnError::InvalidSyntaxError("(".to_string()));
+    }
+
+    analysis.set_cursor(ROOT_NODE).unwrap();
+    Ok(analysis)
+}
+
+/// Import a PGN game as a [Game], including any annotations on its moves. Only the main line is
+/// imported; any variations are ignored.
+pub fn read_game(pgn: &str, id: Option<String>) -> Result<Game, PgnError> {
+    let analysis = read_analysis(pgn, None)?;
+    let mut game = Game::new_with_id(id);
+
+    let mut node = analysis.get_node(ROOT_NODE).unwrap();
+    game.set_annotation(0, node.annotation.clone()).unwrap();
+
+    while let Some(&child) = node.children.first() {
+        node = analysis.get_node(child).unwrap();
+
+        let (position, new_position) = node.mv.unwrap();
+        game.move_piece_at_position(&position, &new_position)
+            .expect("moves in the analysis tree should be legal");
+        game.set_annotation(game.get_move_count(), node.annotation.clone())
+            .unwrap();
+    }
+
+    Ok(game)
+}
+
+/// Write the Seven Tag Roster (using placeholder values for any tag not in `tags`), followed by
+/// any additional tags.
+fn write_tags(pgn: &mut String, tags: &[(&str, &str)]) {
+    const SEVEN_TAG_ROSTER: [&str; 7] =
+        ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+    let value_of = |name: &str| tags.iter().find(|(tag, _)| *tag == name).map(|t| t.1);
+
+    for name in SEVEN_TAG_ROSTER {
+        let value = value_of(name).unwrap_or(match name {
+            "Date" => "????.??.??",
+            "Result" => "*",
+            _ => "?",
+        });
+        writeln!(pgn, "[{} \"{}\"]", name, escape(value)).unwrap();
+    }
+
+    for (name, value) in tags {
+        if !SEVEN_TAG_ROSTER.contains(name) {
+            writeln!(pgn, "[{} \"{}\"]", name, escape(value)).unwrap();
+        }
+    }
+}
+
+/// Write the continuation from the specified node: its main line move, then each alternative
+/// variation in parentheses, then the rest of the main line.
+fn write_continuation(
+    analysis: &Analysis,
+    id: usize,
+    game: &Game,
+    tokens: &mut Vec<String>,
+    force_move_number: bool,
+) {
+    let node = analysis.get_node(id).unwrap();
+    let Some(&main_line) = node.children.first() else {
+        return;
+    };
+
+    let after_main_line = write_node(analysis, main_line, game, tokens, force_move_number);
+
+    let variations = &node.children[1..];
+    for &variation in variations {
+        tokens.push("(".to_string());
+        let after_variation = write_node(analysis, variation, game, tokens, true);
+        write_continuation(
+            analysis,
+            variation,
+            &after_variation,
+            tokens,
+            has_comment(analysis, variation),
+        );
+        tokens.push(")".to_string());
+    }
+
+    write_continuation(
+        analysis,
+        main_line,
+        &after_main_line,
+        tokens,
+        !variations.is_empty() || has_comment(analysis, main_line),
+    );
+}
+
+/// Write the move (and annotation) for the specified node, played from `game`, and return the
+/// resulting game.
+fn write_node(
+    analysis: &Analysis,
+    id: usize,
+    game: &Game,
+    tokens: &mut Vec<String>,
+    force_move_number: bool,
+) -> Game {
+    let node = analysis.get_node(id).unwrap();
+    let (position, new_position) = node.mv.unwrap();
+
+    write_move_number(tokens, game, force_move_number);
+    tokens.push(to_san(game, &position, &new_position));
+    write_annotation(tokens, &node.annotation);
+
+    let after = game.clone();
+    after
+        .move_piece_at_position(&position, &new_position)
+        .expect("moves in the analysis tree should be legal");
+    after
+}
+
+fn has_comment(analysis: &Analysis, id: usize) -> bool {
+    analysis
+        .get_node(id)
+        .unwrap()
+        .annotation
+        .to_pgn_comment()
+        .is_some()
+}
+
+/// Write the move number for the next move in `game`. Black's moves are only numbered when
+/// `force_move_number` is set (e.g., at the start of a variation or after a comment).
+fn write_move_number(tokens: &mut Vec<String>, game: &Game, force_move_number: bool) {
+    let number = game.get_fullmove_number();
+    if game.get_current_move() == White {
+        tokens.push(format!("{}.", number));
+    } else if force_move_number {
+        tokens.push(format!("{}...", number));
+    }
+}
+
+/// Write the NAGs and comment (if any) for an annotation. A comment containing a closing brace is
+/// written as a rest-of-line comment instead.
+fn write_annotation(tokens: &mut Vec<String>, annotation: &Annotation) {
+    for nag in &annotation.nags {
+        tokens.push(format!("${}", nag.0));
+    }
+
+    if let Some(comment) = annotation.to_pgn_comment() {
+        if comment.contains('}') {
+            tokens.push(format!(";{}", comment));
+        } else {
+            tokens.push(format!("{{{}}}", comment));
+        }
+    }
+}
+
+/// Join movetext tokens with spaces (keeping parentheses tight against their contents),
+/// wrapping lines to [MAX_LINE_LENGTH] and after every rest-of-line comment.
+fn write_movetext(pgn: &mut String, tokens: &[String]) {
+    let mut line = String::new();
+    let mut previous: Option<&str> = None;
+
+    for token in tokens {
+        let separator = match previous {
+            None | Some("(") => "",
+            _ if token == ")" => "",
+            _ => " ",
+        };
+
+        if !line.is_empty() && line.len() + separator.len() + token.len() > MAX_LINE_LENGTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        } else {
+            line.push_str(separator);
+        }
+
+        line.push_str(token);
+        previous = Some(token);
+
+        if token.starts_with(';') {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+            previous = None;
+        }
+    }
+
+    pgn.push_str(&line);
+    pgn.push('\n');
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Move(String),
+    Comment(String),
+    Nag(Nag),
+    StartVariation,
+    EndVariation,
+    Result(String),
+}
+
+/// Split PGN text into tag pairs and movetext tokens. Move numbers are discarded and move suffix
+/// glyphs (e.g., `e4!?`) are split into separate [Token::Nag]s.
+fn tokenize(pgn: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = pgn.chars().peekable();
+    let mut at_line_start = true;
+
+    while let Some(c) = chars.next() {
+        let was_at_line_start = at_line_start;
+        at_line_start = c == '\n';
+
+        match c {
+            c if c.is_whitespace() => {}
+            // Escaped lines are ignored.
+            '%' if was_at_line_start => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        at_line_start = true;
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|c| *c != '\n').collect();
+                at_line_start = true;
+                tokens.push(Token::Comment(comment));
+            }
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => comment.push(c),
+                        None => return Err(PgnError::InvalidSyntaxError(format!("{{{}", comment))),
+                    }
+                }
+                tokens.push(Token::Comment(comment));
+            }
+            '[' => {
+                let tag: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                tokens.push(parse_tag(&tag)?);
+            }
+            '(' => tokens.push(Token::StartVariation),
+            ')' => tokens.push(Token::EndVariation),
+            _ => {
+                let mut symbol = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "(){}[];".contains(c) {
+                        break;
+                    }
+                    symbol.push(c);
+                    chars.next();
+                }
+                tokens.extend(parse_symbol(&symbol)?);
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parse the contents of a tag pair (e.g., `Event "Club Championship"`).
+fn parse_tag(tag: &str) -> Result<Token, PgnError> {
+    let invalid = || PgnError::InvalidSyntaxError(format!("[{}]", tag));
+
+    let (name, value) = tag
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(invalid)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+
+    let value = value.replace("\\\"", "\"").replace("\\\\", "\\");
+    Ok(Token::Tag(name.to_string(), value))
+}
+
+/// Parse a movetext symbol: a NAG, a game termination marker, or a (possibly numbered) move.
+fn parse_symbol(symbol: &str) -> Result<Vec<Token>, PgnError> {
+    if symbol.starts_with('$') {
+        let nag =
+            Nag::from_str(symbol).map_err(|_| PgnError::InvalidSyntaxError(symbol.to_string()))?;
+        return Ok(vec![Token::Nag(nag)]);
+    }
+
+    if ["1-0", "0-1", "1/2-1/2", "*"].contains(&symbol) {
+        return Ok(vec![Token::Result(symbol.to_string())]);
+    }
+
+    // Strip any move number (e.g., "1." or "1...").
+    let san = symbol.trim_start_matches(|c: char| c.is_ascii_digit());
+    let san = if san.len() < symbol.len() && san.starts_with('.') {
+        san.trim_start_matches('.')
+    } else {
+        symbol
+    };
+
+    let mut tokens = Vec::new();
+    let glyph_start = san.find(['!', '?']).unwrap_or(san.len());
+    let (san, glyph) = san.split_at(glyph_start);
+
+    if !san.is_empty() {
+        if san.contains('=') {
+            return Err(PgnError::UnsupportedError("promotion".to_string()));
+        }
+        tokens.push(Token::Move(san.to_string()));
+    }
+
+    if !glyph.is_empty() {
+        let nag =
+            Nag::from_str(glyph).map_err(|_| PgnError::InvalidSyntaxError(symbol.to_string()))?;
+        tokens.push(Token::Nag(nag));
+    }
+
+    Ok(tokens)
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn file_char(file: usize) -> char {
+    char::from(file as u8 + b'a')
+}
+
+fn rank_char(rank: usize) -> char {
+    char::from(b'8' - rank as u8)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::analysis::Analysis;
+    use crate::annotation::{Annotation, Nag};
+    use crate::fen::Fen;
+    use crate::game::{Game, GameOptions};
+    use crate::moves::{Move, Position};
+    use crate::pgn::{read_analysis, read_game, to_san, write_analysis, write_game};
+    use std::str::FromStr;
+
+    fn play(analysis: &mut Analysis, from: &str, to: &str) -> usize {
+        analysis
+            .play_move(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_san() {
+        let game = Game::new();
+        let san = |game: &Game, from: &str, to: &str| {
+            to_san(
+                game,
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+        };
+
+        assert_eq!(san(&game, "E2", "E4"), "e4");
+        assert_eq!(san(&game, "G1", "F3"), "Nf3");
+
+        // Move the knights so that both can reach E4.
+        let moves = [
+            ("B1", "C3"),
+            ("A7", "A6"),
+            ("G1", "F3"),
+            ("A6", "A5"),
+            ("F3", "G5"),
+            ("H7", "H6"),
+        ];
+        for (from, to) in moves {
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap();
+        }
+        assert_eq!(san(&game, "C3", "E4"), "Nce4");
+        assert_eq!(san(&game, "G5", "E4"), "Nge4");
+        assert_eq!(san(&game, "G5", "F7"), "Nxf7");
+
+        // Castling is written the same way whether the king moves onto the rook or two squares.
+        let game = read_game("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O *", None).unwrap();
+        assert_eq!(
+            game.get_moves().last(),
+            Some(&Move::Normal(
+                Position::from_str("E1").unwrap(),
+                Position::from_str("H1").unwrap()
+            ))
+        );
+        assert!(write_game(&game).ends_with("4. O-O *\n"));
+
+        let game = read_game("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5", None).unwrap();
+        assert_eq!(san(&game, "E1", "H1"), "O-O");
+        assert_eq!(san(&game, "E1", "G1"), "O-O");
+    }
+
+    #[test]
+    fn test_write_analysis() {
+        let mut analysis = Analysis::new();
+        let e4 = play(&mut analysis, "E2", "E4");
+        play(&mut analysis, "E7", "E5");
+        play(&mut analysis, "G1", "F3");
+
+        analysis.set_cursor(e4).unwrap();
+        play(&mut analysis, "C7", "C5");
+        play(&mut analysis, "G1", "F3");
+
+        let pgn = write_analysis(&analysis);
+        assert!(pgn.starts_with("[Event \"?\"]\n"));
+        assert!(pgn.ends_with("\n\n1. e4 e5 (1... c5 2. Nf3) 2. Nf3 *\n"));
+    }
+
+    #[test]
+    fn test_annotated_game_round_trip() {
+        let mut game = Game::new();
+        for (from, to) in [("E2", "E4"), ("E7", "E5"), ("G1", "F3")] {
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let mut annotation = Annotation::default();
+        annotation
+            .add_pgn_comment("[%cal Gg8f6] Best by test")
+            .unwrap();
+        annotation.nags.push(Nag(1));
+        game.set_annotation(1, annotation).unwrap();
+
+        let pgn = write_game(&game);
+        assert!(pgn.ends_with("\n\n1. e4 $1 {[%cal Gg8f6] Best by test} 1... e5 2. Nf3 *\n"));
+
+        let imported = read_game(&pgn, None).unwrap();
+        assert_eq!(imported.get_move_count(), 3);
+        assert_eq!(imported.get_annotation(1), game.get_annotation(1));
+        assert!(imported.get_annotation(2).is_none());
+        assert_eq!(write_game(&imported).lines().last(), pgn.lines().last());
+    }
+
+    #[test]
+    fn test_rest_of_line_comment_round_trip() {
+        let game = read_game("1. e4 ; Not {really} best\n1... e5 *", None).unwrap();
+        assert_eq!(
+            game.get_annotation(1).unwrap().comment.as_deref(),
+            Some("Not {really} best")
+        );
+
+        // A closing brace would end a braced comment early.
+        let pgn = write_game(&game);
+        assert!(pgn.ends_with("\n\n1. e4 ;Not {really} best\n1... e5 *\n"));
+
+        let imported = read_game(&pgn, None).unwrap();
+        assert_eq!(imported.get_annotation(1), game.get_annotation(1));
+        assert_eq!(write_game(&imported), pgn);
+    }
+
+    #[test]
+    fn test_write_game_from_position() {
+        let options = GameOptions {
+            starting_fen: Some(Fen::from_str("4k3/8/8/8/8/8/4P3/4K3 b - - 0 40").unwrap()),
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        for (from, to) in [("E8", "D7"), ("E2", "E4")] {
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let pgn = write_game(&game);
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n"));
+        assert!(pgn.ends_with("\n40... Kd7 41. e4 *\n"), "{}", pgn);
+    }
+
+    #[test]
+    fn test_read_analysis() {
+        let pgn = "[Event \"Casual\"]\n\n\
+            {Start} 1.e4 e5!? (1... c5 2. Nf3 (2. Nc3) 2... d6 $10) 2. Nf3 ; Develops\n*";
+        let analysis = read_analysis(pgn, None).unwrap();
+
+        assert_eq!(
+            write_analysis(&analysis).lines().last().unwrap(),
+            "{Start} 1. e4 e5 $5 (1... c5 2. Nf3 (2. Nc3) 2... d6 $10) 2. Nf3 {Develops} *"
+        );
+
+        assert!(read_analysis("1. e4 e4", None).is_err());
+        assert!(read_analysis("1. e4 (1. d4", None).is_err());
+        assert!(read_analysis("(1. d4)", None).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use chrono::serde::ts_milliseconds;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::f64::consts::PI;
+
+/// The rating given to new players.
+pub const DEFAULT_RATING: f64 = 1500.0;
+
+/// The rating deviation given to new players (and the maximum deviation).
+pub const DEFAULT_DEVIATION: f64 = 350.0;
+
+/// The rating volatility given to new players.
+pub const DEFAULT_VOLATILITY: f64 = 0.06;
+
+/// The factor used to convert between the Glicko and Glicko-2 scales.
+const GLICKO2_SCALE: f64 = 173.7178;
+
+/// The convergence tolerance used when computing the new volatility.
+const GLICKO2_TOLERANCE: f64 = 0.000001;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct Rating {
+    pub rating: f64,
+
+    /// The rating deviation (RD): how uncertain the rating is.
+    pub deviation: f64,
+
+    /// How erratic the player's performances are expected to be.
+    pub volatility: f64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating {
+            rating: DEFAULT_RATING,
+            deviation: DEFAULT_DEVIATION,
+            volatility: DEFAULT_VOLATILITY,
+        }
+    }
+}
+
+/// The system used to update ratings after each game.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RatingSystem {
+    /// Plain Elo, with the specified K-factor. The deviation and volatility are left unchanged.
+    Elo { k_factor: f64 },
+
+    /// Glicko-2, with the specified system constant (tau), which constrains how quickly the
+    /// volatility may change. Each game is treated as its own rating period.
+    Glicko2 { tau: f64 },
+}
+
+impl Default for RatingSystem {
+    fn default() -> Self {
+        RatingSystem::Glicko2 { tau: 0.5 }
+    }
+}
+
+impl RatingSystem {
+    /// Compute a player's new rating after a game against the specified opponent, where `score`
+    /// is 1 for a win, 0.5 for a draw and 0 for a loss.
+    pub fn update(&self, player: Rating, opponent: Rating, score: f64) -> Rating {
+        match *self {
+            RatingSystem::Elo { k_factor } => update_elo(player, opponent, score, k_factor),
+            RatingSystem::Glicko2 { tau } => update_glicko2(player, &[(opponent, score)], tau),
+        }
+    }
+}
+
+/// Get the expected score of a player against an opponent under Elo.
+pub fn expected_elo_score(rating: f64, opponent_rating: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent_rating - rating) / 400.0))
+}
+
+fn update_elo(player: Rating, opponent: Rating, score: f64, k_factor: f64) -> Rating {
+    let expected = expected_elo_score(player.rating, opponent.rating);
+    Rating {
+        rating: player.rating + k_factor * (score - expected),
+        ..player
+    }
+}
+
+/// Update a player's rating under Glicko-2 after a rating period with the specified results
+/// (each an opponent's rating and the player's score against them).
+pub fn update_glicko2(player: Rating, results: &[(Rating, f64)], tau: f64) -> Rating {
+    let mu = (player.rating - DEFAULT_RATING) / GLICKO2_SCALE;
+    let phi = player.deviation / GLICKO2_SCALE;
+    let sigma = player.volatility;
+
+    // A player who did not play only becomes less certain.
+    if results.is_empty() {
+        let deviation = (phi * phi + sigma * sigma).sqrt() * GLICKO2_SCALE;
+        return Rating {
+            deviation: deviation.min(DEFAULT_DEVIATION),
+            ..player
+        };
+    }
+
+    let g = |phi: f64| 1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt();
+    let expected = |mu_j: f64, phi_j: f64| 1.0 / (1.0 + (-g(phi_j) * (mu - mu_j)).exp());
+
+    // Estimated variance of the rating based only on game outcomes, and the estimated
+    // improvement in rating.
+    let mut variance_inverse = 0.0;
+    let mut improvement = 0.0;
+    for (opponent, score) in results {
+        let mu_j = (opponent.rating - DEFAULT_RATING) / GLICKO2_SCALE;
+        let phi_j = opponent.deviation / GLICKO2_SCALE;
+        let e = expected(mu_j, phi_j);
+
+        variance_inverse += g(phi_j).powi(2) * e * (1.0 - e);
+        improvement += g(phi_j) * (score - e);
+    }
+    let v = 1.0 / variance_inverse;
+    let delta = v * improvement;
+
+    // Compute the new volatility using the Illinois algorithm.
+    let a = (sigma * sigma).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        let denominator = phi * phi + v + ex;
+        ex * (delta * delta - phi * phi - v - ex) / (2.0 * denominator * denominator)
+            - (x - a) / (tau * tau)
+    };
+
+    let mut big_a = a;
+    let mut big_b = if delta * delta > phi * phi + v {
+        (delta * delta - phi * phi - v).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * tau) < 0.0 {
+            k += 1.0;
+        }
+        a - k * tau
+    };
+
+    let mut f_a = f(big_a);
+    let mut f_b = f(big_b);
+    while (big_b - big_a).abs() > GLICKO2_TOLERANCE {
+        let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+        let f_c = f(big_c);
+        if f_c * f_b <= 0.0 {
+            big_a = big_b;
+            f_a = f_b;
+        } else {
+            f_a /= 2.0;
+        }
+        big_b = big_c;
+        f_b = f_c;
+    }
+    let new_sigma = (big_a / 2.0).exp();
+
+    let phi_star = (phi * phi + new_sigma * new_sigma).sqrt();
+    let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+    let new_mu = mu + new_phi * new_phi * improvement;
+
+    Rating {
+        rating: new_mu * GLICKO2_SCALE + DEFAULT_RATING,
+        deviation: (new_phi * GLICKO2_SCALE).min(DEFAULT_DEVIATION),
+        volatility: new_sigma,
+    }
+}
+
+/// A player's rating after a game.
+#[derive(Clone, Debug, Serialize)]
+pub struct RatingHistoryEntry {
+    /// The ID of the game that changed the rating.
+    pub game_id: Option<String>,
+
+    pub rating: f64,
+
+    pub deviation: f64,
+
+    /// The [DateTime] the rating changed.
+    #[serde(with = "ts_milliseconds")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// A player's rating in one time control category.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CategoryRating {
+    pub current: Rating,
+
+    /// The number of rated games played in the category.
+    pub games: usize,
+
+    /// The player's rating after each rated game in the category, oldest first.
+    #[serde(skip)]
+    pub history: Vec<RatingHistoryEntry>,
+}
+
+impl CategoryRating {
+    /// Record a new rating as a result of the specified game.
+    pub fn record(&mut self, rating: Rating, game_id: Option<String>) {
+        self.current = rating;
+        self.games += 1;
+        self.history.push(RatingHistoryEntry {
+            game_id,
+            rating: rating.rating,
+            deviation: rating.deviation,
+            recorded_at: Utc::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rating::{update_glicko2, Rating, RatingSystem};
+
+    fn rating(rating: f64, deviation: f64) -> Rating {
+        Rating {
+            rating,
+            deviation,
+            ..Rating::default()
+        }
+    }
+
+    #[test]
+    fn test_glicko2() {
+        // The worked example from Glickman's "Example of the Glicko-2 system".
+        let player = rating(1500.0, 200.0);
+        let results = [
+            (rating(1400.0, 30.0), 1.0),
+            (rating(1550.0, 100.0), 0.0),
+            (rating(1700.0, 300.0), 0.0),
+        ];
+
+        let updated = update_glicko2(player, &results, 0.5);
+        assert!((updated.rating - 1464.06).abs() < 0.01, "{:?}", updated);
+        assert!((updated.deviation - 151.52).abs() < 0.01, "{:?}", updated);
+        assert!(
+            (updated.volatility - 0.05999).abs() < 0.00001,
+            "{:?}",
+            updated
+        );
+    }
+
+    #[test]
+    fn test_elo() {
+        let elo = RatingSystem::Elo { k_factor: 32.0 };
+        let updated = elo.update(Rating::default(), Rating::default(), 1.0);
+        assert_eq!(updated.rating, 1516.0);
+        assert_eq!(updated.deviation, Rating::default().deviation);
+
+        let updated = elo.update(Rating::default(), Rating::default(), 0.5);
+        assert_eq!(updated.rating, 1500.0);
+    }
+}
@@ -0,0 +1,1427 @@
+use crate::chess960;
+use crate::error::SetupError;
+use crate::fen::Fen;
+use crate::game::Color::{Black, White};
+use crate::game::PieceKind::{
+    Amazon, Archbishop, Bishop, Chancellor, King, Knight, Pawn, Queen, Rook,
+};
+use crate::game::{
+    Color, Game, GameBoard, GameOptions, GameResult, Piece, PieceKind, Termination, Variant,
+    MAX_FILES, RANKS,
+};
+use crate::moves::Position;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// The number of checks a player must give to win a game of three-check.
+pub const THREE_CHECK_LIMIT: usize = 3;
+
+/// The starting position of Horde: White has 36 pawns (and no king) against Black's usual army.
+pub const HORDE_FEN: &str =
+    "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1";
+
+/// The starting position of Capablanca Chess, on a 10x8 board with an archbishop and a chancellor
+/// for each player.
+pub const CAPABLANCA_FEN: &str =
+    "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1";
+
+/// The state of a game besides the board that the rules of some variants depend on.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VariantState {
+    /// The number of times each player has given check, in variants that count them.
+    pub checks: BTreeMap<Color, usize>,
+
+    /// The pieces each player holds in their pocket, ready to be dropped onto the board (in
+    /// Crazyhouse). The most valuable pieces come first.
+    pub pockets: BTreeMap<Color, Vec<PieceKind>>,
+}
+
+impl VariantState {
+    /// Get the number of times the specified player has given check.
+    pub fn get_checks(&self, color: Color) -> usize {
+        self.checks.get(&color).copied().unwrap_or(0)
+    }
+
+    /// Get the pieces in the specified player's pocket.
+    pub fn get_pocket(&self, color: Color) -> &[PieceKind] {
+        self.pockets.get(&color).map_or(&[], Vec::as_slice)
+    }
+
+    /// Add a piece of the specified kind to the specified player's pocket.
+    pub fn add_to_pocket(&mut self, color: Color, kind: PieceKind) {
+        let pocket = self.pockets.entry(color).or_default();
+        pocket.push(kind);
+        pocket.sort_by_key(|kind| (Reverse(kind.material_value()), kind.char()));
+    }
+
+    /// Take a piece of the specified kind from the specified player's pocket. Returns whether
+    /// they had one.
+    pub fn take_from_pocket(&mut self, color: Color, kind: PieceKind) -> bool {
+        let Some(pocket) = self.pockets.get_mut(&color) else {
+            return false;
+        };
+        match pocket.iter().position(|&k| k == kind) {
+            Some(index) => {
+                pocket.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The rules of a variant, which a [Game] delegates to. Every method has a default that follows
+/// the rules of standard chess, so a variant only overrides the rules it changes. The building
+/// blocks for these rules are the board functions on [Game] (e.g.,
+/// [Game::get_legal_moves_on_board]) and the move patterns of each piece in [crate::moves].
+pub trait Rules: Send + Sync {
+    /// Get the position a game starts from when no starting FEN is given, or [None] for the
+    /// standard starting position. The options may be updated to record how the position was
+    /// chosen (e.g., the index of a random Chess960 position).
+    fn get_starting_fen(&self, _options: &mut GameOptions) -> Option<Fen> {
+        None
+    }
+
+    /// Get the legal moves for the specified player on the specified board.
+    fn get_legal_moves(&self, board: &GameBoard, color: Color) -> Vec<(Position, Position)> {
+        let moves = Game::get_legal_moves_on_board(board, color);
+        self.filter_moves(board, color, moves)
+    }
+
+    /// Restrict or extend the moves generated by the standard rules (e.g., to make captures
+    /// compulsory).
+    fn filter_moves(
+        &self,
+        _board: &GameBoard,
+        _color: Color,
+        moves: Vec<(Position, Position)>,
+    ) -> Vec<(Position, Position)> {
+        moves
+    }
+
+    /// Check whether the specified player is in check on the specified board.
+    fn is_in_check(&self, board: &GameBoard, color: Color) -> bool {
+        Game::is_in_check_on_board(board, color)
+    }
+
+    /// Get a copy of the specified board after the piece at `position` moves to `new_position`,
+    /// with any side effects of the move on the board (e.g., the rook moving when the king
+    /// castles) or on the rest of the game's state.
+    fn apply_move(
+        &self,
+        board: &GameBoard,
+        _state: &mut VariantState,
+        position: &Position,
+        new_position: &Position,
+    ) -> GameBoard {
+        Game::apply_move_on_board(board, position, new_position)
+    }
+
+    /// Get the legal drops of pieces from the specified player's pocket, as the kind of piece and
+    /// the square it is dropped onto. Only variants with pockets have any.
+    fn get_legal_drops(
+        &self,
+        _board: &GameBoard,
+        _state: &VariantState,
+        _color: Color,
+    ) -> Vec<(PieceKind, Position)> {
+        Vec::new()
+    }
+
+    /// Get a copy of the specified board after the specified player drops a piece of the
+    /// specified kind from their pocket onto `position`, taking it out of their pocket.
+    fn apply_drop(
+        &self,
+        board: &GameBoard,
+        state: &mut VariantState,
+        color: Color,
+        kind: PieceKind,
+        position: &Position,
+    ) -> GameBoard {
+        state.take_from_pocket(color, kind);
+
+        // A dropped rook may not castle, but a pawn dropped on its starting rank may still move
+        // two squares.
+        let mut board = *board;
+        board[position.rank][position.file] = Some(Piece {
+            kind,
+            color,
+            move_count: usize::from(kind != Pawn),
+        });
+        board
+    }
+
+    /// Get the result of the game if it is over, with the specified player to move and the
+    /// specified legal moves available to them. Otherwise, returns [None].
+    fn get_outcome(
+        &self,
+        board: &GameBoard,
+        _state: &VariantState,
+        color: Color,
+        legal_moves: &[(Position, Position)],
+    ) -> Option<GameResult> {
+        get_checkmate_or_stalemate(self, board, color, legal_moves)
+    }
+
+    /// Check whether a position (e.g., one set up in the board editor) is legal: each player has
+    /// one king and no more pieces than a game can have, the castling and en passant rights are
+    /// possible, and the checks are ones a move could have given.
+    fn validate_position(&self, fen: &Fen) -> Result<(), SetupError> {
+        for color in [White, Black] {
+            validate_king(&fen.board, color)?;
+            validate_material(&fen.board, color)?;
+        }
+        validate_rights(fen)?;
+        validate_checks(self, fen)
+    }
+}
+
+/// Get the result of a game in which the specified player is to move, if they have no legal moves:
+/// a loss if they are in check (by the specified rules), otherwise a draw.
+pub fn get_checkmate_or_stalemate<R: Rules + ?Sized>(
+    rules: &R,
+    board: &GameBoard,
+    color: Color,
+    legal_moves: &[(Position, Position)],
+) -> Option<GameResult> {
+    if !legal_moves.is_empty() {
+        return None;
+    }
+
+    Some(if rules.is_in_check(board, color) {
+        GameResult {
+            winner: Some(color.opponent()),
+            termination: Termination::Checkmate,
+        }
+    } else {
+        GameResult {
+            winner: None,
+            termination: Termination::Stalemate,
+        }
+    })
+}
+
+/// Check that the specified player has exactly one king on the specified board.
+pub fn validate_king(board: &GameBoard, color: Color) -> Result<(), SetupError> {
+    let kings = board
+        .iter()
+        .flatten()
+        .flatten()
+        .filter(|piece| piece.kind == King && piece.color == color)
+        .count();
+    if kings != 1 {
+        return Err(SetupError::KingCountError);
+    }
+    Ok(())
+}
+
+/// Check that the specified player's pieces on the specified board could be left from a full set:
+/// no pawns on the first or last rank, no more pawns than files, and no more pieces beyond a full
+/// set (which must be promoted pawns) than pawns missing.
+pub fn validate_material(board: &GameBoard, color: Color) -> Result<(), SetupError> {
+    let mut kinds = Vec::new();
+    for (rank, row) in board.iter().enumerate() {
+        for piece in row.iter().flatten().filter(|piece| piece.color == color) {
+            if piece.kind == Pawn && (rank == 0 || rank == RANKS - 1) {
+                return Err(SetupError::PawnOnBackRankError);
+            }
+            kinds.push(piece.kind);
+        }
+    }
+
+    let count = |kind: PieceKind| kinds.iter().filter(|&&k| k == kind).count();
+    let full_set = [
+        (Rook, 2),
+        (Knight, 2),
+        (Bishop, 2),
+        (Queen, 1),
+        (Archbishop, 1),
+        (Chancellor, 1),
+        (Amazon, 0),
+    ];
+    let promoted: usize = full_set
+        .iter()
+        .map(|&(kind, full)| count(kind).saturating_sub(full))
+        .sum();
+    let pawns = count(Pawn);
+    if pawns + promoted > board.files() {
+        return Err(SetupError::TooManyPiecesError);
+    }
+    Ok(())
+}
+
+/// Check that the castling rights of a position have their king and rook on the back rank, and
+/// that a pawn can have just made a double step past its en passant square.
+pub fn validate_rights(fen: &Fen) -> Result<(), SetupError> {
+    let board = &fen.board;
+    let is_piece = |rank: usize, file: usize, kind: PieceKind, color: Color| matches!(board[rank][file], Some(piece) if piece.kind == kind && piece.color == color);
+
+    for &(color, file) in &fen.castling {
+        let back_rank = if color == White { RANKS - 1 } else { 0 };
+        let has_king = (0..board.files()).any(|file| is_piece(back_rank, file, King, color));
+        if file >= board.files() || !has_king || !is_piece(back_rank, file, Rook, color) {
+            return Err(SetupError::InvalidCastlingError);
+        }
+    }
+
+    validate_en_passant(fen)
+}
+
+/// Check that a pawn of the player not to move can have just made a double step past the en
+/// passant square of a position, if it has one.
+pub fn validate_en_passant(fen: &Fen) -> Result<(), SetupError> {
+    let Some(square) = fen.en_passant else {
+        return Ok(());
+    };
+    let board = &fen.board;
+
+    // The pawn that moved belongs to the player not to move, and stands just past the square.
+    let (skipped, from, to) = match fen.turn {
+        White => (2, 1, 3),
+        Black => (5, 6, 4),
+    };
+    let is_pawn = matches!(board[to][square.file], Some(piece) if piece.kind == Pawn && piece.color == fen.turn.opponent());
+    if square.rank != skipped
+        || !board.contains(&square)
+        || board[skipped][square.file].is_some()
+        || board[from][square.file].is_some()
+        || !is_pawn
+    {
+        return Err(SetupError::InvalidEnPassantError);
+    }
+    Ok(())
+}
+
+/// Check that the player not to move in a position is not in check (by the specified rules), and
+/// that the player to move is in check by at most two pieces, of which at most one is a pawn or
+/// leaps to the king (e.g., a knight), as a move either gives check directly or uncovers at most
+/// one other check, and only a sliding piece can be uncovered. A king never gives check.
+pub fn validate_checks<R: Rules + ?Sized>(rules: &R, fen: &Fen) -> Result<(), SetupError> {
+    let board = &fen.board;
+    if rules.is_in_check(board, fen.turn) {
+        if let Some(king) = find_king(board, fen.turn) {
+            validate_checkers(board, fen.turn, &king)?;
+        }
+    }
+    if rules.is_in_check(board, fen.turn.opponent()) {
+        return Err(SetupError::OpponentInCheckError);
+    }
+    Ok(())
+}
+
+/// Check that the pieces giving check to the specified player's king could have done so with a
+/// single move.
+fn validate_checkers(board: &GameBoard, color: Color, king: &Position) -> Result<(), SetupError> {
+    let mut checkers = Vec::new();
+    for (rank, row) in board.iter().enumerate() {
+        for (file, square) in row.iter().enumerate() {
+            let Some(piece) = square.filter(|piece| piece.color != color) else {
+                continue;
+            };
+            let position = Position::new(rank, file);
+            if piece
+                .get_valid_moves_on_board(board, &position)
+                .contains(king)
+            {
+                checkers.push((piece.kind, position));
+            }
+        }
+    }
+
+    // A pawn or a piece a knight's move away reaches the king without crossing any squares, so
+    // its check cannot have been uncovered by another piece moving.
+    let leapers = checkers
+        .iter()
+        .filter(|(kind, position)| {
+            let distance = (
+                position.rank.abs_diff(king.rank),
+                position.file.abs_diff(king.file),
+            );
+            *kind == Pawn || distance == (1, 2) || distance == (2, 1)
+        })
+        .count();
+    let has_king = checkers.iter().any(|(kind, _)| *kind == King);
+    if checkers.len() > 2 || leapers > 1 || has_king {
+        return Err(SetupError::ImpossibleCheckError);
+    }
+    Ok(())
+}
+
+/// The rules of standard chess.
+pub struct Standard;
+
+impl Rules for Standard {}
+
+/// The rules of Chess960, which only differ from standard chess in the starting position (castling
+/// is handled by the standard rules for any arrangement of the back rank).
+pub struct Chess960;
+
+impl Rules for Chess960 {
+    fn get_starting_fen(&self, options: &mut GameOptions) -> Option<Fen> {
+        // The starting position is chosen once, so that the game is replayed from the same
+        // position (e.g., when it is forked).
+        let index = options
+            .chess960_position
+            .filter(|&index| index < chess960::POSITION_COUNT)
+            .unwrap_or_else(chess960::random_index);
+        options.chess960_position = Some(index);
+        chess960::get_starting_fen(index)
+    }
+}
+
+/// The rules of three-check: a player also wins by giving check three times.
+pub struct ThreeCheck;
+
+impl Rules for ThreeCheck {
+    fn apply_move(
+        &self,
+        board: &GameBoard,
+        state: &mut VariantState,
+        position: &Position,
+        new_position: &Position,
+    ) -> GameBoard {
+        let Some(piece) = Game::get_piece(board, position) else {
+            return *board;
+        };
+
+        let board = Standard.apply_move(board, state, position, new_position);
+        if self.is_in_check(&board, piece.color.opponent()) {
+            *state.checks.entry(piece.color).or_default() += 1;
+        }
+        board
+    }
+
+    fn get_outcome(
+        &self,
+        board: &GameBoard,
+        state: &VariantState,
+        color: Color,
+        legal_moves: &[(Position, Position)],
+    ) -> Option<GameResult> {
+        for winner in [color.opponent(), color] {
+            if state.get_checks(winner) >= THREE_CHECK_LIMIT {
+                return Some(GameResult {
+                    winner: Some(winner),
+                    termination: Termination::ThreeChecks,
+                });
+            }
+        }
+        get_checkmate_or_stalemate(self, board, color, legal_moves)
+    }
+}
+
+/// The rules of King of the Hill: a player also wins by moving their king to one of the four
+/// center squares (d4, e4, d5 or e5).
+pub struct KingOfTheHill;
+
+impl Rules for KingOfTheHill {
+    fn get_outcome(
+        &self,
+        board: &GameBoard,
+        _state: &VariantState,
+        color: Color,
+        legal_moves: &[(Position, Position)],
+    ) -> Option<GameResult> {
+        // The hill is the four squares in the center (d4, d5, e4 and e5 on an 8-file board).
+        let files = (board.files() - 1) / 2..=board.files() / 2;
+        let hill = (3..=4).flat_map(|rank| &board[rank][files.clone()]);
+        if let Some(king) = hill.flatten().find(|piece| piece.kind == King) {
+            return Some(GameResult {
+                winner: Some(king.color),
+                termination: Termination::KingOfTheHill,
+            });
+        }
+        get_checkmate_or_stalemate(self, board, color, legal_moves)
+    }
+}
+
+/// The rules of Crazyhouse: captured pieces change color and go into the capturer's pocket, and
+/// instead of moving, a player may drop a piece from their pocket onto any empty square (except
+/// pawns onto the first or last rank). The core rules have no promotion, so there are no promoted
+/// pieces to revert to pawns when captured.
+pub struct Crazyhouse;
+
+impl Rules for Crazyhouse {
+    fn apply_move(
+        &self,
+        board: &GameBoard,
+        state: &mut VariantState,
+        position: &Position,
+        new_position: &Position,
+    ) -> GameBoard {
+        if let (Some(piece), Some(captured)) = (
+            Game::get_piece(board, position),
+            Game::get_piece(board, new_position),
+        ) {
+            if captured.color != piece.color {
+                state.add_to_pocket(piece.color, captured.kind);
+            }
+        }
+        Standard.apply_move(board, state, position, new_position)
+    }
+
+    fn get_legal_drops(
+        &self,
+        board: &GameBoard,
+        state: &VariantState,
+        color: Color,
+    ) -> Vec<(PieceKind, Position)> {
+        let mut kinds = state.get_pocket(color).to_vec();
+        kinds.dedup();
+
+        let mut drops = Vec::new();
+        for kind in kinds {
+            for rank in 0..8 {
+                if kind == Pawn && (rank == 0 || rank == 7) {
+                    continue;
+                }
+
+                for file in 0..board.files() {
+                    if board[rank][file].is_some() {
+                        continue;
+                    }
+
+                    // A drop may not leave the player's own king in check.
+                    let mut after = *board;
+                    after[rank][file] = Some(Piece::new(kind, color));
+                    if !self.is_in_check(&after, color) {
+                        drops.push((kind, Position::new(rank, file)));
+                    }
+                }
+            }
+        }
+        drops
+    }
+
+    fn get_outcome(
+        &self,
+        board: &GameBoard,
+        state: &VariantState,
+        color: Color,
+        legal_moves: &[(Position, Position)],
+    ) -> Option<GameResult> {
+        // A player who cannot move may still be able to drop a piece (e.g., to block a check).
+        if legal_moves.is_empty() && !self.get_legal_drops(board, state, color).is_empty() {
+            return None;
+        }
+        get_checkmate_or_stalemate(self, board, color, legal_moves)
+    }
+}
+
+/// The rules of Capablanca Chess, which are those of standard chess on a 10x8 board (see
+/// [CAPABLANCA_FEN]). Castling moves the king to the c- or i-file, with the rook beside it.
+pub struct Capablanca;
+
+impl Rules for Capablanca {
+    fn get_starting_fen(&self, _options: &mut GameOptions) -> Option<Fen> {
+        Some(
+            Fen::from_str(CAPABLANCA_FEN)
+                .expect("the Capablanca starting position should be valid"),
+        )
+    }
+}
+
+/// The rules of one board of Bughouse: pieces are dropped from the pocket as in Crazyhouse, but
+/// captured pieces go to the capturer's partner on the other board (see
+/// [crate::game_manager::GameManager::new_bughouse_match]) rather than into their own pocket.
+pub struct Bughouse;
+
+impl Rules for Bughouse {
+    fn get_legal_drops(
+        &self,
+        board: &GameBoard,
+        state: &VariantState,
+        color: Color,
+    ) -> Vec<(PieceKind, Position)> {
+        Crazyhouse.get_legal_drops(board, state, color)
+    }
+
+    fn get_outcome(
+        &self,
+        board: &GameBoard,
+        state: &VariantState,
+        color: Color,
+        legal_moves: &[(Position, Position)],
+    ) -> Option<GameResult> {
+        Crazyhouse.get_outcome(board, state, color, legal_moves)
+    }
+}
+
+/// Get every move the pieces of the specified player can make on the specified board, whether or
+/// not it leaves their king in check (and without castling).
+pub fn get_piece_moves(board: &GameBoard, color: Color) -> Vec<(Position, Position)> {
+    let mut moves = Vec::new();
+    for rank in 0..8 {
+        for file in 0..board.files() {
+            let piece = match board[rank][file] {
+                Some(piece) if piece.color == color => piece,
+                _ => continue,
+            };
+
+            let position = Position::new(rank, file);
+            for new_position in piece.get_valid_moves_on_board(board, &position) {
+                moves.push((position, new_position));
+            }
+        }
+    }
+    moves.sort_by_key(|(from, to)| (from.rank, from.file, to.rank, to.file));
+    moves
+}
+
+/// Check whether the specified player has any pieces left on the specified board.
+fn has_pieces(board: &GameBoard, color: Color) -> bool {
+    board
+        .iter()
+        .flatten()
+        .flatten()
+        .any(|piece| piece.color == color)
+}
+
+/// Find the specified player's king on the specified board.
+fn find_king(board: &GameBoard, color: Color) -> Option<Position> {
+    (0..8)
+        .flat_map(|rank| (0..board.files()).map(move |file| Position::new(rank, file)))
+        .find(|position| {
+            matches!(Game::get_piece(board, position), Some(piece) if piece.kind == King && piece.color == color)
+        })
+}
+
+/// The rules of Atomic: a capture explodes, removing the capturing and captured pieces along with
+/// every piece other than a pawn on the surrounding squares. Kings may not capture, and a player
+/// wins by exploding the opponent's king. Kings standing next to each other cannot give check.
+pub struct Atomic;
+
+impl Atomic {
+    /// Check whether the kings stand next to each other on the specified board.
+    fn are_kings_connected(board: &GameBoard) -> bool {
+        match (find_king(board, White), find_king(board, Black)) {
+            (Some(white), Some(black)) => {
+                white.rank.abs_diff(black.rank) <= 1 && white.file.abs_diff(black.file) <= 1
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Rules for Atomic {
+    fn get_legal_moves(&self, board: &GameBoard, color: Color) -> Vec<(Position, Position)> {
+        let mut state = VariantState::default();
+        let mut moves: Vec<(Position, Position)> = get_piece_moves(board, color)
+            .into_iter()
+            .filter(|(position, new_position)| {
+                let piece = Game::get_piece(board, position).unwrap();
+                let is_capture = Game::get_piece(board, new_position).is_some();
+                if piece.kind == King && is_capture {
+                    return false;
+                }
+
+                // A move may not explode the player's own king, nor leave it in check unless it
+                // explodes the opponent's king.
+                let after = self.apply_move(board, &mut state, position, new_position);
+                find_king(&after, color).is_some()
+                    && (find_king(&after, color.opponent()).is_none()
+                        || !self.is_in_check(&after, color))
+            })
+            .collect();
+        moves.extend(Game::get_castling_moves_on_board(board, color));
+        moves
+    }
+
+    fn is_in_check(&self, board: &GameBoard, color: Color) -> bool {
+        !Atomic::are_kings_connected(board) && Game::is_in_check_on_board(board, color)
+    }
+
+    fn apply_move(
+        &self,
+        board: &GameBoard,
+        state: &mut VariantState,
+        position: &Position,
+        new_position: &Position,
+    ) -> GameBoard {
+        let (Some(piece), Some(captured)) = (
+            Game::get_piece(board, position),
+            Game::get_piece(board, new_position),
+        ) else {
+            return Standard.apply_move(board, state, position, new_position);
+        };
+        if captured.color == piece.color {
+            // The king castles onto its own rook.
+            return Standard.apply_move(board, state, position, new_position);
+        }
+
+        let mut board = *board;
+        board[position.rank][position.file] = None;
+        board[new_position.rank][new_position.file] = None;
+        let files =
+            new_position.file.saturating_sub(1)..=(new_position.file + 1).min(board.files() - 1);
+        let ranks = new_position.rank.saturating_sub(1)..=(new_position.rank + 1).min(7);
+        for rank in ranks {
+            for square in &mut board[rank][files.clone()] {
+                if square.is_some_and(|piece| piece.kind != Pawn) {
+                    *square = None;
+                }
+            }
+        }
+        board
+    }
+
+    fn get_outcome(
+        &self,
+        board: &GameBoard,
+        _state: &VariantState,
+        color: Color,
+        legal_moves: &[(Position, Position)],
+    ) -> Option<GameResult> {
+        if find_king(board, color).is_none() {
+            return Some(GameResult {
+                winner: Some(color.opponent()),
+                termination: Termination::Explosion,
+            });
+        }
+        get_checkmate_or_stalemate(self, board, color, legal_moves)
+    }
+}
+
+/// The rules of Antichess: captures are compulsory, there is no check (the king is captured like
+/// any other piece) and no castling, and a player wins by losing all their pieces or by having no
+/// legal moves.
+pub struct Antichess;
+
+impl Rules for Antichess {
+    fn get_legal_moves(&self, board: &GameBoard, color: Color) -> Vec<(Position, Position)> {
+        let moves = get_piece_moves(board, color);
+        self.filter_moves(board, color, moves)
+    }
+
+    fn filter_moves(
+        &self,
+        board: &GameBoard,
+        _color: Color,
+        moves: Vec<(Position, Position)>,
+    ) -> Vec<(Position, Position)> {
+        let captures: Vec<(Position, Position)> = moves
+            .iter()
+            .copied()
+            .filter(|(_, new_position)| Game::get_piece(board, new_position).is_some())
+            .collect();
+        if captures.is_empty() {
+            moves
+        } else {
+            captures
+        }
+    }
+
+    fn is_in_check(&self, _board: &GameBoard, _color: Color) -> bool {
+        false
+    }
+
+    fn get_outcome(
+        &self,
+        board: &GameBoard,
+        _state: &VariantState,
+        color: Color,
+        legal_moves: &[(Position, Position)],
+    ) -> Option<GameResult> {
+        if !has_pieces(board, color) {
+            return Some(GameResult {
+                winner: Some(color),
+                termination: Termination::NoPiecesLeft,
+            });
+        }
+        if legal_moves.is_empty() {
+            return Some(GameResult {
+                winner: Some(color),
+                termination: Termination::Stalemate,
+            });
+        }
+        None
+    }
+
+    fn validate_position(&self, fen: &Fen) -> Result<(), SetupError> {
+        // A player may have any number of kings, which are never in check.
+        for color in [White, Black] {
+            validate_material(&fen.board, color)?;
+        }
+        Ok(())
+    }
+}
+
+/// The rules of Horde: White starts with 36 pawns and no king, and wins by checkmating Black.
+/// Black wins by capturing every White piece. White's pawns on the first rank may move two
+/// squares, like those on the second.
+pub struct Horde;
+
+impl Rules for Horde {
+    fn get_starting_fen(&self, _options: &mut GameOptions) -> Option<Fen> {
+        let mut fen =
+            Fen::from_str(HORDE_FEN).expect("the Horde starting position should be valid");
+        for piece in fen.board[7].iter_mut().flatten() {
+            piece.move_count = 0;
+        }
+        Some(fen)
+    }
+
+    fn get_outcome(
+        &self,
+        board: &GameBoard,
+        _state: &VariantState,
+        color: Color,
+        legal_moves: &[(Position, Position)],
+    ) -> Option<GameResult> {
+        if !has_pieces(board, White) {
+            return Some(GameResult {
+                winner: Some(Black),
+                termination: Termination::NoPiecesLeft,
+            });
+        }
+        get_checkmate_or_stalemate(self, board, color, legal_moves)
+    }
+
+    fn validate_position(&self, fen: &Fen) -> Result<(), SetupError> {
+        // White's horde of pawns has no king and is not limited to a full set.
+        validate_king(&fen.board, Black)?;
+        validate_material(&fen.board, Black)?;
+        validate_rights(fen)?;
+        validate_checks(self, fen)
+    }
+}
+
+/// The rules of Dark Chess: each player only sees their own pieces and the squares those pieces
+/// can move to. As a player cannot always tell whether they are in check, there is no check: a
+/// king may move into or stay in danger, and a player wins by capturing the opponent's king.
+pub struct DarkChess;
+
+impl DarkChess {
+    /// Get the squares the specified player can see on the specified board: those their pieces
+    /// stand on or can move to, along with the square in front of each of their pawns (so that they
+    /// can see what blocks it).
+    pub fn get_visible_squares(board: &GameBoard, color: Color) -> [[bool; MAX_FILES]; RANKS] {
+        let mut visible = [[false; MAX_FILES]; RANKS];
+        for (_, new_position) in get_piece_moves(board, color) {
+            visible[new_position.rank][new_position.file] = true;
+        }
+
+        for (rank, row) in board.iter().enumerate() {
+            for (file, square) in row.iter().enumerate() {
+                let Some(piece) = square.filter(|piece| piece.color == color) else {
+                    continue;
+                };
+
+                visible[rank][file] = true;
+                if piece.kind == Pawn {
+                    let ahead = match color {
+                        White => rank.checked_sub(1),
+                        Black => Some(rank + 1).filter(|&rank| rank < 8),
+                    };
+                    if let Some(ahead) = ahead {
+                        visible[ahead][file] = true;
+                    }
+                }
+            }
+        }
+        visible
+    }
+
+    /// Get a copy of the specified board with the pieces the specified player cannot see removed.
+    pub fn hide_unseen(board: &GameBoard, color: Color) -> GameBoard {
+        let visible = DarkChess::get_visible_squares(board, color);
+        let mut board = *board;
+        for (row, visible) in board.iter_mut().zip(visible) {
+            for (square, visible) in row.iter_mut().zip(visible) {
+                if !visible {
+                    *square = None;
+                }
+            }
+        }
+        board
+    }
+}
+
+impl Rules for DarkChess {
+    fn get_legal_moves(&self, board: &GameBoard, color: Color) -> Vec<(Position, Position)> {
+        let mut moves = get_piece_moves(board, color);
+        moves.extend(Game::get_castling_moves_on_board(board, color));
+        self.filter_moves(board, color, moves)
+    }
+
+    fn is_in_check(&self, _board: &GameBoard, _color: Color) -> bool {
+        false
+    }
+
+    fn get_outcome(
+        &self,
+        board: &GameBoard,
+        _state: &VariantState,
+        color: Color,
+        legal_moves: &[(Position, Position)],
+    ) -> Option<GameResult> {
+        if find_king(board, color).is_none() {
+            return Some(GameResult {
+                winner: Some(color.opponent()),
+                termination: Termination::KingCaptured,
+            });
+        }
+        get_checkmate_or_stalemate(self, board, color, legal_moves)
+    }
+}
+
+impl Variant {
+    /// Get the rules games of this variant are played by.
+    pub fn rules(&self) -> &'static dyn Rules {
+        match self {
+            Variant::Standard => &Standard,
+            Variant::Chess960 => &Chess960,
+            Variant::ThreeCheck => &ThreeCheck,
+            Variant::KingOfTheHill => &KingOfTheHill,
+            Variant::Crazyhouse => &Crazyhouse,
+            Variant::Atomic => &Atomic,
+            Variant::Antichess => &Antichess,
+            Variant::Horde => &Horde,
+            Variant::DarkChess => &DarkChess,
+            Variant::Bughouse => &Bughouse,
+            Variant::Capablanca => &Capablanca,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::{MoveError, SetupError};
+    use crate::fen::{Fen, STARTING_FEN};
+    use crate::game::Color::{Black, White};
+    use crate::game::PieceKind::{Amazon, Knight, Pawn};
+    use crate::game::{Game, GameOptions, GameResult, Termination, Variant, Viewer};
+    use crate::game_manager::GameManager;
+    use crate::moves::{Move, Position};
+    use crate::pgn::write_game;
+    use crate::rules::{DarkChess, VariantState, CAPABLANCA_FEN};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_standard_rules() {
+        let rules = Variant::Standard.rules();
+        let state = VariantState::default();
+        let mut options = GameOptions::default();
+        assert_eq!(rules.get_starting_fen(&mut options), None);
+
+        let board = Fen::from_str(STARTING_FEN).unwrap().board;
+        let moves = rules.get_legal_moves(&board, White);
+        assert_eq!(moves.len(), 20);
+        assert_eq!(rules.get_outcome(&board, &state, White, &moves), None);
+
+        // Fool's mate.
+        let board = Fen::from_str("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+            .unwrap()
+            .board;
+        let moves = rules.get_legal_moves(&board, White);
+        assert_eq!(
+            rules.get_outcome(&board, &state, White, &moves),
+            Some(GameResult {
+                winner: Some(Black),
+                termination: Termination::Checkmate,
+            })
+        );
+
+        let board = Fen::from_str("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")
+            .unwrap()
+            .board;
+        let moves = rules.get_legal_moves(&board, Black);
+        assert_eq!(
+            rules.get_outcome(&board, &state, Black, &moves),
+            Some(GameResult {
+                winner: None,
+                termination: Termination::Stalemate,
+            })
+        );
+    }
+
+    #[test]
+    fn test_chess960_rules() {
+        let rules = Variant::Chess960.rules();
+        let mut options = GameOptions {
+            chess960_position: Some(0),
+            ..GameOptions::default()
+        };
+        assert_eq!(
+            rules.get_starting_fen(&mut options).unwrap().to_string(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+        );
+
+        // An index out of range is replaced with a random one.
+        let mut options = GameOptions {
+            chess960_position: Some(960),
+            ..GameOptions::default()
+        };
+        assert!(rules.get_starting_fen(&mut options).is_some());
+        assert!(options.chess960_position.unwrap() < 960);
+    }
+
+    #[test]
+    fn test_three_check() {
+        let options = GameOptions {
+            variant: Variant::ThreeCheck,
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+
+        // White gives check twice with the bishop, then with the queen.
+        let moves = [
+            ("E2", "E4"),
+            ("D7", "D6"),
+            ("F1", "B5"),
+            ("B8", "C6"),
+            ("B5", "C6"),
+            ("B7", "C6"),
+            ("D1", "H5"),
+            ("G8", "F6"),
+            ("H5", "F7"),
+        ];
+        for (index, (from, to)) in moves.into_iter().enumerate() {
+            assert!(game.get_result().is_none(), "{}", index);
+            game.move_piece_at_position(
+                &Position::from_str(from).unwrap(),
+                &Position::from_str(to).unwrap(),
+            )
+            .unwrap();
+        }
+        assert_eq!(game.get_variant_state().get_checks(White), 3);
+        assert_eq!(game.get_variant_state().get_checks(Black), 0);
+        assert_eq!(
+            game.get_result(),
+            Some(GameResult {
+                winner: Some(White),
+                termination: Termination::ThreeChecks,
+            })
+        );
+    }
+
+    #[test]
+    fn test_king_of_the_hill() {
+        let rules = Variant::KingOfTheHill.rules();
+        let state = VariantState::default();
+
+        let board = Fen::from_str("4k3/8/8/8/4K3/8/8/8 b - - 0 1")
+            .unwrap()
+            .board;
+        let moves = rules.get_legal_moves(&board, Black);
+        assert_eq!(
+            rules.get_outcome(&board, &state, Black, &moves),
+            Some(GameResult {
+                winner: Some(White),
+                termination: Termination::KingOfTheHill,
+            })
+        );
+
+        let board = Fen::from_str("4k3/8/8/8/8/4K3/8/8 b - - 0 1")
+            .unwrap()
+            .board;
+        let moves = rules.get_legal_moves(&board, Black);
+        assert_eq!(rules.get_outcome(&board, &state, Black, &moves), None);
+    }
+
+    #[test]
+    fn test_crazyhouse() {
+        let position = |s: &str| Position::from_str(s).unwrap();
+        let options = GameOptions {
+            variant: Variant::Crazyhouse,
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        for (from, to) in [("E2", "E4"), ("D7", "D5"), ("E4", "D5"), ("D8", "D5")] {
+            game.move_piece_at_position(&position(from), &position(to))
+                .unwrap();
+        }
+
+        // Each captured pawn went into the capturer's pocket.
+        let state = game.get_variant_state();
+        assert_eq!(state.get_pocket(White), &[Pawn]);
+        assert_eq!(state.get_pocket(Black), &[Pawn]);
+
+        assert!(matches!(
+            game.drop_piece(Knight, &position("C3")),
+            Err(MoveError::PieceNotInPocketError)
+        ));
+        assert!(matches!(
+            game.drop_piece(Pawn, &position("C8")),
+            Err(MoveError::IllegalMoveError)
+        ));
+        assert!(matches!(
+            game.drop_piece(Pawn, &position("D5")),
+            Err(MoveError::IllegalMoveError)
+        ));
+
+        game.drop_piece(Pawn, &position("C4")).unwrap();
+        assert_eq!(
+            game.get_piece_by_position(&position("C4")).unwrap().kind,
+            Pawn
+        );
+        assert!(game.get_variant_state().get_pocket(White).is_empty());
+        assert_eq!(
+            game.get_moves().last(),
+            Some(&Move::Drop(Pawn, position("C4")))
+        );
+        assert!(write_game(&game).ends_with("\n1. e4 d5 2. exd5 Qxd5 3. P@c4 *\n"));
+
+        // A player who can only block a check by dropping a piece has not been checkmated.
+        let rules = Variant::Crazyhouse.rules();
+        let board = Fen::from_str("7k/8/8/8/8/8/PP6/K6r w - - 0 1")
+            .unwrap()
+            .board;
+        let mut state = VariantState::default();
+        assert!(rules.get_outcome(&board, &state, White, &[]).is_some());
+
+        state.add_to_pocket(White, Knight);
+        let drops = rules.get_legal_drops(&board, &state, White);
+        assert_eq!(drops.len(), 6);
+        assert_eq!(rules.get_outcome(&board, &state, White, &[]), None);
+    }
+
+    /// Create a game of the specified variant from the specified position.
+    fn game_from_fen(variant: Variant, fen: &str) -> Game {
+        let options = GameOptions {
+            variant,
+            starting_fen: Some(Fen::from_str(fen).unwrap()),
+            ..GameOptions::default()
+        };
+        Game::new_with_options(None, options)
+    }
+
+    #[test]
+    fn test_atomic() {
+        let position = |s: &str| Position::from_str(s).unwrap();
+        let rules = Variant::Atomic.rules();
+        let mut state = VariantState::default();
+
+        // The capture explodes the pieces around d5, except pawns.
+        let fen = Fen::from_str("4k3/8/2b5/3nq3/4P1p1/8/8/4K3 w - - 0 1").unwrap();
+        let after = rules.apply_move(&fen.board, &mut state, &position("E4"), &position("D5"));
+        let fen = Fen {
+            board: after,
+            ..fen
+        };
+        assert_eq!(fen.to_string(), "4k3/8/8/8/6p1/8/8/4K3 w - - 0 1");
+
+        // Kings may not capture, and connected kings cannot give check.
+        let board = Fen::from_str("8/8/8/8/8/8/3k4/r3K3 w - - 0 1")
+            .unwrap()
+            .board;
+        assert!(!rules.is_in_check(&board, White));
+        assert!(rules
+            .get_legal_moves(&board, White)
+            .iter()
+            .all(|(_, to)| *to != position("D2")));
+
+        let game = game_from_fen(Variant::Atomic, "4k3/4r3/8/8/8/8/8/K3R3 w - - 0 1");
+        game.move_piece_at_position(&position("E1"), &position("E7"))
+            .unwrap();
+        assert_eq!(
+            game.get_result(),
+            Some(GameResult {
+                winner: Some(White),
+                termination: Termination::Explosion,
+            })
+        );
+    }
+
+    #[test]
+    fn test_antichess() {
+        let position = |s: &str| Position::from_str(s).unwrap();
+        let game = game_from_fen(Variant::Antichess, "4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1");
+
+        // Captures are compulsory.
+        assert_eq!(
+            game.get_legal_moves(),
+            vec![(position("E4"), position("D5"))]
+        );
+        assert!(game
+            .move_piece_at_position(&position("E4"), &position("E5"))
+            .is_err());
+
+        // The player who loses all their pieces wins.
+        let game = game_from_fen(Variant::Antichess, "8/8/8/3p4/4P3/8/8/8 w - - 0 1");
+        game.move_piece_at_position(&position("E4"), &position("D5"))
+            .unwrap();
+        assert_eq!(
+            game.get_result(),
+            Some(GameResult {
+                winner: Some(Black),
+                termination: Termination::NoPiecesLeft,
+            })
+        );
+    }
+
+    #[test]
+    fn test_horde() {
+        let position = |s: &str| Position::from_str(s).unwrap();
+        let options = GameOptions {
+            variant: Variant::Horde,
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        let board = *game.board.lock().unwrap();
+        let white_pawns = board
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|piece| piece.color == White)
+            .count();
+        assert_eq!(white_pawns, 36);
+        assert!(!game.is_player_in_check(White));
+
+        // Pawns on the first rank may move two squares.
+        let rules = Variant::Horde.rules();
+        let mut board = rules
+            .get_starting_fen(&mut GameOptions::default())
+            .unwrap()
+            .board;
+        board[6][0] = None;
+        board[5][0] = None;
+        let moves = rules.get_legal_moves(&board, White);
+        assert!(moves.contains(&(position("A1"), position("A3"))));
+
+        // Black wins by capturing every White piece.
+        let game = game_from_fen(Variant::Horde, "4k3/8/8/8/8/8/3r4/3P4 b - - 0 1");
+        game.move_piece_at_position(&position("D2"), &position("D1"))
+            .unwrap();
+        assert_eq!(
+            game.get_result(),
+            Some(GameResult {
+                winner: Some(Black),
+                termination: Termination::NoPiecesLeft,
+            })
+        );
+    }
+
+    #[test]
+    fn test_dark_chess() {
+        let position = |s: &str| Position::from_str(s).unwrap();
+
+        // Each player sees their own half of the starting position, and the two ranks in front.
+        let board = Fen::from_str(STARTING_FEN).unwrap().board;
+        let visible = DarkChess::get_visible_squares(&board, White);
+        for (rank, row) in visible.iter().enumerate() {
+            assert!(row[..8].iter().all(|&visible| visible == (rank >= 4)));
+        }
+        let hidden = DarkChess::hide_unseen(&board, White);
+        for (rank, (hidden, row)) in hidden.iter().zip(board.iter()).enumerate() {
+            if rank < 4 {
+                assert!(hidden.iter().all(Option::is_none));
+            } else {
+                assert_eq!(hidden, row);
+            }
+        }
+
+        // A king may move into danger, and is captured rather than checkmated.
+        let rules = Variant::DarkChess.rules();
+        let board = Fen::from_str("4k3/8/8/8/8/8/8/3RK3 b - - 0 1")
+            .unwrap()
+            .board;
+        assert!(!rules.is_in_check(&board, Black));
+        assert!(rules
+            .get_legal_moves(&board, Black)
+            .contains(&(position("E8"), position("D8"))));
+
+        let game = game_from_fen(Variant::DarkChess, "3k4/8/8/8/8/8/8/3RK3 w - - 0 1");
+        game.move_piece_at_position(&position("D1"), &position("D8"))
+            .unwrap();
+        assert_eq!(
+            game.get_result(),
+            Some(GameResult {
+                winner: Some(White),
+                termination: Termination::KingCaptured,
+            })
+        );
+
+        // Until the game is over, Black cannot see White's pawn on e4, and spectators see either
+        // nothing or the position a ply ago.
+        let view = |game: &Game, viewer: Viewer| game.get_visible_position(viewer).board;
+        let e4 = position("E4");
+        for spectator_delay in [None, Some(1)] {
+            let options = GameOptions {
+                variant: Variant::DarkChess,
+                spectator_delay,
+                ..GameOptions::default()
+            };
+            let game = Game::new_with_options(None, options);
+            game.move_piece_at_position(&position("E2"), &e4).unwrap();
+            assert!(game.hides_moves());
+            assert!(view(&game, Viewer::Player(White))[e4.rank][e4.file].is_some());
+            assert!(view(&game, Viewer::Player(Black))[e4.rank][e4.file].is_none());
+
+            let spectator_board = view(&game, Viewer::Spectator);
+            match spectator_delay {
+                None => assert!(spectator_board.iter().flatten().all(Option::is_none)),
+                Some(_) => assert_eq!(spectator_board, Fen::from_str(STARTING_FEN).unwrap().board),
+            }
+
+            game.resign(Black).unwrap();
+            assert!(!game.hides_moves());
+            assert_eq!(view(&game, Viewer::Spectator), *game.board.lock().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_bughouse() {
+        let position = |s: &str| Position::from_str(s).unwrap();
+        let mut game_manager = GameManager::new();
+        let [first, second] = game_manager.new_bughouse_match(GameOptions::default());
+        let (first, second) = (first.lock().unwrap(), second.lock().unwrap());
+        assert_eq!(first.get_options().variant, Variant::Bughouse);
+
+        // White's capture on the first board goes to their partner, who plays Black on the second.
+        for (from, to) in [("E2", "E4"), ("D7", "D5"), ("E4", "D5")] {
+            first
+                .move_piece_at_position(&position(from), &position(to))
+                .unwrap();
+        }
+        assert!(first.get_variant_state().get_pocket(White).is_empty());
+        assert_eq!(second.get_variant_state().get_pocket(Black), &[Pawn]);
+
+        second
+            .move_piece_at_position(&position("E2"), &position("E4"))
+            .unwrap();
+        second.drop_piece(Pawn, &position("D5")).unwrap();
+        assert!(second.get_variant_state().get_pocket(Black).is_empty());
+
+        // A loss on either board ends the match.
+        first.resign(Black).unwrap();
+        assert_eq!(
+            second.get_result(),
+            Some(GameResult {
+                winner: Some(Black),
+                termination: Termination::PartnerBoard,
+            })
+        );
+        assert!(matches!(
+            second.move_piece_at_position(&position("E4"), &position("D5")),
+            Err(MoveError::GameOverError)
+        ));
+    }
+
+    #[test]
+    fn test_bughouse_drop_blocks_mate() {
+        let position = |s: &str| Position::from_str(s).unwrap();
+        let mut game_manager = GameManager::new();
+        let [first, second] = game_manager.new_bughouse_match(GameOptions::default());
+        let (first, second) = (first.lock().unwrap(), second.lock().unwrap());
+
+        // White captures a knight on the second board, which goes to Black on the first.
+        for (from, to) in [
+            ("E2", "E4"),
+            ("G8", "F6"),
+            ("B1", "C3"),
+            ("F6", "E4"),
+            ("C3", "E4"),
+        ] {
+            second
+                .move_piece_at_position(&position(from), &position(to))
+                .unwrap();
+        }
+        assert_eq!(first.get_variant_state().get_pocket(Black), &[Knight]);
+
+        // Black has no move out of check, but may drop the knight to block it.
+        for (from, to) in [
+            ("E2", "E4"),
+            ("F7", "F6"),
+            ("D2", "D4"),
+            ("G7", "G5"),
+            ("D1", "H5"),
+        ] {
+            first
+                .move_piece_at_position(&position(from), &position(to))
+                .unwrap();
+        }
+        assert!(first.get_legal_moves().is_empty());
+        assert_eq!(first.get_legal_drops().len(), 2);
+        assert_eq!(first.get_result(), None);
+        assert_eq!(second.get_result(), None);
+
+        first.drop_piece(Knight, &position("G6")).unwrap();
+        assert_eq!(first.get_result(), None);
+    }
+
+    #[test]
+    fn test_capablanca() {
+        let position = |s: &str| Position::from_str(s).unwrap();
+        let options = GameOptions {
+            variant: Variant::Capablanca,
+            ..GameOptions::default()
+        };
+        let game = Game::new_with_options(None, options);
+        assert_eq!(game.board.lock().unwrap().files(), 10);
+        assert_eq!(Fen::from_game(&game).to_string(), CAPABLANCA_FEN);
+
+        // The archbishop and chancellor start with only their knight moves.
+        let moves = game.get_legal_moves();
+        let targets = |from: &str| {
+            let mut targets: Vec<String> = moves
+                .iter()
+                .filter(|(position, _)| *position == Position::from_str(from).unwrap())
+                .map(|(_, new_position)| new_position.to_string())
+                .collect();
+            targets.sort();
+            targets
+        };
+        assert_eq!(targets("C1"), ["B3", "D3"]);
+        assert_eq!(targets("H1"), ["G3", "I3"]);
+        assert_eq!(moves.len(), 28);
+
+        // An amazon moves as a queen or a knight.
+        let board = Fen::from_str("4k3/8/8/8/8/8/8/M3K3 w - - 0 1")
+            .unwrap()
+            .board;
+        let amazon = board[7][0].unwrap();
+        assert_eq!(amazon.kind, Amazon);
+        assert_eq!(
+            amazon
+                .get_valid_moves_on_board(&board, &position("A1"))
+                .len(),
+            19
+        );
+
+        // The king castles to the i-file, with the rook beside it.
+        let game = game_from_fen(
+            Variant::Capablanca,
+            "r4k3r/10/10/10/10/10/10/R4K3R w KQkq - 0 1",
+        );
+        game.move_piece_at_position(&position("F1"), &position("I1"))
+            .unwrap();
+        assert_eq!(
+            Fen::from_game(&game).to_string(),
+            "r4k3r/10/10/10/10/10/10/R6RK1 b kq - 1 1"
+        );
+        assert_eq!(write_game(&game).lines().last(), Some("1. O-O *"));
+    }
+
+    #[test]
+    fn test_validate_checks() {
+        let validate = |variant: Variant, fen: &str| {
+            variant
+                .rules()
+                .validate_position(&Fen::from_str(fen).unwrap())
+        };
+
+        // A knight's check may uncover a rook's, but not a pawn's.
+        validate(Variant::Standard, "4k3/8/5N2/8/8/8/8/4RK2 b - - 0 1").unwrap();
+        assert!(matches!(
+            validate(Variant::Standard, "4k3/3P4/5N2/8/8/8/8/4K3 b - - 0 1"),
+            Err(SetupError::ImpossibleCheckError)
+        ));
+
+        // An archbishop leaping to the king cannot uncover a knight's check either.
+        assert!(matches!(
+            validate(
+                Variant::Capablanca,
+                "4k5/10/3A1N4/10/10/10/10/4K5 b - - 0 1"
+            ),
+            Err(SetupError::ImpossibleCheckError)
+        ));
+
+        // A king never gives check.
+        assert!(matches!(
+            validate(Variant::Standard, "8/8/8/8/8/8/4k3/4K3 w - - 0 1"),
+            Err(SetupError::ImpossibleCheckError)
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::game::Color;
+use crate::game::Color::{Black, White};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The number of moves used to estimate the duration of a game from its time control.
+const ESTIMATED_MOVES: u64 = 40;
+
+/// The broad categories of time control that players are rated in separately.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeControlCategory {
+    Bullet,
+    Blitz,
+    Rapid,
+    Classical,
+}
+
+/// A time control: the time each player starts with and the time added after each of their moves.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeControl {
+    /// The initial time on each player's clock, in seconds.
+    pub initial: u64,
+
+    /// The time added to a player's clock after each of their moves, in seconds.
+    #[serde(default)]
+    pub increment: u64,
+}
+
+impl TimeControl {
+    pub fn new(initial: u64, increment: u64) -> TimeControl {
+        TimeControl { initial, increment }
+    }
+
+    /// Get the category of the time control, based on the estimated duration of a game of
+    /// [ESTIMATED_MOVES] moves (e.g., 3+2 is estimated at 3 minutes plus 40 * 2 seconds).
+    pub fn category(&self) -> TimeControlCategory {
+        let estimated_duration = self.initial + ESTIMATED_MOVES * self.increment;
+
+        match estimated_duration {
+            0..180 => TimeControlCategory::Bullet,
+            180..480 => TimeControlCategory::Blitz,
+            480..1500 => TimeControlCategory::Rapid,
+            _ => TimeControlCategory::Classical,
+        }
+    }
+}
+
+/// A chess clock for a timed game. Neither player's time runs until the first move has been made.
+#[derive(Clone, Debug)]
+pub struct Clock {
+    /// The time each player had left when their clock was last stopped.
+    remaining: BTreeMap<Color, TimeDelta>,
+
+    /// The time added to each player's clock after each of their moves.
+    increment: BTreeMap<Color, TimeDelta>,
+
+    /// The player whose clock is running and the [DateTime] it was started, once the clock has
+    /// started.
+    running: Option<(Color, DateTime<Utc>)>,
+
+    /// The players that have berserked (i.e., given up half their time).
+    berserk: Vec<Color>,
+}
+
+/// The state of a [Clock] at a point in time.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ClockState {
+    /// The time each player has left, in milliseconds.
+    pub remaining: BTreeMap<Color, i64>,
+
+    /// The player whose clock is running, if the clock has started.
+    pub running: Option<Color>,
+
+    pub berserk: Vec<Color>,
+}
+
+impl Clock {
+    pub fn new(time_control: TimeControl) -> Clock {
+        Clock::new_asymmetric(time_control, time_control)
+    }
+
+    /// Create a clock on which each player has their own time control (e.g., when one gives the
+    /// other time odds).
+    pub fn new_asymmetric(white: TimeControl, black: TimeControl) -> Clock {
+        let initial = |time_control: TimeControl| TimeDelta::seconds(time_control.initial as i64);
+        let increment =
+            |time_control: TimeControl| TimeDelta::seconds(time_control.increment as i64);
+
+        Clock {
+            remaining: [(White, initial(white)), (Black, initial(black))].into(),
+            increment: [(White, increment(white)), (Black, increment(black))].into(),
+            running: None,
+            berserk: Vec::new(),
+        }
+    }
+
+    /// Get the time the specified player has left at the specified [DateTime].
+    pub fn get_remaining(&self, color: Color, now: DateTime<Utc>) -> TimeDelta {
+        let remaining = match self.running {
+            Some((running, since)) if running == color => self.remaining[&color] - (now - since),
+            _ => self.remaining[&color],
+        };
+        remaining.max(TimeDelta::zero())
+    }
+
+    /// Get the player whose clock is running, if the clock has started.
+    pub fn get_running(&self) -> Option<Color> {
+        self.running.map(|(color, _)| color)
+    }
+
+    /// Check whether the specified player's time has run out.
+    pub fn is_flagged(&self, color: Color, now: DateTime<Utc>) -> bool {
+        self.get_running() == Some(color) && self.get_remaining(color, now).is_zero()
+    }
+
+    /// Stop the specified player's clock after they move (adding their increment), and start their
+    /// opponent's.
+    pub fn press(&mut self, color: Color, now: DateTime<Utc>) {
+        if self.get_running() == Some(color) {
+            let remaining = self.get_remaining(color, now) + self.increment[&color];
+            self.remaining.insert(color, remaining);
+        }
+
+        self.running = Some((color.opponent(), now));
+    }
+
+    /// Halve the specified player's time and remove their increment.
+    pub fn berserk(&mut self, color: Color) {
+        let remaining = self.remaining[&color] / 2;
+        self.remaining.insert(color, remaining);
+        self.increment.insert(color, TimeDelta::zero());
+        self.berserk.push(color);
+    }
+
+    pub fn is_berserk(&self, color: Color) -> bool {
+        self.berserk.contains(&color)
+    }
+
+    pub fn get_state(&self, now: DateTime<Utc>) -> ClockState {
+        ClockState {
+            remaining: [White, Black]
+                .into_iter()
+                .map(|color| (color, self.get_remaining(color, now).num_milliseconds()))
+                .collect(),
+            running: self.get_running(),
+            berserk: self.berserk.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::Color::{Black, White};
+    use crate::time_control::{Clock, TimeControl, TimeControlCategory};
+    use chrono::{TimeDelta, Utc};
+
+    #[test]
+    fn test_categories() {
+        let cases = [
+            ((60, 0), TimeControlCategory::Bullet),
+            ((120, 1), TimeControlCategory::Bullet),
+            ((180, 2), TimeControlCategory::Blitz),
+            ((600, 0), TimeControlCategory::Rapid),
+            ((1800, 20), TimeControlCategory::Classical),
+        ];
+
+        for ((initial, increment), category) in cases {
+            assert_eq!(TimeControl::new(initial, increment).category(), category);
+        }
+    }
+
+    #[test]
+    fn test_clock() {
+        let start = Utc::now();
+        let at = |seconds: i64| start + TimeDelta::seconds(seconds);
+        let mut clock = Clock::new(TimeControl::new(60, 2));
+
+        // The clock only starts once White has moved.
+        assert_eq!(clock.get_remaining(White, at(30)), TimeDelta::seconds(60));
+        clock.press(White, at(30));
+        assert_eq!(clock.get_running(), Some(Black));
+        assert_eq!(clock.get_remaining(White, at(30)), TimeDelta::seconds(60));
+
+        clock.press(Black, at(40));
+        assert_eq!(clock.get_remaining(Black, at(40)), TimeDelta::seconds(52));
+        assert_eq!(clock.get_remaining(White, at(45)), TimeDelta::seconds(55));
+
+        // Berserking halves the time left and removes the increment.
+        clock.berserk(Black);
+        assert!(clock.is_berserk(Black));
+        clock.press(White, at(45));
+        assert_eq!(clock.get_remaining(Black, at(45)), TimeDelta::seconds(26));
+        clock.press(Black, at(50));
+        assert_eq!(clock.get_remaining(Black, at(50)), TimeDelta::seconds(21));
+
+        assert!(!clock.is_flagged(White, at(100)));
+        assert!(clock.is_flagged(White, at(110)));
+        assert!(!clock.is_flagged(Black, at(110)));
+        assert_eq!(clock.get_remaining(White, at(200)), TimeDelta::zero());
+    }
+}
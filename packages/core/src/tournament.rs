@@ -0,0 +1,749 @@
+use crate::error::TournamentError;
+use crate::game::Color::{Black, White};
+use crate::game::{Color, GameOptions};
+use crate::game_manager::GameManager;
+use chrono::serde::ts_milliseconds;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// How the players in a tournament are paired.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "system", rename_all = "snake_case")]
+pub enum TournamentFormat {
+    /// Every player plays every other player once, following the Berger tables.
+    RoundRobin,
+
+    /// Players with similar scores are paired each round (following the Dutch system) for the
+    /// specified number of rounds.
+    Swiss { rounds: usize },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TournamentStatus {
+    /// Players may register, and no rounds have been played.
+    Registration,
+    InProgress,
+    Finished,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TournamentPlayer {
+    pub user_id: Uuid,
+
+    /// The player's rating when they registered, used to seed the tournament.
+    pub rating: f64,
+}
+
+/// A game in a round of a tournament, or a bye if there is no black player.
+#[derive(Clone, Debug, Serialize)]
+pub struct TournamentGame {
+    pub white: Uuid,
+
+    /// The black player, or [None] if the white player has a bye.
+    pub black: Option<Uuid>,
+
+    /// The ID of the game in the [GameManager] (or [None] for a bye).
+    pub game_id: Option<Uuid>,
+
+    /// The scores of the white and black players, once the game has finished. An aborted game
+    /// scores zero for both players.
+    pub result: Option<(f64, f64)>,
+}
+
+impl TournamentGame {
+    /// Get the specified player's opponent, or [None] if the player did not play in the game (or
+    /// had a bye).
+    pub fn get_opponent(&self, user_id: Uuid) -> Option<Uuid> {
+        match self.black {
+            Some(black) if self.white == user_id => Some(black),
+            Some(black) if black == user_id => Some(self.white),
+            _ => None,
+        }
+    }
+
+    /// Get the color the specified player played as, if they played in the game.
+    pub fn get_color(&self, user_id: Uuid) -> Option<Color> {
+        if self.white == user_id {
+            Some(White)
+        } else if self.black == Some(user_id) {
+            Some(Black)
+        } else {
+            None
+        }
+    }
+
+    /// Get the specified player's score in the game, if it has finished.
+    pub fn get_score(&self, user_id: Uuid) -> Option<f64> {
+        let (white, black) = self.result?;
+        match self.get_color(user_id)? {
+            White => Some(white),
+            Black => Some(black),
+        }
+    }
+}
+
+/// A player's position in the tournament standings.
+#[derive(Clone, Debug, Serialize)]
+pub struct Standing {
+    pub user_id: Uuid,
+
+    pub points: f64,
+
+    /// The sum of the points of each of the player's opponents.
+    pub buchholz: f64,
+
+    /// The sum of the points of each opponent the player beat, plus half the points of each
+    /// opponent they drew with.
+    pub sonneborn_berger: f64,
+}
+
+/// A seat in a newly created tournament game.
+#[derive(Clone, Debug)]
+pub struct SeatAssignment {
+    pub game_id: Uuid,
+    pub color: Color,
+    pub user_id: Uuid,
+
+    /// The token required to act on behalf of the seat.
+    pub token: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Tournament {
+    pub id: Uuid,
+
+    pub name: String,
+
+    /// The ID of the user that created the tournament, who is responsible for starting rounds.
+    pub organizer: Uuid,
+
+    pub format: TournamentFormat,
+
+    /// The settings that each game in the tournament is created with.
+    pub options: GameOptions,
+
+    pub status: TournamentStatus,
+
+    /// The registered players, in order of seeding once the tournament has started.
+    pub players: Vec<TournamentPlayer>,
+
+    /// The games in each round that has been paired.
+    pub rounds: Vec<Vec<TournamentGame>>,
+
+    /// The [DateTime] the tournament was created.
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl Tournament {
+    pub fn new(
+        id: Uuid,
+        name: String,
+        organizer: Uuid,
+        format: TournamentFormat,
+        options: GameOptions,
+    ) -> Tournament {
+        Tournament {
+            id,
+            name,
+            organizer,
+            format,
+            options,
+            status: TournamentStatus::Registration,
+            players: Vec::new(),
+            rounds: Vec::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Register a player for the tournament. Players can only register before the first round.
+    pub fn register(&mut self, user_id: Uuid, rating: f64) -> Result<(), TournamentError> {
+        if self.status != TournamentStatus::Registration {
+            return Err(TournamentError::RegistrationClosedError);
+        }
+
+        if self.players.iter().any(|player| player.user_id == user_id) {
+            return Err(TournamentError::AlreadyRegisteredError);
+        }
+
+        self.players.push(TournamentPlayer { user_id, rating });
+        Ok(())
+    }
+
+    /// Get the total number of rounds in the tournament.
+    pub fn get_round_count(&self) -> usize {
+        match self.format {
+            TournamentFormat::RoundRobin => {
+                let players = self.players.len();
+                players + players % 2 - 1
+            }
+            TournamentFormat::Swiss { rounds } => rounds,
+        }
+    }
+
+    /// Record the results of any games in the current round that have finished. The tournament is
+    /// finished once every game in the last round has a result.
+    pub fn update_results(&mut self, game_manager: &GameManager) {
+        let Some(round) = self.rounds.last_mut() else {
+            return;
+        };
+
+        for tournament_game in round.iter_mut().filter(|g| g.result.is_none()) {
+            let game = tournament_game
+                .game_id
+                .and_then(|game_id| game_manager.get_game(game_id));
+            let Some(result) = game.and_then(|game| game.lock().unwrap().get_result()) else {
+                continue;
+            };
+
+            tournament_game.result = Some((
+                result.score(White).unwrap_or(0.0),
+                result.score(Black).unwrap_or(0.0),
+            ));
+        }
+
+        if self.rounds.len() == self.get_round_count() && self.is_round_complete() {
+            self.status = TournamentStatus::Finished;
+        }
+    }
+
+    fn is_round_complete(&self) -> bool {
+        self.rounds
+            .last()
+            .is_none_or(|round| round.iter().all(|game| game.result.is_some()))
+    }
+
+    /// Pair the next round and create its games, returning the seats of the players in the new
+    /// games. The previous round must be complete.
+    pub fn start_next_round(
+        &mut self,
+        game_manager: &mut GameManager,
+    ) -> Result<Vec<SeatAssignment>, TournamentError> {
+        self.update_results(game_manager);
+
+        if self.status == TournamentStatus::Finished {
+            return Err(TournamentError::TournamentFinishedError);
+        }
+        if self.players.len() < 2 {
+            return Err(TournamentError::NotEnoughPlayersError);
+        }
+        if !self.is_round_complete() {
+            return Err(TournamentError::RoundInProgressError);
+        }
+
+        if self.status == TournamentStatus::Registration {
+            // Seed the players by rating.
+            self.players.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+            self.status = TournamentStatus::InProgress;
+        }
+
+        let pairings = match self.format {
+            TournamentFormat::RoundRobin => self.pair_round_robin(),
+            TournamentFormat::Swiss { .. } => self.pair_swiss(),
+        };
+
+        let mut round = Vec::new();
+        let mut seats = Vec::new();
+        for (white, black) in pairings {
+            let Some(black) = black else {
+                // Byes are worth a point in Swiss tournaments, but nothing in a round robin
+                // (where every player has the same number of byes).
+                let bye = match self.format {
+                    TournamentFormat::RoundRobin => 0.0,
+                    TournamentFormat::Swiss { .. } => 1.0,
+                };
+                round.push(TournamentGame {
+                    white,
+                    black: None,
+                    game_id: None,
+                    result: Some((bye, 0.0)),
+                });
+                continue;
+            };
+
+            let game_id = create_game(game_manager, &self.options, white, black, &mut seats);
+            round.push(TournamentGame {
+                white,
+                black: Some(black),
+                game_id: Some(game_id),
+                result: None,
+            });
+        }
+
+        self.rounds.push(round);
+        Ok(seats)
+    }
+
+    /// Pair the next round of a round robin using the Berger tables.
+    fn pair_round_robin(&self) -> Vec<(Uuid, Option<Uuid>)> {
+        // With an odd number of players, the player paired with the extra (last) player has a bye.
+        let players = self.players.len();
+        let n = players + players % 2;
+        let player = |number: usize| self.players.get(number - 1).map(|p| p.user_id);
+
+        // Map any number onto the players 1 to n - 1 (the last player is fixed).
+        let wrap = |number: isize| (number - 1).rem_euclid(n as isize - 1) as usize + 1;
+
+        let round = self.rounds.len() + 1;
+        let first = if round % 2 == 1 {
+            round.div_ceil(2)
+        } else {
+            round / 2 + n / 2
+        };
+
+        let mut pairings = Vec::new();
+        for board in 0..n / 2 {
+            let (white, black) = match board {
+                0 if round % 2 == 1 => (first, n),
+                0 => (n, first),
+                _ => (
+                    wrap(first as isize + board as isize),
+                    wrap(first as isize - board as isize),
+                ),
+            };
+
+            match (player(white), player(black)) {
+                (Some(white), Some(black)) => pairings.push((white, Some(black))),
+                (Some(player), None) | (None, Some(player)) => pairings.push((player, None)),
+                (None, None) => {}
+            }
+        }
+
+        pairings
+    }
+
+    /// Pair the next round of a Swiss tournament, following a simplified Dutch system: players
+    /// are ranked by points (then rating), and within each score group, the top half is paired
+    /// against the bottom half. Players never meet twice, and two players that are due the same
+    /// color are never paired, unless that is unavoidable.
+    fn pair_swiss(&self) -> Vec<(Uuid, Option<Uuid>)> {
+        let points: BTreeMap<Uuid, f64> = self
+            .get_standings()
+            .into_iter()
+            .map(|standing| (standing.user_id, standing.points))
+            .collect();
+
+        let mut ranked: Vec<Uuid> = self.players.iter().map(|p| p.user_id).collect();
+        ranked.sort_by(|a, b| points[b].total_cmp(&points[a]));
+
+        // The lowest ranked player that has not yet had a bye receives one.
+        let mut bye = None;
+        if ranked.len() % 2 == 1 {
+            let had_bye = |user_id: Uuid| {
+                self.rounds
+                    .iter()
+                    .flatten()
+                    .any(|game| game.white == user_id && game.black.is_none())
+            };
+            let index = ranked
+                .iter()
+                .rposition(|&user_id| !had_bye(user_id))
+                .unwrap_or(ranked.len() - 1);
+            bye = Some(ranked.remove(index));
+        }
+
+        let pairs = self
+            .pair_swiss_players(&ranked, &points, false)
+            .or_else(|| self.pair_swiss_players(&ranked, &points, true))
+            .expect("players can always be paired when the constraints are relaxed");
+
+        let mut pairings: Vec<(Uuid, Option<Uuid>)> = pairs
+            .into_iter()
+            .enumerate()
+            .map(
+                |(board, (higher, lower))| match self.allocate_color(higher, lower, board) {
+                    White => (higher, Some(lower)),
+                    Black => (lower, Some(higher)),
+                },
+            )
+            .collect();
+        pairings.extend(bye.map(|user_id| (user_id, None)));
+        pairings
+    }
+
+    /// Pair the ranked players, pairing the highest ranked player first and backtracking if the
+    /// rest of the players cannot then be paired. If `relaxed` is set, rematches and color
+    /// clashes are allowed.
+    fn pair_swiss_players(
+        &self,
+        ranked: &[Uuid],
+        points: &BTreeMap<Uuid, f64>,
+        relaxed: bool,
+    ) -> Option<Vec<(Uuid, Uuid)>> {
+        let Some((&top, rest)) = ranked.split_first() else {
+            return Some(Vec::new());
+        };
+
+        // The rest of the top player's score group, and the players in lower score groups.
+        let (group, lower): (Vec<Uuid>, Vec<Uuid>) = rest
+            .iter()
+            .partition(|&&user_id| points[&user_id] == points[&top]);
+
+        // Prefer the first player in the bottom half of the score group, then the rest of the
+        // bottom half, then the top half (from the bottom), then lower score groups.
+        let half = group.len().div_ceil(2);
+        let candidates = group[half.saturating_sub(1)..]
+            .iter()
+            .chain(group[..half.saturating_sub(1)].iter().rev())
+            .chain(lower.iter());
+
+        for &candidate in candidates {
+            let rematch = self.get_opponents(top).contains(&candidate);
+            let colors_clash = match (self.get_due_color(top), self.get_due_color(candidate)) {
+                (Some(color), Some(other)) => color == other,
+                _ => false,
+            };
+            if (rematch || colors_clash) && !relaxed {
+                continue;
+            }
+
+            let remaining: Vec<Uuid> = rest.iter().copied().filter(|&p| p != candidate).collect();
+            if let Some(mut pairs) = self.pair_swiss_players(&remaining, points, relaxed) {
+                pairs.insert(0, (top, candidate));
+                return Some(pairs);
+            }
+        }
+
+        None
+    }
+
+    /// Get the colors the specified player has played, in order.
+    fn get_colors(&self, user_id: Uuid) -> Vec<Color> {
+        self.rounds
+            .iter()
+            .flatten()
+            .filter(|game| game.black.is_some())
+            .filter_map(|game| game.get_color(user_id))
+            .collect()
+    }
+
+    /// Get the color a player must have in the next round, if any: a player may not have three
+    /// more games as one color than the other, or play the same color three times in a row.
+    fn get_due_color(&self, user_id: Uuid) -> Option<Color> {
+        let colors = self.get_colors(user_id);
+        let whites = colors.iter().filter(|&&color| color == White).count() as isize;
+        let difference = 2 * whites - colors.len() as isize;
+
+        match colors.as_slice() {
+            _ if difference >= 2 => Some(Black),
+            _ if difference <= -2 => Some(White),
+            [.., a, b] if a == b => Some(a.opponent()),
+            _ => None,
+        }
+    }
+
+    /// Decide the color of the higher ranked player of a pairing, giving priority to a player
+    /// that is due a color, then to the player that has had that color less often, then
+    /// alternating from the last color they played. In the first round, colors alternate between
+    /// boards.
+    fn allocate_color(&self, higher: Uuid, lower: Uuid, board: usize) -> Color {
+        if let Some(color) = self.get_due_color(higher) {
+            return color;
+        }
+        if let Some(color) = self.get_due_color(lower) {
+            return color.opponent();
+        }
+
+        let balance = |user_id: Uuid| {
+            self.get_colors(user_id)
+                .iter()
+                .map(|&color| if color == White { 1 } else { -1 })
+                .sum::<isize>()
+        };
+        match balance(higher).cmp(&balance(lower)) {
+            std::cmp::Ordering::Less => return White,
+            std::cmp::Ordering::Greater => return Black,
+            std::cmp::Ordering::Equal => {}
+        }
+
+        match self.get_colors(higher).last() {
+            Some(color) => color.opponent(),
+            None if board.is_multiple_of(2) => White,
+            None => Black,
+        }
+    }
+
+    /// Get the players the specified player has been paired against.
+    fn get_opponents(&self, user_id: Uuid) -> BTreeSet<Uuid> {
+        self.rounds
+            .iter()
+            .flatten()
+            .filter_map(|game| game.get_opponent(user_id))
+            .collect()
+    }
+
+    /// Get the standings, ordered by points, then Buchholz, then Sonneborn-Berger.
+    pub fn get_standings(&self) -> Vec<Standing> {
+        let games: Vec<&TournamentGame> = self.rounds.iter().flatten().collect();
+        let points: BTreeMap<Uuid, f64> = self
+            .players
+            .iter()
+            .map(|player| {
+                let points = games
+                    .iter()
+                    .filter_map(|game| game.get_score(player.user_id))
+                    .fold(0.0, |total, score| total + score);
+                (player.user_id, points)
+            })
+            .collect();
+
+        let mut standings: Vec<Standing> = self
+            .players
+            .iter()
+            .map(|player| {
+                let user_id = player.user_id;
+                let mut buchholz = 0.0;
+                let mut sonneborn_berger = 0.0;
+
+                for game in &games {
+                    let (Some(opponent), Some(score)) =
+                        (game.get_opponent(user_id), game.get_score(user_id))
+                    else {
+                        continue;
+                    };
+
+                    buchholz += points[&opponent];
+                    sonneborn_berger += score * points[&opponent];
+                }
+
+                Standing {
+                    user_id,
+                    points: points[&user_id],
+                    buchholz,
+                    sonneborn_berger,
+                }
+            })
+            .collect();
+
+        // Sorting is stable, so players that are tied remain in order of seeding.
+        standings.sort_by(|a, b| {
+            b.points
+                .total_cmp(&a.points)
+                .then(b.buchholz.total_cmp(&a.buchholz))
+                .then(b.sonneborn_berger.total_cmp(&a.sonneborn_berger))
+        });
+        standings
+    }
+}
+
+/// Create a game between two players, seating them and adding their seats to `seats`. Returns the
+/// ID of the new game.
+pub(crate) fn create_game(
+    game_manager: &mut GameManager,
+    options: &GameOptions,
+    white: Uuid,
+    black: Uuid,
+    seats: &mut Vec<SeatAssignment>,
+) -> Uuid {
+    let game = game_manager.new_game_with_options(options.clone());
+    let mut game = game.lock().unwrap();
+    let game_id = Uuid::parse_str(game.get_id().unwrap()).unwrap();
+
+    for (color, user_id) in [(White, white), (Black, black)] {
+        let token = game
+            .claim_seat(color, Some(user_id))
+            .expect("seats of a new game should be free");
+        seats.push(SeatAssignment {
+            game_id,
+            color,
+            user_id,
+            token,
+        });
+    }
+
+    game_id
+}
+
+pub struct TournamentManager {
+    tournaments: BTreeMap<Uuid, Arc<Mutex<Tournament>>>,
+}
+
+impl TournamentManager {
+    pub fn new() -> TournamentManager {
+        TournamentManager {
+            tournaments: BTreeMap::new(),
+        }
+    }
+
+    pub fn create_tournament(
+        &mut self,
+        name: String,
+        organizer: Uuid,
+        format: TournamentFormat,
+        options: GameOptions,
+    ) -> Arc<Mutex<Tournament>> {
+        let id = Uuid::new_v4();
+        let tournament = Tournament::new(id, name, organizer, format, options);
+        let tournament_ref = Arc::new(Mutex::new(tournament));
+
+        self.tournaments.insert(id, tournament_ref.clone());
+        tournament_ref
+    }
+
+    pub fn get_all_tournaments(&self) -> Vec<Arc<Mutex<Tournament>>> {
+        self.tournaments.values().cloned().collect()
+    }
+
+    pub fn get_tournament(&self, id: Uuid) -> Option<Arc<Mutex<Tournament>>> {
+        self.tournaments.get(&id).cloned()
+    }
+}
+
+impl Default for TournamentManager {
+    fn default() -> Self {
+        TournamentManager::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::Color::{Black, White};
+    use crate::game::GameOptions;
+    use crate::game_manager::GameManager;
+    use crate::tournament::{Tournament, TournamentFormat, TournamentStatus};
+    use std::collections::BTreeSet;
+    use uuid::Uuid;
+
+    fn tournament(format: TournamentFormat, players: usize) -> (Tournament, Vec<Uuid>) {
+        let mut tournament = Tournament::new(
+            Uuid::new_v4(),
+            "Club Championship".to_string(),
+            Uuid::new_v4(),
+            format,
+            GameOptions::default(),
+        );
+
+        // Register the players in reverse order of rating, to check that they are seeded.
+        let ids: Vec<Uuid> = (0..players).map(|_| Uuid::new_v4()).collect();
+        for (seed, id) in ids.iter().enumerate().rev() {
+            tournament.register(*id, 2000.0 - seed as f64).unwrap();
+        }
+        (tournament, ids)
+    }
+
+    /// Finish every game in the current round, with the higher seeded player winning.
+    fn play_round(tournament: &mut Tournament, game_manager: &mut GameManager, ids: &[Uuid]) {
+        for game in tournament.rounds.last().unwrap() {
+            let (Some(black), Some(game_id)) = (game.black, game.game_id) else {
+                continue;
+            };
+
+            let seed = |user_id: Uuid| ids.iter().position(|&id| id == user_id).unwrap();
+            let loser = if seed(game.white) < seed(black) {
+                Black
+            } else {
+                White
+            };
+            let game = game_manager.get_game(game_id).unwrap();
+            game.lock().unwrap().resign(loser).unwrap();
+        }
+        tournament.update_results(game_manager);
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let mut game_manager = GameManager::new();
+        let (mut tournament, ids) = tournament(TournamentFormat::RoundRobin, 4);
+
+        // The Berger tables for four players.
+        let expected = [[(1, 4), (2, 3)], [(4, 3), (1, 2)], [(2, 4), (3, 1)]];
+        for round in expected {
+            tournament.start_next_round(&mut game_manager).unwrap();
+            assert!(tournament.start_next_round(&mut game_manager).is_err());
+
+            let pairings: Vec<(Uuid, Option<Uuid>)> = tournament
+                .rounds
+                .last()
+                .unwrap()
+                .iter()
+                .map(|game| (game.white, game.black))
+                .collect();
+            let expected: Vec<(Uuid, Option<Uuid>)> = round
+                .iter()
+                .map(|&(white, black)| (ids[white - 1], Some(ids[black - 1])))
+                .collect();
+            assert_eq!(pairings, expected);
+
+            play_round(&mut tournament, &mut game_manager, &ids);
+        }
+
+        assert_eq!(tournament.status, TournamentStatus::Finished);
+        assert!(tournament.start_next_round(&mut game_manager).is_err());
+
+        let points: Vec<f64> = tournament
+            .get_standings()
+            .iter()
+            .map(|s| s.points)
+            .collect();
+        assert_eq!(points, vec![3.0, 2.0, 1.0, 0.0]);
+        assert_eq!(tournament.get_standings()[0].user_id, ids[0]);
+        // The winner beat players with 2, 1 and 0 points.
+        assert_eq!(tournament.get_standings()[0].sonneborn_berger, 3.0);
+        assert_eq!(tournament.get_standings()[0].buchholz, 3.0);
+    }
+
+    #[test]
+    fn test_round_robin_with_bye() {
+        let mut game_manager = GameManager::new();
+        let (mut tournament, ids) = tournament(TournamentFormat::RoundRobin, 5);
+        assert_eq!(tournament.get_round_count(), 5);
+
+        let mut pairs = BTreeSet::new();
+        for _ in 0..5 {
+            tournament.start_next_round(&mut game_manager).unwrap();
+            let round = tournament.rounds.last().unwrap();
+            assert_eq!(round.iter().filter(|game| game.black.is_none()).count(), 1);
+
+            for game in round {
+                if let Some(black) = game.black {
+                    assert!(pairs.insert(BTreeSet::from([game.white, black])));
+                }
+            }
+            play_round(&mut tournament, &mut game_manager, &ids);
+        }
+
+        // Every player has played every other player once.
+        assert_eq!(pairs.len(), 10);
+        assert_eq!(tournament.status, TournamentStatus::Finished);
+    }
+
+    #[test]
+    fn test_swiss() {
+        let mut game_manager = GameManager::new();
+        let (mut tournament, ids) = tournament(TournamentFormat::Swiss { rounds: 3 }, 7);
+        assert!(tournament.register(ids[0], 1500.0).is_err());
+
+        let mut pairs = BTreeSet::new();
+        let mut byes = BTreeSet::new();
+        for round in 0..3 {
+            tournament.start_next_round(&mut game_manager).unwrap();
+            assert!(tournament.register(Uuid::new_v4(), 1500.0).is_err());
+
+            for game in tournament.rounds.last().unwrap() {
+                match game.black {
+                    Some(black) => assert!(pairs.insert(BTreeSet::from([game.white, black]))),
+                    None => assert!(byes.insert(game.white)),
+                }
+            }
+
+            if round == 0 {
+                // The top half plays the bottom half, and the lowest seed has the bye.
+                let first = &tournament.rounds[0];
+                assert_eq!((first[0].white, first[0].black), (ids[0], Some(ids[3])));
+                assert_eq!((first[1].white, first[1].black), (ids[4], Some(ids[1])));
+                assert_eq!((first[3].white, first[3].black), (ids[6], None));
+            }
+
+            play_round(&mut tournament, &mut game_manager, &ids);
+        }
+
+        assert_eq!(tournament.status, TournamentStatus::Finished);
+        let standings = tournament.get_standings();
+        assert_eq!(standings[0].user_id, ids[0]);
+        assert_eq!(standings[0].points, 3.0);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::error::AccountError;
+use crate::game::Color::{Black, White};
+use crate::game::Game;
+use crate::rating::{CategoryRating, RatingSystem};
+use crate::time_control::TimeControlCategory;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::SaltString;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use chrono::serde::ts_milliseconds;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// The minimum number of characters in a password.
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// How long a session remains valid after logging in.
+const SESSION_DURATION: Duration = Duration::days(30);
+
+#[derive(Clone, Debug, Serialize)]
+pub struct User {
+    pub id: Uuid,
+
+    pub username: String,
+
+    /// The argon2 hash of the user's password (in PHC string format).
+    #[serde(skip)]
+    password_hash: String,
+
+    /// The [DateTime] the user registered.
+    #[serde(with = "ts_milliseconds")]
+    pub created_at: DateTime<Utc>,
+
+    /// The user's rating in each time control category they have played a rated game in.
+    pub ratings: BTreeMap<TimeControlCategory, CategoryRating>,
+}
+
+impl User {
+    /// Check the specified password against the user's.
+    pub fn verify_password(&self, password: &str) -> Result<(), AccountError> {
+        let password_hash =
+            PasswordHash::new(&self.password_hash).expect("stored password hash should be valid");
+        Argon2::default()
+            .verify_password(password.as_bytes(), &password_hash)
+            .map_err(|_| AccountError::InvalidCredentialsError)
+    }
+
+    /// Get the user's rating in the specified category (the default rating if they have not yet
+    /// played a rated game in it).
+    pub fn get_rating(&self, category: TimeControlCategory) -> CategoryRating {
+        self.ratings.get(&category).cloned().unwrap_or_default()
+    }
+}
+
+/// A password hashed with argon2 (in PHC string format), to register a user with.
+#[derive(Clone, Debug)]
+pub struct HashedPassword(String);
+
+struct Session {
+    user_id: Uuid,
+    expires_at: DateTime<Utc>,
+}
+
+pub struct UserManager {
+    users: BTreeMap<Uuid, User>,
+
+    /// Active sessions, by session token.
+    sessions: BTreeMap<String, Session>,
+
+    /// The system used to update users' ratings after rated games.
+    rating_system: RatingSystem,
+}
+
+impl UserManager {
+    pub fn new() -> UserManager {
+        UserManager {
+            users: BTreeMap::new(),
+            sessions: BTreeMap::new(),
+            rating_system: RatingSystem::default(),
+        }
+    }
+
+    /// Create a user manager that updates ratings with the specified [RatingSystem].
+    pub fn with_rating_system(rating_system: RatingSystem) -> UserManager {
+        UserManager {
+            rating_system,
+            ..UserManager::new()
+        }
+    }
+
+    /// Register a new user with the specified username and password.
+    pub fn register(&mut self, username: &str, password: &str) -> Result<User, AccountError> {
+        let password_hash = UserManager::hash_password(password)?;
+        self.add_user(username, password_hash)
+    }
+
+    /// Hash a password to register a user with. Hashing is deliberately slow, so it does not
+    /// need (and should not hold) access to the user manager.
+    pub fn hash_password(password: &str) -> Result<HashedPassword, AccountError> {
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(AccountError::WeakPasswordError);
+        }
+
+        let salt = SaltString::generate(&mut OsRng);
+        let password_hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|_| AccountError::WeakPasswordError)?
+            .to_string();
+        Ok(HashedPassword(password_hash))
+    }
+
+    /// Register a new user with the specified username and (hashed) password.
+    pub fn add_user(
+        &mut self,
+        username: &str,
+        password_hash: HashedPassword,
+    ) -> Result<User, AccountError> {
+        let username = username.trim();
+        if username.is_empty() || !username.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(AccountError::InvalidUsernameError);
+        }
+
+        if self.find_user_by_username(username).is_some() {
+            return Err(AccountError::UsernameTakenError);
+        }
+
+        let user = User {
+            id: Uuid::new_v4(),
+            username: username.to_string(),
+            password_hash: password_hash.0,
+            created_at: Utc::now(),
+            ratings: BTreeMap::new(),
+        };
+
+        self.users.insert(user.id, user.clone());
+        Ok(user)
+    }
+
+    /// Log in with the specified username and password, returning a new session token.
+    pub fn login(&mut self, username: &str, password: &str) -> Result<String, AccountError> {
+        let user = self
+            .find_user_by_username(username.trim())
+            .ok_or(AccountError::InvalidCredentialsError)?;
+        user.verify_password(password)?;
+        Ok(self.start_session(user.id))
+    }
+
+    /// Start a session for the specified user (whose password has been verified), returning the
+    /// session token.
+    pub fn start_session(&mut self, user_id: Uuid) -> String {
+        let token = Uuid::new_v4().to_string();
+        self.sessions.insert(
+            token.clone(),
+            Session {
+                user_id,
+                expires_at: Utc::now() + SESSION_DURATION,
+            },
+        );
+        token
+    }
+
+    /// End the session with the specified token.
+    pub fn logout(&mut self, token: &str) {
+        self.sessions.remove(token);
+    }
+
+    /// Get the user that the specified (unexpired) session token belongs to.
+    pub fn get_user_by_session(&self, token: &str) -> Option<&User> {
+        self.sessions
+            .get(token)
+            .filter(|session| session.expires_at > Utc::now())
+            .and_then(|session| self.users.get(&session.user_id))
+    }
+
+    pub fn get_user(&self, id: Uuid) -> Option<&User> {
+        self.users.get(&id)
+    }
+
+    /// Update the players' ratings with the result of a finished game. This has no effect unless
+    /// the game is rated, has a result that is not an abort and both seats were claimed by
+    /// (different) users. Returns whether the ratings were updated.
+    pub fn record_game_result(&mut self, game: &Game) -> bool {
+        let Some(result) = game.get_result().filter(|_| game.is_rated()) else {
+            return false;
+        };
+        let (Some(white_id), Some(black_id)) = (game.get_player(White), game.get_player(Black))
+        else {
+            return false;
+        };
+        let (Some(white_score), Some(black_score)) = (result.score(White), result.score(Black))
+        else {
+            return false;
+        };
+        if white_id == black_id {
+            return false;
+        }
+        let (Some(white), Some(black)) = (self.users.get(&white_id), self.users.get(&black_id))
+        else {
+            return false;
+        };
+
+        let category = game.get_category();
+        let white_rating = white.get_rating(category).current;
+        let black_rating = black.get_rating(category).current;
+
+        let game_id = game.get_id().cloned();
+        let updates = [
+            (
+                white_id,
+                self.rating_system
+                    .update(white_rating, black_rating, white_score),
+            ),
+            (
+                black_id,
+                self.rating_system
+                    .update(black_rating, white_rating, black_score),
+            ),
+        ];
+        for (user_id, rating) in updates {
+            if let Some(user) = self.users.get_mut(&user_id) {
+                user.ratings
+                    .entry(category)
+                    .or_default()
+                    .record(rating, game_id.clone());
+            }
+        }
+
+        true
+    }
+
+    /// Find a user by username (ignoring case).
+    pub fn find_user_by_username(&self, username: &str) -> Option<&User> {
+        self.users
+            .values()
+            .find(|user| user.username.eq_ignore_ascii_case(username))
+    }
+}
+
+impl Default for UserManager {
+    fn default() -> Self {
+        UserManager::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::Color::{Black, White};
+    use crate::game::{Game, GameOptions};
+    use crate::time_control::{TimeControl, TimeControlCategory};
+    use crate::user_manager::UserManager;
+
+    #[test]
+    fn test_register_and_login() {
+        let mut user_manager = UserManager::new();
+        let user = user_manager.register("magnus", "correct horse").unwrap();
+
+        assert!(user_manager.register("Magnus", "another password").is_err());
+        assert!(user_manager.register("hikaru", "short").is_err());
+        assert!(user_manager.register("", "correct horse").is_err());
+
+        assert!(user_manager.login("magnus", "wrong password").is_err());
+        assert!(user_manager.login("nobody", "correct horse").is_err());
+
+        let token = user_manager.login("MAGNUS", "correct horse").unwrap();
+        assert_eq!(
+            user_manager.get_user_by_session(&token).unwrap().id,
+            user.id
+        );
+
+        user_manager.logout(&token);
+        assert!(user_manager.get_user_by_session(&token).is_none());
+
+        // The password may be hashed and verified apart from the user manager.
+        let password_hash = UserManager::hash_password("battery staple").unwrap();
+        let hikaru = user_manager.add_user("hikaru", password_hash).unwrap();
+        assert!(hikaru.verify_password("correct horse").is_err());
+        hikaru.verify_password("battery staple").unwrap();
+        let token = user_manager.start_session(hikaru.id);
+        assert_eq!(
+            user_manager.get_user_by_session(&token).unwrap().id,
+            hikaru.id
+        );
+    }
+
+    #[test]
+    fn test_rated_game() {
+        let mut user_manager = UserManager::new();
+        let magnus = user_manager.register("magnus", "correct horse").unwrap();
+        let hikaru = user_manager.register("hikaru", "battery staple").unwrap();
+
+        let options = GameOptions {
+            rated: true,
+            time_control: Some(TimeControl::new(180, 2)),
+            ..GameOptions::default()
+        };
+        let mut game = Game::new_with_options(Some("game".to_string()), options);
+        game.claim_seat(White, Some(magnus.id)).unwrap();
+        game.claim_seat(Black, Some(hikaru.id)).unwrap();
+
+        assert!(!user_manager.record_game_result(&game));
+
+        game.resign(Black).unwrap();
+        assert!(user_manager.record_game_result(&game));
+
+        let magnus = user_manager.get_user(magnus.id).unwrap();
+        let hikaru = user_manager.get_user(hikaru.id).unwrap();
+        let blitz = magnus.get_rating(TimeControlCategory::Blitz);
+        assert!(blitz.current.rating > 1500.0);
+        assert_eq!(blitz.games, 1);
+        assert_eq!(blitz.history[0].game_id.as_deref(), Some("game"));
+        assert!(hikaru.get_rating(TimeControlCategory::Blitz).current.rating < 1500.0);
+        assert_eq!(magnus.get_rating(TimeControlCategory::Bullet).games, 0);
+
+        // Casual games do not affect ratings.
+        let mut game = Game::new();
+        game.claim_seat(White, Some(magnus.id)).unwrap();
+        game.claim_seat(Black, Some(hikaru.id)).unwrap();
+        game.resign(Black).unwrap();
+        assert!(!user_manager.record_game_result(&game));
+    }
+}
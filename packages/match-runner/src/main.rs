@@ -0,0 +1,450 @@
+mod openings;
+mod player;
+mod stats;
+
+use crate::openings::Opening;
+use crate::player::{EngineConfig, Player};
+use crate::stats::{MatchScore, Sprt, SprtStatus};
+use core::fen::Fen;
+use core::game::Color::{Black, White};
+use core::game::Game;
+use core::game::PieceKind::{King, Pawn};
+use core::pgn;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+
+const USAGE: &str = "\
+Usage: match-runner --engine <options> --engine <options> [flags]
+
+Plays a match between two engines and reports the result from the first engine's point of view.
+
+Engine options are comma-separated key=value pairs:
+    name=<name>          The name used in the report and in PGN
+    cmd=<command>        Run an external UCI engine (the built-in engine is used otherwise)
+    depth=<plies>        The depth to search to
+    movetime=<ms>        The time to search each move for (UCI engines only)
+    book=<file>          A Polyglot opening book to play from (built-in engine only)
+
+Flags:
+    --games <n>          The number of games to play (default 100)
+    --concurrency <n>    The number of games to play at once (default: the number of cores)
+    --openings <file>    An opening suite, with one FEN/EPD or sequence of SAN moves per line
+    --pgn <file>         Where to write the games (default match.pgn)
+    --sprt <options>     Stop once an SPRT has finished, with options elo0, elo1, alpha and
+                         beta (e.g., elo0=0,elo1=10,alpha=0.05,beta=0.05)
+    --max-plies <n>      Adjudicate games as drawn after this many plies (default 300)";
+
+/// The settings of a match.
+struct MatchConfig {
+    engines: [EngineConfig; 2],
+    games: usize,
+    concurrency: usize,
+    openings: Vec<Opening>,
+    pgn: String,
+    sprt: Option<Sprt>,
+    max_plies: usize,
+}
+
+impl MatchConfig {
+    fn from_args(args: &[String]) -> Result<MatchConfig, String> {
+        let mut engines = Vec::new();
+        let mut games = 100;
+        let mut concurrency = thread::available_parallelism().map_or(1, |n| n.get());
+        let mut openings = None;
+        let mut pgn = "match.pgn".to_string();
+        let mut sprt = None;
+        let mut max_plies = 300;
+
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {}", flag))
+            };
+            let number = |value: &String| {
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid value for {}: '{}'", flag, value))
+            };
+
+            match flag.as_str() {
+                "--engine" => engines.push(value()?.parse()?),
+                "--games" => games = number(value()?)?,
+                "--concurrency" => concurrency = number(value()?)?,
+                "--openings" => {
+                    let path = value()?;
+                    let suite = std::fs::read_to_string(path)
+                        .map_err(|e| format!("failed to read {}: {}", path, e))?;
+                    openings = Some(openings::parse_suite(&suite)?);
+                }
+                "--pgn" => pgn = value()?.clone(),
+                "--sprt" => sprt = Some(parse_sprt(value()?)?),
+                "--max-plies" => max_plies = number(value()?)?,
+                _ => return Err(format!("unknown flag: {}", flag)),
+            }
+        }
+
+        let engines: [EngineConfig; 2] = engines
+            .try_into()
+            .map_err(|_| "exactly two engines are required".to_string())?;
+        if games == 0 || concurrency == 0 {
+            return Err("--games and --concurrency must be positive".to_string());
+        }
+
+        Ok(MatchConfig {
+            engines,
+            games,
+            concurrency,
+            openings: openings.unwrap_or_else(openings::default_suite),
+            pgn,
+            sprt,
+            max_plies,
+        })
+    }
+}
+
+fn parse_sprt(options: &str) -> Result<Sprt, String> {
+    let mut sprt = Sprt::default();
+    for option in options.split(',') {
+        let (key, value) = option
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, got '{}'", option))?;
+        let value: f64 = value
+            .parse()
+            .map_err(|_| format!("invalid value for {}: '{}'", key, value))?;
+
+        match key {
+            "elo0" => sprt.elo0 = value,
+            "elo1" => sprt.elo1 = value,
+            "alpha" => sprt.alpha = value,
+            "beta" => sprt.beta = value,
+            _ => return Err(format!("unknown SPRT option: {}", key)),
+        }
+    }
+    Ok(sprt)
+}
+
+/// A finished game in the match.
+struct MatchGame {
+    /// The number of the game (from 1).
+    round: usize,
+
+    /// The index of the engine that played white.
+    white: usize,
+
+    game: Game,
+
+    /// Why the game was stopped before its end, if it was. Unfinished games do not count towards
+    /// the score.
+    unfinished: Option<&'static str>,
+}
+
+/// Play the game to its end, adjudicating it if it would otherwise go on indefinitely (or if a
+/// player cannot make a legal move). The core rules have no promotion or en passant, so the game
+/// is stopped unfinished (with the reason) once either would be possible.
+fn play_game(game: &Game, players: [&mut dyn Player; 2], max_plies: usize) -> Option<&'static str> {
+    let [white, black] = players;
+    let mut repetitions: HashMap<String, usize> = HashMap::new();
+
+    loop {
+        if can_capture_en_passant(game) {
+            return Some("en passant is not supported");
+        }
+        if game.get_result().is_some() {
+            break;
+        }
+
+        // Positions repeat when the pieces, the player to move and the castling and en passant
+        // rights are all the same.
+        let fen = Fen::from_game(game).to_string();
+        let position: Vec<&str> = fen.split(' ').take(4).collect();
+        let count = repetitions.entry(position.join(" ")).or_default();
+        *count += 1;
+
+        if *count >= 3
+            || game.get_halfmove_clock() >= 100
+            || game.get_move_count() >= max_plies
+            || is_insufficient_material(game)
+        {
+            game.adjudicate(None).unwrap();
+            break;
+        }
+
+        let color = game.get_current_move();
+        let player = match color {
+            White => &mut *white,
+            Black => &mut *black,
+        };
+
+        // A player that fails to make a legal move loses.
+        let mv = player.choose_move(game);
+        let Ok((position, new_position)) = mv else {
+            game.adjudicate(Some(color.opponent())).unwrap();
+            break;
+        };
+        if !game.get_legal_moves().contains(&(position, new_position)) {
+            game.adjudicate(Some(color.opponent())).unwrap();
+            break;
+        }
+        let piece = game.get_piece_by_position(&position).unwrap();
+        game.move_piece_at_position(&position, &new_position)
+            .unwrap();
+        if piece.kind != Pawn {
+            continue;
+        }
+
+        let last_rank = match color {
+            White => 0,
+            Black => 7,
+        };
+        if new_position.rank == last_rank {
+            return Some("promotion is not supported");
+        }
+    }
+    None
+}
+
+/// Check whether the player to move could capture a pawn that has just made a double step (or
+/// that the starting position says has), beside which one of their pawns stands.
+fn can_capture_en_passant(game: &Game) -> bool {
+    let Some(square) = Fen::from_game(game).en_passant else {
+        return false;
+    };
+    let color = game.get_current_move();
+    let rank = match color {
+        White => square.rank + 1,
+        Black => square.rank - 1,
+    };
+
+    let board = game.board.lock().unwrap();
+    [square.file.wrapping_sub(1), square.file + 1]
+        .into_iter()
+        .filter_map(|file| board[rank].get(file).copied().flatten())
+        .any(|piece| piece.kind == Pawn && piece.color == color)
+}
+
+/// Check whether neither player has any pieces left but their king.
+fn is_insufficient_material(game: &Game) -> bool {
+    let board = game.board.lock().unwrap();
+    let only_kings = board
+        .iter()
+        .flatten()
+        .flatten()
+        .all(|piece| piece.kind == King);
+    only_kings
+}
+
+/// Play games until there are none left (or the match is stopped), sending each finished game.
+fn run_worker(
+    config: &MatchConfig,
+    next_game: &AtomicUsize,
+    stop: &AtomicBool,
+    results: mpsc::Sender<Result<MatchGame, String>>,
+) {
+    let players: Result<Vec<Box<dyn Player>>, String> =
+        config.engines.iter().map(|engine| engine.start()).collect();
+    let mut players = match players {
+        Ok(players) => players,
+        Err(e) => {
+            let _ = results.send(Err(e));
+            return;
+        }
+    };
+
+    loop {
+        let index = next_game.fetch_add(1, Ordering::SeqCst);
+        if index >= config.games || stop.load(Ordering::SeqCst) {
+            return;
+        }
+
+        // Each opening is played twice, with the engines swapping colors.
+        let opening = &config.openings[(index / 2) % config.openings.len()];
+        let white = index % 2;
+
+        let game = match opening.start() {
+            Ok(game) => game,
+            Err(e) => {
+                let _ = results.send(Err(e));
+                return;
+            }
+        };
+
+        let (first, second) = players.split_at_mut(1);
+        let (white_player, black_player) = match white {
+            0 => (&mut first[0], &mut second[0]),
+            _ => (&mut second[0], &mut first[0]),
+        };
+        for player in [&mut *white_player, &mut *black_player] {
+            if let Err(e) = player.new_game() {
+                let _ = results.send(Err(e));
+                return;
+            }
+        }
+
+        let unfinished = play_game(
+            &game,
+            [white_player.as_mut(), black_player.as_mut()],
+            config.max_plies,
+        );
+
+        let round = index + 1;
+        let match_game = MatchGame {
+            round,
+            white,
+            game,
+            unfinished,
+        };
+        if results.send(Ok(match_game)).is_err() {
+            return;
+        }
+    }
+}
+
+fn run(config: MatchConfig) -> Result<(), String> {
+    let mut pgn_file =
+        File::create(&config.pgn).map_err(|e| format!("failed to create {}: {}", config.pgn, e))?;
+
+    let config = Arc::new(config);
+    let next_game = Arc::new(AtomicUsize::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+
+    let workers: Vec<_> = (0..config.concurrency.min(config.games))
+        .map(|_| {
+            let (config, next_game, stop) = (config.clone(), next_game.clone(), stop.clone());
+            let sender = sender.clone();
+            thread::spawn(move || run_worker(&config, &next_game, &stop, sender))
+        })
+        .collect();
+    drop(sender);
+
+    let names = [&config.engines[0].name, &config.engines[1].name];
+    let mut score = MatchScore::default();
+    let mut unfinished_games = 0;
+    let mut sprt_status = SprtStatus::Continue;
+    for result in receiver {
+        let MatchGame {
+            round,
+            white,
+            game,
+            unfinished,
+        } = match result {
+            Ok(game) => game,
+            Err(e) => {
+                stop.store(true, Ordering::SeqCst);
+                return Err(e);
+            }
+        };
+
+        let round_tag = round.to_string();
+        let tags = [
+            ("Event", "Engine match"),
+            ("Round", round_tag.as_str()),
+            ("White", names[white].as_str()),
+            ("Black", names[1 - white].as_str()),
+        ];
+        writeln!(pgn_file, "{}", pgn::write_game_with_tags(&game, &tags))
+            .map_err(|e| e.to_string())?;
+
+        if let Some(reason) = unfinished {
+            unfinished_games += 1;
+            println!(
+                "Game {} ({} vs {}): unfinished, as {}",
+                round,
+                names[white],
+                names[1 - white],
+                reason
+            );
+            continue;
+        }
+
+        let result = game.get_result().unwrap();
+        let first_color = if white == 0 { White } else { Black };
+        score.record(result.score(first_color).unwrap());
+        println!(
+            "Game {} ({} vs {}): {} {:?}",
+            round,
+            names[white],
+            names[1 - white],
+            result.to_pgn(),
+            result.termination
+        );
+        print_score(&score, names);
+
+        if let Some(sprt) = &config.sprt {
+            sprt_status = sprt.status(&score);
+            if sprt_status != SprtStatus::Continue {
+                stop.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
+    for worker in workers {
+        worker.join().map_err(|_| "a worker panicked".to_string())?;
+    }
+
+    println!();
+    println!(
+        "Finished match: {} games ({} unfinished)",
+        score.games(),
+        unfinished_games
+    );
+    print_score(&score, names);
+    if let Some(elo) = score.elo() {
+        println!("Elo difference: {:.1} +/- {:.1}", elo.0, elo.1);
+    }
+    if let Some(sprt) = &config.sprt {
+        let (lower, upper) = sprt.bounds();
+        println!(
+            "SPRT: llr {:.2} ({:.2}, {:.2}) [{}, {}] {}",
+            sprt.llr(&score),
+            lower,
+            upper,
+            sprt.elo0,
+            sprt.elo1,
+            match sprt_status {
+                SprtStatus::Pass => "- H1 accepted (pass)",
+                SprtStatus::Fail => "- H0 accepted (fail)",
+                SprtStatus::Continue => "- inconclusive",
+            }
+        );
+    }
+    println!("Games written to {}", config.pgn);
+    Ok(())
+}
+
+fn print_score(score: &MatchScore, names: [&String; 2]) {
+    println!(
+        "Score of {} vs {}: {} - {} - {} [{:.3}] {}",
+        names[0],
+        names[1],
+        score.wins,
+        score.losses,
+        score.draws,
+        score.score(),
+        score.games()
+    );
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let result = MatchConfig::from_args(&args).and_then(run);
+    match result {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use core::fen::Fen;
+use core::game::{Game, GameOptions};
+use core::pgn;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The openings played when no opening suite is supplied.
+const DEFAULT_OPENINGS: [&str; 8] = [
+    "e4 e5 Nf3 Nc6 Bb5",
+    "e4 c5 Nf3 d6",
+    "e4 e6 d4 d5",
+    "e4 c6 d4 d5",
+    "d4 d5 c4 e6",
+    "d4 Nf6 c4 g6",
+    "c4 e5 Nc3 Nf6",
+    "Nf3 d5 g3 Nf6",
+];
+
+/// The position a game in a match starts from: a FEN, followed by any moves in SAN.
+#[derive(Clone, Debug)]
+pub struct Opening {
+    pub fen: Option<Fen>,
+    pub moves: Vec<String>,
+}
+
+impl Opening {
+    /// Create a game that has reached the end of the opening.
+    pub fn start(&self) -> Result<Game, String> {
+        let options = GameOptions {
+            starting_fen: self.fen.clone(),
+            ..GameOptions::default()
+        };
+
+        let game = Game::new_with_options(None, options);
+        for san in &self.moves {
+            let (position, new_position) = pgn::from_san(&game, san)
+                .ok_or_else(|| format!("illegal move in opening '{}': {}", self, san))?;
+            game.move_piece_at_position(&position, &new_position)
+                .map_err(|e| format!("illegal move in opening '{}': {:?}", self, e))?;
+        }
+        Ok(game)
+    }
+}
+
+impl FromStr for Opening {
+    type Err = String;
+
+    /// Parse an opening: either a FEN (or EPD, whose operations are ignored), or a sequence of
+    /// moves in SAN (move numbers such as `1.` are skipped).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('/') {
+            let fields: Vec<&str> = s.split_whitespace().take(6).collect();
+            let fen = match Fen::from_str(&fields.join(" ")) {
+                Ok(fen) => fen,
+                Err(_) if fields.len() > 4 => {
+                    Fen::from_str(&fields[..4].join(" ")).map_err(|e| e.to_string())?
+                }
+                Err(e) => return Err(e.to_string()),
+            };
+
+            return Ok(Opening {
+                fen: Some(fen),
+                moves: Vec::new(),
+            });
+        }
+
+        let moves = s
+            .split_whitespace()
+            .map(|token| token.rsplit('.').next().unwrap())
+            .filter(|san| !san.is_empty())
+            .map(str::to_string)
+            .collect();
+        Ok(Opening { fen: None, moves })
+    }
+}
+
+impl Display for Opening {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.fen {
+            Some(fen) => write!(f, "{}", fen),
+            None => write!(f, "{}", self.moves.join(" ")),
+        }
+    }
+}
+
+/// Parse an opening suite, with one opening per line. Blank lines and lines starting with `#` are
+/// ignored.
+pub fn parse_suite(suite: &str) -> Result<Vec<Opening>, String> {
+    let openings = suite
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(Opening::from_str)
+        .collect::<Result<Vec<Opening>, String>>()?;
+
+    for opening in &openings {
+        opening.start()?;
+    }
+    if openings.is_empty() {
+        return Err("the opening suite is empty".to_string());
+    }
+    Ok(openings)
+}
+
+pub fn default_suite() -> Vec<Opening> {
+    parse_suite(&DEFAULT_OPENINGS.join("\n")).expect("the default openings should be legal")
+}
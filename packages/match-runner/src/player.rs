@@ -0,0 +1,220 @@
+use core::book::Book;
+use core::engine::Engine;
+use core::fen::Fen;
+use core::game::Game;
+use core::moves::Position;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// How an engine taking part in a match is configured, parsed from comma-separated `key=value`
+/// options (e.g., `name=base,depth=3` or `name=sf,cmd=stockfish,movetime=100`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct EngineConfig {
+    pub name: String,
+
+    /// The command used to start an external UCI engine, or [None] for the built-in engine.
+    pub command: Option<String>,
+
+    /// The depth to search to.
+    pub depth: Option<u32>,
+
+    /// The time to search each move for, in milliseconds (UCI engines only).
+    pub movetime: Option<u64>,
+
+    /// The path of a Polyglot opening book to play from (built-in engine only).
+    pub book: Option<String>,
+}
+
+impl FromStr for EngineConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = EngineConfig {
+            name: String::new(),
+            command: None,
+            depth: None,
+            movetime: None,
+            book: None,
+        };
+
+        for option in s.split(',') {
+            let (key, value) = option
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, got '{}'", option))?;
+            let invalid = |_| format!("invalid value for {}: '{}'", key, value);
+
+            match key {
+                "name" => config.name = value.to_string(),
+                "cmd" => config.command = Some(value.to_string()),
+                "depth" => config.depth = Some(value.parse().map_err(invalid)?),
+                "movetime" => config.movetime = Some(value.parse().map_err(invalid)?),
+                "book" => config.book = Some(value.to_string()),
+                _ => return Err(format!("unknown engine option: {}", key)),
+            }
+        }
+
+        if config.command.is_none() && config.movetime.is_some() {
+            return Err("movetime is only supported for UCI engines".to_string());
+        }
+        if config.command.is_some() && config.book.is_some() {
+            return Err("book is only supported for the built-in engine".to_string());
+        }
+        if config.name.is_empty() {
+            config.name = match &config.command {
+                Some(command) => command.clone(),
+                None => format!(
+                    "builtin-d{}",
+                    config.depth.unwrap_or(Engine::default().depth)
+                ),
+            };
+        }
+        Ok(config)
+    }
+}
+
+impl EngineConfig {
+    /// Start an instance of the engine.
+    pub fn start(&self) -> Result<Box<dyn Player>, String> {
+        match &self.command {
+            Some(command) => Ok(Box::new(UciEngine::start(command, self)?)),
+            None => {
+                let mut engine: Engine = self.depth.map(Engine::new).unwrap_or_default();
+                if let Some(path) = &self.book {
+                    let book = Book::open(path).map_err(|e| format!("{}: {}", path, e))?;
+                    engine.book = Some(Arc::new(book));
+                }
+                Ok(Box::new(BuiltinEngine { engine }))
+            }
+        }
+    }
+}
+
+/// An engine playing in a match.
+pub trait Player {
+    /// Prepare for a new game.
+    fn new_game(&mut self) -> Result<(), String>;
+
+    /// Choose a move for the player to move in the game.
+    fn choose_move(&mut self, game: &Game) -> Result<(Position, Position), String>;
+}
+
+struct BuiltinEngine {
+    engine: Engine,
+}
+
+impl Player for BuiltinEngine {
+    fn new_game(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Result<(Position, Position), String> {
+        self.engine
+            .best_move(game)
+            .ok_or_else(|| "no legal moves".to_string())
+    }
+}
+
+/// An external engine speaking the Universal Chess Interface (UCI) over its standard input and
+/// output.
+struct UciEngine {
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+
+    /// The `go` command sent to search each move.
+    go: String,
+}
+
+impl UciEngine {
+    fn start(command: &str, config: &EngineConfig) -> Result<UciEngine, String> {
+        let mut process = Command::new(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("failed to start {}: {}", command, e))?;
+
+        let mut engine = UciEngine {
+            stdin: process.stdin.take().unwrap(),
+            stdout: BufReader::new(process.stdout.take().unwrap()),
+            process,
+            go: match (config.depth, config.movetime) {
+                (Some(depth), _) => format!("go depth {}", depth),
+                (None, Some(movetime)) => format!("go movetime {}", movetime),
+                (None, None) => "go movetime 100".to_string(),
+            },
+        };
+
+        engine.send("uci")?;
+        engine.wait_for("uciok")?;
+        Ok(engine)
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), String> {
+        writeln!(self.stdin, "{}", command).map_err(|e| e.to_string())
+    }
+
+    /// Read lines from the engine until one starts with `prefix`, and return it.
+    fn wait_for(&mut self, prefix: &str) -> Result<String, String> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = self
+                .stdout
+                .read_line(&mut line)
+                .map_err(|e| e.to_string())?;
+            if read == 0 {
+                return Err("the engine exited unexpectedly".to_string());
+            }
+
+            if line.starts_with(prefix) {
+                return Ok(line.trim().to_string());
+            }
+        }
+    }
+
+    /// Get the FEN of the game's current position to send to the engine.
+    fn get_fen(game: &Game) -> String {
+        Fen::from_game(game).to_string()
+    }
+}
+
+impl Player for UciEngine {
+    fn new_game(&mut self) -> Result<(), String> {
+        self.send("ucinewgame")?;
+        self.send("isready")?;
+        self.wait_for("readyok").map(|_| ())
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Result<(Position, Position), String> {
+        self.send(&format!("position fen {}", UciEngine::get_fen(game)))?;
+        let go = self.go.clone();
+        self.send(&go)?;
+
+        // e.g., `bestmove e2e4 ponder e7e5`. Any promotion suffix is ignored.
+        let line = self.wait_for("bestmove")?;
+        let mv = line.split_whitespace().nth(1).unwrap_or_default();
+        let parse = |square: Option<&str>| {
+            square
+                .and_then(|square| Position::from_str(square).ok())
+                .ok_or_else(|| format!("invalid move from engine: '{}'", mv))
+        };
+        let (position, new_position) = (parse(mv.get(0..2))?, parse(mv.get(2..4))?);
+
+        // Castling is sent as the king's move (e.g., `e1g1`), but played as the king moving onto
+        // the rook.
+        let board = *game.board.lock().unwrap();
+        let new_position =
+            Game::get_castling_rook(&board, &position, &new_position).unwrap_or(new_position);
+        Ok((position, new_position))
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.process.wait();
+    }
+}
@@ -0,0 +1,190 @@
+use core::rating::expected_elo_score;
+
+/// The z-score of a two-sided 95% confidence interval.
+const Z_95: f64 = 1.959964;
+
+/// The results of a match from the point of view of the first engine.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MatchScore {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl MatchScore {
+    /// Record a game in which the first engine scored `score` (1, 0.5 or 0).
+    pub fn record(&mut self, score: f64) {
+        if score == 1.0 {
+            self.wins += 1;
+        } else if score == 0.5 {
+            self.draws += 1;
+        } else {
+            self.losses += 1;
+        }
+    }
+
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Get the first engine's average score per game.
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+
+    /// Get the variance of the first engine's score in a single game.
+    fn variance(&self) -> f64 {
+        let score = self.score();
+        let games = self.games() as f64;
+        (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / games
+    }
+
+    /// Get the Elo difference implied by the match score, and the margin of its 95% confidence
+    /// interval. Returns [None] until the score is neither 0 nor 1 (for which the difference is
+    /// unbounded).
+    pub fn elo(&self) -> Option<(f64, f64)> {
+        if self.games() == 0 {
+            return None;
+        }
+
+        let score = self.score();
+        if score <= 0.0 || score >= 1.0 {
+            return None;
+        }
+
+        let error = (self.variance() / self.games() as f64).sqrt();
+        let lower = elo_difference((score - Z_95 * error).max(f64::EPSILON));
+        let upper = elo_difference((score + Z_95 * error).min(1.0 - f64::EPSILON));
+        Some((elo_difference(score), (upper - lower) / 2.0))
+    }
+}
+
+/// Get the Elo difference at which the stronger player is expected to score `score` per game.
+pub fn elo_difference(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// The outcome of a sequential probability ratio test.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SprtStatus {
+    /// More games are needed.
+    Continue,
+
+    /// The first engine is stronger by at least `elo1` (H1 accepted).
+    Pass,
+
+    /// The first engine is not stronger by `elo1`; it is at most `elo0` stronger (H0 accepted).
+    Fail,
+}
+
+/// A sequential probability ratio test of whether the first engine is `elo1` stronger (H1)
+/// rather than `elo0` stronger (H0), with the specified false positive and false negative rates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Sprt {
+            elo0: 0.0,
+            elo1: 10.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+impl Sprt {
+    /// Get the log-likelihood ratios at which H0 and H1 are accepted.
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    /// Get the log-likelihood ratio of H1 to H0 given the match score, using the normal
+    /// approximation to the distribution of the score per game.
+    pub fn llr(&self, score: &MatchScore) -> f64 {
+        if score.games() == 0 {
+            return 0.0;
+        }
+
+        let variance = score.variance();
+        if variance == 0.0 {
+            return 0.0;
+        }
+
+        let s0 = expected_elo_score(self.elo0, 0.0);
+        let s1 = expected_elo_score(self.elo1, 0.0);
+        let games = score.games() as f64;
+        let total = score.score() * games;
+        (s1 - s0) * (2.0 * total - (s0 + s1) * games) / (2.0 * variance)
+    }
+
+    pub fn status(&self, score: &MatchScore) -> SprtStatus {
+        let llr = self.llr(score);
+        let (lower, upper) = self.bounds();
+        if llr >= upper {
+            SprtStatus::Pass
+        } else if llr <= lower {
+            SprtStatus::Fail
+        } else {
+            SprtStatus::Continue
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::stats::{elo_difference, MatchScore, Sprt, SprtStatus};
+
+    fn score(wins: usize, draws: usize, losses: usize) -> MatchScore {
+        MatchScore {
+            wins,
+            draws,
+            losses,
+        }
+    }
+
+    #[test]
+    fn test_elo() {
+        assert_eq!(elo_difference(0.5), 0.0);
+        assert!((elo_difference(0.75) - 190.85).abs() < 0.01);
+        assert!((elo_difference(0.25) + 190.85).abs() < 0.01);
+
+        assert_eq!(score(0, 0, 0).elo(), None);
+        assert_eq!(score(10, 0, 0).elo(), None);
+
+        let (elo, margin) = score(30, 40, 30).elo().unwrap();
+        assert_eq!(elo, 0.0);
+        assert!((margin - 53.2).abs() < 0.1, "{}", margin);
+
+        // More games narrow the confidence interval.
+        let (elo, wide) = score(60, 20, 20).elo().unwrap();
+        let (_, narrow) = score(600, 200, 200).elo().unwrap();
+        assert!((elo - 147.19).abs() < 0.01, "{}", elo);
+        assert!(narrow < wide / 3.0);
+    }
+
+    #[test]
+    fn test_sprt() {
+        let sprt = Sprt::default();
+        let (lower, upper) = sprt.bounds();
+        assert!((lower + 2.944).abs() < 0.001);
+        assert!((upper - 2.944).abs() < 0.001);
+
+        assert_eq!(sprt.status(&score(0, 0, 0)), SprtStatus::Continue);
+        assert_eq!(sprt.status(&score(12, 10, 8)), SprtStatus::Continue);
+        assert_eq!(sprt.status(&score(600, 200, 200)), SprtStatus::Pass);
+        assert_eq!(sprt.status(&score(200, 200, 600)), SprtStatus::Fail);
+        assert_eq!(sprt.status(&score(1000, 2000, 1000)), SprtStatus::Fail);
+    }
+}
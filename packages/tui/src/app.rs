@@ -0,0 +1,384 @@
+use core::engine::Engine;
+use core::fen::Fen;
+use core::game::Color::{Black, White};
+use core::game::{Color, Game, GameOptions};
+use core::moves::Position;
+use core::pgn;
+use crossterm::event::KeyCode;
+use std::str::FromStr;
+
+/// A file name being entered by the player, and what to do with it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Prompt {
+    Save(String),
+    Load(String),
+}
+
+/// The state of the terminal UI: the game being played, and the player's cursor and selection.
+pub struct App {
+    pub game: Game,
+
+    /// The square the cursor is on.
+    pub cursor: Position,
+
+    /// The square of the piece the player has selected to move, if any.
+    pub selected: Option<Position>,
+
+    /// Whether the board is shown from Black's side.
+    pub flipped: bool,
+
+    pub engine: Engine,
+
+    /// The player the engine plays as, or [None] if both players are human.
+    pub engine_color: Option<Color>,
+
+    pub prompt: Option<Prompt>,
+
+    /// A message shown below the board (e.g., why a file could not be loaded).
+    pub message: Option<String>,
+
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new(game: Game, engine: Engine, engine_color: Option<Color>) -> App {
+        App {
+            game,
+            cursor: Position::new(6, 4),
+            selected: None,
+            flipped: engine_color == Some(White),
+            engine,
+            engine_color,
+            prompt: None,
+            message: None,
+            quit: false,
+        }
+    }
+
+    /// Get the squares the selected piece can move to: those returned by `get_valid_moves` that
+    /// do not leave the player's king in check.
+    pub fn get_targets(&self) -> Vec<Position> {
+        let Some(selected) = self.selected else {
+            return Vec::new();
+        };
+
+        self.game
+            .get_legal_moves()
+            .into_iter()
+            .filter(|(position, _)| *position == selected)
+            .map(|(_, new_position)| new_position)
+            .collect()
+    }
+
+    /// Check whether the engine should make the next move.
+    pub fn is_engine_turn(&self) -> bool {
+        self.game.get_result().is_none() && self.engine_color == Some(self.game.get_current_move())
+    }
+
+    /// Make the engine's move.
+    pub fn play_engine_move(&mut self) {
+        if let Some((position, new_position)) = self.engine.best_move(&self.game) {
+            self.play_move(&position, &new_position);
+        }
+    }
+
+    fn play_move(&mut self, position: &Position, new_position: &Position) {
+        self.selected = None;
+        if let Err(e) = self.game.move_piece_at_position(position, new_position) {
+            self.message = Some(format!("Cannot move: {}", e));
+        }
+    }
+
+    /// Move the cursor by the specified number of squares, as seen on screen (i.e., up is towards
+    /// the top of the board as drawn).
+    pub fn move_cursor(&mut self, up: i32, right: i32) {
+        let (up, right) = if self.flipped {
+            (-up, -right)
+        } else {
+            (up, right)
+        };
+
+        let rank = (self.cursor.rank as i32 - up).clamp(0, 7);
+        let last_file = self.game.board.lock().unwrap().files() as i32 - 1;
+        let file = (self.cursor.file as i32 + right).clamp(0, last_file);
+        self.cursor = Position::new(rank as usize, file as usize);
+    }
+
+    /// Select the piece under the cursor, or move the selected piece to the cursor.
+    pub fn select(&mut self) {
+        if let Some(selected) = self.selected {
+            if self.get_targets().contains(&self.cursor) {
+                self.play_move(&selected, &self.cursor.clone());
+                return;
+            }
+        }
+
+        let piece = self.game.get_piece_by_position(&self.cursor);
+        self.selected = match piece {
+            Some(piece)
+                if piece.color == self.game.get_current_move()
+                    && self.selected != Some(self.cursor) =>
+            {
+                Some(self.cursor)
+            }
+            _ => None,
+        };
+    }
+
+    /// Take back the last move. When playing the engine, its reply is taken back too, so that it
+    /// is the player's move again.
+    pub fn undo(&mut self) {
+        self.selected = None;
+        let mut plies = self.game.get_move_count();
+        if plies == 0 {
+            self.message = Some("There are no moves to undo".to_string());
+            return;
+        }
+
+        plies -= 1;
+        let game = self.game.fork(None, plies).unwrap();
+        if self.engine_color == Some(game.get_current_move()) && plies > 0 {
+            self.game = self.game.fork(None, plies - 1).unwrap();
+        } else {
+            self.game = game;
+        }
+    }
+
+    pub fn new_game(&mut self) {
+        self.selected = None;
+        self.game = Game::new();
+    }
+
+    /// Save the game to a file: as FEN if the file name ends in `.fen`, and as PGN otherwise.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let contents = if path.ends_with(".fen") {
+            format!("{}\n", Fen::from_game(&self.game))
+        } else {
+            pgn::write_game(&self.game)
+        };
+        std::fs::write(path, contents).map_err(|e| format!("Cannot save {}: {}", path, e))
+    }
+
+    /// Load a game from a file containing either a FEN or a PGN game.
+    pub fn load(&mut self, path: &str) -> Result<(), String> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| format!("Cannot load {}: {}", path, e))?;
+        self.game = read_game(&contents).map_err(|e| format!("Cannot load {}: {}", path, e))?;
+        self.selected = None;
+        Ok(())
+    }
+
+    /// Get the moves of the game in SAN, with one line per move number (e.g., `1. e4 e5`).
+    pub fn get_move_list(&self) -> Vec<String> {
+        let replay = Game::new_with_options(None, self.game.get_options().clone());
+        let mut move_number = Fen::from_game(&replay).fullmove_number;
+
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for mv in self.game.get_moves() {
+            let san = pgn::move_to_san(&replay, &mv);
+            match replay.get_current_move() {
+                White => line = format!("{:>3}. {:<8}", move_number, san),
+                Black if line.is_empty() => {
+                    line = format!("{:>3}. {:<8}{}", move_number, "...", san)
+                }
+                Black => line.push_str(&san),
+            }
+
+            if replay.get_current_move() == Black {
+                lines.push(std::mem::take(&mut line).trim_end().to_string());
+                move_number += 1;
+            }
+            replay.play(&mv).unwrap();
+        }
+        if !line.is_empty() {
+            lines.push(line.trim_end().to_string());
+        }
+        lines
+    }
+
+    /// Describe the state of the game: whose move it is, check or checkmate, and the result.
+    pub fn get_status(&self) -> String {
+        let name = |color: Color| match color {
+            White => "White",
+            Black => "Black",
+        };
+
+        if let Some(result) = self.game.get_result() {
+            let outcome = match result.winner {
+                Some(winner) => format!("{} wins", name(winner)),
+                None => "Draw".to_string(),
+            };
+            return format!("{} ({:?})", outcome, result.termination);
+        }
+
+        let color = self.game.get_current_move();
+        let player = if self.engine_color == Some(color) {
+            "engine"
+        } else {
+            "player"
+        };
+        let check = if self.game.is_player_in_check(color) {
+            ", check!"
+        } else {
+            ""
+        };
+        format!("{} to move ({}{})", name(color), player, check)
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        if self.prompt.is_some() {
+            self.handle_prompt_key(key);
+            return;
+        }
+
+        self.message = None;
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(1, 0),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(-1, 0),
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(0, -1),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(0, 1),
+            KeyCode::Enter | KeyCode::Char(' ') => self.select(),
+            KeyCode::Esc => self.selected = None,
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('f') => self.flipped = !self.flipped,
+            KeyCode::Char('n') => self.new_game(),
+            KeyCode::Char('s') => self.prompt = Some(Prompt::Save(String::new())),
+            KeyCode::Char('o') => self.prompt = Some(Prompt::Load(String::new())),
+            KeyCode::Char('e') => {
+                // Cycle between playing against the engine as White, as Black, and hot-seat.
+                self.engine_color = match self.engine_color {
+                    None => Some(Black),
+                    Some(Black) => Some(White),
+                    Some(White) => None,
+                };
+            }
+            KeyCode::Char('q') => self.quit = true,
+            _ => {}
+        }
+    }
+
+    fn handle_prompt_key(&mut self, key: KeyCode) {
+        let Some(prompt) = &mut self.prompt else {
+            return;
+        };
+        let (Prompt::Save(path) | Prompt::Load(path)) = prompt;
+
+        match key {
+            KeyCode::Char(c) => path.push(c),
+            KeyCode::Backspace => {
+                path.pop();
+            }
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => {
+                let result = match self.prompt.take().unwrap() {
+                    Prompt::Save(path) => self.save(&path).map(|_| format!("Saved {}", path)),
+                    Prompt::Load(path) => self.load(&path).map(|_| format!("Loaded {}", path)),
+                };
+                self.message = Some(result.unwrap_or_else(|e| e));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Read a game from either a FEN or a PGN game.
+pub fn read_game(contents: &str) -> Result<Game, String> {
+    if let Ok(fen) = Fen::from_str(contents.trim()) {
+        let options = GameOptions {
+            starting_fen: Some(fen),
+            ..GameOptions::default()
+        };
+        return Ok(Game::new_with_options(None, options));
+    }
+
+    pgn::read_game(contents, None).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::app::App;
+    use core::engine::Engine;
+    use core::game::Color::Black;
+    use core::game::Game;
+    use core::moves::Position;
+    use crossterm::event::KeyCode;
+    use std::str::FromStr;
+
+    fn position(s: &str) -> Position {
+        Position::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_select_and_undo() {
+        let mut app = App::new(Game::new(), Engine::new(1), None);
+        assert_eq!(app.cursor, position("e2"));
+
+        // Selecting a pawn highlights its moves, and selecting a target moves it.
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.selected, Some(position("e2")));
+        let mut targets = app.get_targets();
+        targets.sort_by_key(|target| target.rank);
+        assert_eq!(targets, vec![position("e4"), position("e3")]);
+
+        app.handle_key(KeyCode::Up);
+        app.handle_key(KeyCode::Up);
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.game.get_move_count(), 1);
+        assert_eq!(app.selected, None);
+
+        // Only the player to move can select their pieces.
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.selected, None);
+
+        app.handle_key(KeyCode::Char('u'));
+        assert_eq!(app.game.get_move_count(), 0);
+
+        // Against the engine, undo takes back the engine's reply as well.
+        app.engine_color = Some(Black);
+        app.cursor = position("e2");
+        app.select();
+        app.cursor = position("e4");
+        app.select();
+        assert!(app.is_engine_turn());
+        app.play_engine_move();
+        assert_eq!(app.game.get_move_count(), 2);
+        assert_eq!(app.get_move_list().len(), 1);
+
+        app.undo();
+        assert_eq!(app.game.get_move_count(), 0);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let app = App::new(Game::new(), Engine::new(1), None);
+        for (from, to) in [("e2", "e4"), ("e7", "e5"), ("g1", "f3")] {
+            app.game
+                .move_piece_at_position(&position(from), &position(to))
+                .unwrap();
+        }
+        assert_eq!(app.get_move_list(), vec!["  1. e4      e5", "  2. Nf3"]);
+
+        let directory = std::env::temp_dir();
+        let pgn = directory.join("tui-test.pgn").to_string_lossy().to_string();
+        let fen = directory.join("tui-test.fen").to_string_lossy().to_string();
+        app.save(&pgn).unwrap();
+        app.save(&fen).unwrap();
+
+        let mut loaded = App::new(Game::new(), Engine::new(1), None);
+        loaded.load(&pgn).unwrap();
+        assert_eq!(loaded.game.get_moves(), app.game.get_moves());
+
+        loaded.load(&fen).unwrap();
+        assert_eq!(loaded.game.get_move_count(), 0);
+        assert_eq!(loaded.game.get_current_move(), Black);
+        assert_eq!(loaded.get_move_list(), Vec::<String>::new());
+        loaded
+            .game
+            .move_piece_at_position(&position("b8"), &position("c6"))
+            .unwrap();
+        assert_eq!(loaded.get_move_list(), vec!["  2. ...     Nc6"]);
+
+        assert!(loaded.load("/nonexistent/game.pgn").is_err());
+    }
+}
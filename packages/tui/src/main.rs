@@ -0,0 +1,138 @@
+mod app;
+mod view;
+
+use crate::app::App;
+use core::book::Book;
+use core::engine::Engine;
+use core::game::Color::{Black, White};
+use core::game::Game;
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyEventKind};
+use crossterm::style::Print;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+const USAGE: &str = "\
+Usage: tui [--engine <white|black>] [--depth <plies>] [--book <file>] [FILE]
+
+Plays chess in the terminal, without a server: two players at one keyboard, or against the
+built-in engine. FILE is a PGN or FEN file to start from.
+
+Options:
+    --engine <white|black>   The side the engine plays (press 'e' to change during a game)
+    --depth <plies>          The depth the engine searches to (default 3)
+    --book <file>            A Polyglot opening book for the engine to play from";
+
+/// Switches the terminal to raw mode on an alternate screen, and restores it when dropped (even if
+/// the UI panics).
+struct Terminal;
+
+impl Terminal {
+    fn enter() -> io::Result<Terminal> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(Terminal)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<App, String> {
+    let mut engine = Engine::default();
+    let mut engine_color = None;
+    let mut game = Game::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--engine" => {
+                engine_color = match value()?.to_ascii_lowercase().as_str() {
+                    "white" | "w" => Some(White),
+                    "black" | "b" => Some(Black),
+                    color => return Err(format!("invalid color: '{}'", color)),
+                }
+            }
+            "--depth" => {
+                let depth = value()?;
+                engine.depth = depth
+                    .parse()
+                    .map_err(|_| format!("invalid depth: '{}'", depth))?;
+            }
+            "--book" => {
+                let path = value()?;
+                let book = Book::open(path).map_err(|e| format!("cannot load {}: {}", path, e))?;
+                engine.book = Some(Arc::new(book));
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+            path => {
+                let contents = std::fs::read_to_string(path)
+                    .map_err(|e| format!("cannot load {}: {}", path, e))?;
+                game = app::read_game(&contents)
+                    .map_err(|e| format!("cannot load {}: {}", path, e))?;
+            }
+        }
+    }
+
+    Ok(App::new(game, engine, engine_color))
+}
+
+fn run(mut app: App) -> io::Result<()> {
+    let _terminal = Terminal::enter()?;
+    let mut stdout = io::stdout();
+
+    while !app.quit {
+        view::draw(&mut stdout, &app)?;
+
+        if app.is_engine_turn() {
+            queue!(stdout, MoveTo(2, 13), Print("Thinking..."))?;
+            stdout.flush()?;
+            app.play_engine_move();
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key.code);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let app = match parse_args(&args) {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(app) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::app::{App, Prompt};
+use core::moves::Position;
+use crossterm::cursor::MoveTo;
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{queue, QueueableCommand};
+use std::io::{self, Write};
+
+const LIGHT_SQUARE: Color = Color::Rgb {
+    r: 240,
+    g: 217,
+    b: 181,
+};
+const DARK_SQUARE: Color = Color::Rgb {
+    r: 181,
+    g: 136,
+    b: 99,
+};
+const SELECTED_SQUARE: Color = Color::Rgb {
+    r: 246,
+    g: 246,
+    b: 105,
+};
+const TARGET_SQUARE: Color = Color::Rgb {
+    r: 130,
+    g: 170,
+    b: 90,
+};
+const CURSOR_SQUARE: Color = Color::Rgb {
+    r: 90,
+    g: 140,
+    b: 220,
+};
+
+/// The column the move list is drawn from.
+const MOVE_LIST_COLUMN: u16 = 32;
+
+/// The number of moves shown in the move list (the latest are shown once there are more).
+const MOVE_LIST_LINES: usize = 10;
+
+const HELP: &str = "arrows/hjkl: move cursor  enter/space: select  esc: deselect  u: undo  \
+                    f: flip  e: engine side  n: new  s: save  o: open  q: quit";
+
+pub fn draw(out: &mut impl Write, app: &App) -> io::Result<()> {
+    queue!(out, Clear(ClearType::All))?;
+    draw_board(out, app)?;
+
+    // The move list, next to the board.
+    queue!(
+        out,
+        MoveTo(MOVE_LIST_COLUMN, 1),
+        Print(format!("Moves (engine depth {})", app.engine.depth))
+    )?;
+    let moves = app.get_move_list();
+    let start = moves.len().saturating_sub(MOVE_LIST_LINES);
+    for (row, line) in moves[start..].iter().enumerate() {
+        queue!(out, MoveTo(MOVE_LIST_COLUMN, 3 + row as u16), Print(line))?;
+    }
+
+    queue!(out, MoveTo(2, 12), Print(app.get_status()))?;
+    match &app.prompt {
+        Some(Prompt::Save(path)) => {
+            queue!(out, MoveTo(2, 13), Print(format!("Save as: {}", path)))?
+        }
+        Some(Prompt::Load(path)) => queue!(out, MoveTo(2, 13), Print(format!("Open: {}", path)))?,
+        None => {
+            if let Some(message) = &app.message {
+                queue!(out, MoveTo(2, 13), Print(message))?;
+            }
+        }
+    }
+    queue!(out, MoveTo(2, 15), Print(HELP))?;
+
+    out.flush()
+}
+
+fn draw_board(out: &mut impl Write, app: &App) -> io::Result<()> {
+    let targets = app.get_targets();
+    let board = *app.game.board.lock().unwrap();
+
+    for row in 0..8 {
+        let rank = if app.flipped { 7 - row } else { row };
+        queue!(
+            out,
+            MoveTo(0, 1 + row as u16),
+            ResetColor,
+            Print(format!("{} ", 8 - rank))
+        )?;
+
+        for column in 0..board.files() {
+            let file = if app.flipped {
+                board.files() - 1 - column
+            } else {
+                column
+            };
+            let position = Position::new(rank, file);
+
+            let background = if position == app.cursor {
+                CURSOR_SQUARE
+            } else if Some(position) == app.selected {
+                SELECTED_SQUARE
+            } else if targets.contains(&position) {
+                TARGET_SQUARE
+            } else if (rank + file) % 2 == 0 {
+                LIGHT_SQUARE
+            } else {
+                DARK_SQUARE
+            };
+
+            let symbol = board[rank][file].map_or(' ', |piece| piece.unicode_symbol());
+            out.queue(SetBackgroundColor(background))?
+                .queue(SetForegroundColor(Color::Black))?
+                .queue(Print(format!(" {} ", symbol)))?;
+        }
+        queue!(out, ResetColor)?;
+    }
+
+    let files: String = (0..board.files())
+        .map(|column| {
+            let file = if app.flipped {
+                board.files() - 1 - column
+            } else {
+                column
+            };
+            format!(" {} ", char::from(b'a' + file as u8))
+        })
+        .collect();
+    queue!(out, MoveTo(2, 9), Print(files))
+}
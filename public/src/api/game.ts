@@ -39,12 +39,39 @@ export interface Game {
 	 * A map from player color (i.e., 'W' or 'B') to a boolean indicating whether they are currently in check.
 	 */
 	is_player_in_check: Record<Player, boolean>;
+
+	/**
+	 * A map from player color to whether that player's seat has been claimed.
+	 */
+	seats: Record<Player, boolean>;
+
+	/**
+	 * The secret seat tokens held by this client, used to move on behalf of each player.
+	 */
+	tokens: Partial<Record<Player, string>>;
 }
 
-export async function createGame() {
-	return (await axios.put<Game>('/game')).data;
+interface SeatResponse {
+	color: Player;
+	token: string;
+}
+
+export async function claimSeat(game: Game, color: Player) {
+	return (await axios.post<SeatResponse>(`/game/${game.id}/seat/${color}`)).data.token;
+}
+
+export async function createGame(): Promise<Game> {
+	const game = (await axios.put<Game>('/game')).data;
+
+	// Claim both seats so that both sides can be played from this client.
+	const tokens = {
+		W: await claimSeat(game, 'W'),
+		B: await claimSeat(game, 'B'),
+	};
+
+	return { ...game, tokens };
 }
 
-export async function getGame(game: Game) {
-	return (await axios.get<Game>(`/game/${game.id}`)).data;
+export async function getGame(game: Game): Promise<Game> {
+	return { ...(await axios.get<Game>(`/game/${game.id}`)).data, tokens: game.tokens };
 }
@@ -2,5 +2,7 @@ import axios from 'axios';
 import {Game} from './game.ts';
 
 export async function move(game: Game, position: [number, number], newPosition: [number, number]) {
-    await axios.post(`/game/${game.id}/${JSON.stringify(position)}/move`, newPosition);
+    await axios.post(`/game/${game.id}/${JSON.stringify(position)}/move`, newPosition, {
+        headers: { Authorization: `Bearer ${game.tokens[game.current_move]}` },
+    });
 }